@@ -0,0 +1,23 @@
+//! Feeds arbitrary bytes to the parser with every profile, which must return
+//! an error rather than panic on malformed input.
+//!
+//! Run with `cargo +nightly fuzz run parser`, e.g. seeded with the fixtures:
+//! `cargo +nightly fuzz run parser corpus/parser ../tests/fixtures/golden`.
+
+#![no_main]
+
+use cobertura_rs::{Parser, Profile};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    for profile in Profile::ALL {
+        for skip_unknown in [false, true] {
+            let mut parser = Parser::with_profile(profile);
+            parser.set_skip_unknown(skip_unknown);
+
+            let _ = parser.parse_bytes(data);
+            let _ = parser.parse_bytes_lossy(data);
+            let _ = parser.parse_summary(data);
+        }
+    }
+});
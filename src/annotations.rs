@@ -0,0 +1,73 @@
+use std::{collections::BTreeMap, path::Path};
+
+use crate::{Coverage, Line};
+
+// The hits and branch counts of a line.
+type LineCounts = (u64, Option<(usize, usize)>);
+
+/// The coverage of a single line, as an editor would show it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoverageStatus {
+    Covered,
+    Uncovered,
+    /// The line was executed, but only `covered` of its `total` branches
+    /// were taken.
+    PartialBranch {
+        covered: usize,
+        total: usize,
+    },
+}
+
+impl CoverageStatus {
+    /// The status of `line`, which is uncovered if it was hit fewer than
+    /// `min_hits` times, see [`Line::is_covered`].
+    pub fn of(line: &Line, min_hits: u64) -> Self {
+        Self::from_counts(line.hits, line.branch_counts(), min_hits)
+    }
+
+    fn from_counts(hits: u64, branches: Option<(usize, usize)>, min_hits: u64) -> Self {
+        if hits < min_hits.max(1) {
+            return Self::Uncovered;
+        }
+
+        match branches {
+            Some((covered, total)) if covered < total => Self::PartialBranch { covered, total },
+            _ => Self::Covered,
+        }
+    }
+}
+
+impl Coverage {
+    /// The status of every tracked line, ordered by file and line number.
+    ///
+    /// Lines that appear in several classes of the same file are combined:
+    /// the highest hits and covered branch count win.
+    pub fn annotations(&self) -> Vec<(&Path, usize, CoverageStatus)> {
+        let mut lines: BTreeMap<(&Path, usize), LineCounts> = BTreeMap::new();
+
+        for class in self.classes() {
+            for line in &class.lines {
+                let branches = line.branch_counts();
+                let (hits, counts) = lines
+                    .entry((&*class.file_name, line.number))
+                    .or_insert((line.hits, branches));
+
+                *hits = (*hits).max(line.hits);
+                if let (Some((covered, _)), Some((other, _))) = (counts.as_mut(), branches) {
+                    *covered = (*covered).max(other);
+                }
+            }
+        }
+
+        lines
+            .into_iter()
+            .map(|((path, number), (hits, branches))| {
+                (
+                    path,
+                    number,
+                    CoverageStatus::from_counts(hits, branches, self.min_hits),
+                )
+            })
+            .collect()
+    }
+}
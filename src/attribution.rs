@@ -0,0 +1,108 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::Coverage;
+
+/// Which tests cover which lines, built from one coverage report per test.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestAttribution {
+    tests: Vec<String>,
+    // Indices into `tests`, in ascending order.
+    lines: HashMap<(PathBuf, usize), Vec<usize>>,
+}
+
+impl TestAttribution {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a manifest that maps coverage files to test names, one
+    /// `path = test name` pair per line. Empty lines and lines starting with
+    /// `#` are ignored.
+    ///
+    /// Returns the `(path, test name)` pairs, or the (1-based) number of the
+    /// first malformed line.
+    pub fn parse_manifest(manifest: &str) -> Result<Vec<(PathBuf, String)>, usize> {
+        let mut entries = Vec::new();
+
+        for (idx, line) in manifest.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (path, test) = line.split_once('=').ok_or(idx + 1)?;
+            let (path, test) = (path.trim(), test.trim());
+            if path.is_empty() || test.is_empty() {
+                return Err(idx + 1);
+            }
+
+            entries.push((PathBuf::from(path), test.to_string()));
+        }
+
+        Ok(entries)
+    }
+
+    /// Record all lines covered in `coverage`, by its [`Coverage::min_hits`],
+    /// as covered by `test`.
+    pub fn add_test(&mut self, test: impl Into<String>, coverage: &Coverage) {
+        let test = test.into();
+        let idx = match self.tests.iter().position(|t| *t == test) {
+            Some(idx) => idx,
+            None => {
+                self.tests.push(test);
+                self.tests.len() - 1
+            }
+        };
+
+        for class in coverage.classes() {
+            for line in class
+                .lines
+                .iter()
+                .filter(|l| l.is_covered(coverage.min_hits))
+            {
+                let tests = self
+                    .lines
+                    .entry((class.file_name.to_path_buf(), line.number))
+                    .or_default();
+
+                if let Err(pos) = tests.binary_search(&idx) {
+                    tests.insert(pos, idx);
+                }
+            }
+        }
+    }
+
+    /// All test names, in the order they were added.
+    pub fn tests(&self) -> &[String] {
+        &self.tests
+    }
+
+    /// The tests that cover line `line` of `file`.
+    pub fn tests_for_line<'a>(
+        &'a self,
+        file: &Path,
+        line: usize,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        self.lines
+            .get(&(file.to_path_buf(), line))
+            .into_iter()
+            .flatten()
+            .map(|idx| self.tests[*idx].as_str())
+    }
+
+    /// The lines, as `(file, line)`, that are covered by `test`.
+    pub fn lines_for_test<'a>(
+        &'a self,
+        test: &str,
+    ) -> impl Iterator<Item = (&'a Path, usize)> + 'a {
+        let idx = self.tests.iter().position(|t| t == test);
+
+        self.lines
+            .iter()
+            .filter(move |(_, tests)| idx.is_some_and(|idx| tests.contains(&idx)))
+            .map(|((file, line), _)| (file.as_path(), *line))
+    }
+}
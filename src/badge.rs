@@ -0,0 +1,225 @@
+use std::{
+    collections::HashSet,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{unique::unique_rates, Coverage, ReportGenerator, ReportSink, RoundingPolicy};
+
+/// What to create badges for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BadgeScope {
+    /// A single badge for the whole report.
+    Total,
+    /// One badge per package.
+    Packages,
+    /// One badge per path prefix, covering all files below it.
+    PathPrefixes(Vec<String>),
+}
+
+/// The line coverage of a single scope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Badge {
+    /// The file name of the badge, without extension.
+    pub name: String,
+    pub label: String,
+    /// `None` if the scope has no lines.
+    pub line_rate: Option<f64>,
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let name = name.trim_matches('-');
+
+    if name.is_empty() {
+        "root".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn color(rate: Option<f64>) -> &'static str {
+    match rate.map(|r| r * 100.0) {
+        None => "#9f9f9f",
+        Some(p) if p >= 90.0 => "#4c1",
+        Some(p) if p >= 75.0 => "#97ca00",
+        Some(p) if p >= 60.0 => "#dfb317",
+        Some(p) if p >= 40.0 => "#fe7d37",
+        Some(_) => "#e05d44",
+    }
+}
+
+// A rough estimate of the width of `text` in the 11px Verdana that badges
+// use.
+fn text_width(text: &str) -> usize {
+    text.chars().count() * 7 + 10
+}
+
+impl Coverage {
+    /// The badges for `scope`. Badge names are unique.
+    pub fn badges(&self, scope: &BadgeScope) -> Vec<Badge> {
+        let badges: Vec<(String, String, Option<f64>)> = match scope {
+            BadgeScope::Total => vec![(
+                "coverage".to_string(),
+                "coverage".to_string(),
+                self.computed_rates().line_rate,
+            )],
+            BadgeScope::Packages => self
+                .packages
+                .iter()
+                .map(|p| {
+                    let label = if p.name.is_empty() {
+                        "coverage"
+                    } else {
+                        &p.name
+                    };
+                    (
+                        sanitize(&p.name),
+                        label.to_string(),
+                        p.computed_rates(self.min_hits).line_rate,
+                    )
+                })
+                .collect(),
+            BadgeScope::PathPrefixes(prefixes) => prefixes
+                .iter()
+                .map(|prefix| {
+                    let classes = self
+                        .classes()
+                        .filter(|c| c.file_name.starts_with(Path::new(prefix)));
+                    (
+                        sanitize(prefix),
+                        prefix.clone(),
+                        unique_rates(classes, self.min_hits).line_rate,
+                    )
+                })
+                .collect(),
+        };
+
+        let mut names = HashSet::new();
+        badges
+            .into_iter()
+            .map(|(name, label, line_rate)| {
+                let mut unique = name.clone();
+                let mut suffix = 1;
+                while !names.insert(unique.clone()) {
+                    suffix += 1;
+                    unique = format!("{name}-{suffix}");
+                }
+
+                Badge {
+                    name: unique,
+                    label,
+                    line_rate,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Writes SVG coverage badges in the style of shields.io.
+pub struct BadgeGenerator;
+
+impl BadgeGenerator {
+    pub fn write_svg(
+        badge: &Badge,
+        rounding: &RoundingPolicy,
+        mut out: impl Write,
+    ) -> io::Result<()> {
+        let value = badge
+            .line_rate
+            .map(|r| rounding.format_rate(r))
+            .unwrap_or_else(|| "n/a".to_string());
+
+        let label_width = text_width(&badge.label);
+        let value_width = text_width(&value);
+        let width = label_width + value_width;
+        let label = escape(&badge.label);
+
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"20\" role=\"img\" aria-label=\"{label}: {value}\">"
+        )?;
+        writeln!(out, "<title>{label}: {value}</title>")?;
+        writeln!(
+            out,
+            "<rect width=\"{label_width}\" height=\"20\" fill=\"#555\"/>"
+        )?;
+        writeln!(
+            out,
+            "<rect x=\"{label_width}\" width=\"{value_width}\" height=\"20\" fill=\"{}\"/>",
+            color(badge.line_rate)
+        )?;
+        writeln!(
+            out,
+            "<g fill=\"#fff\" text-anchor=\"middle\" font-family=\"Verdana,Geneva,sans-serif\" font-size=\"11\">"
+        )?;
+        writeln!(
+            out,
+            "<text x=\"{}\" y=\"14\">{label}</text>",
+            label_width / 2
+        )?;
+        writeln!(
+            out,
+            "<text x=\"{}\" y=\"14\">{value}</text>",
+            label_width + value_width / 2
+        )?;
+        writeln!(out, "</g>\n</svg>")
+    }
+
+    /// Write one `<name>.svg` per badge of `scope` to `sink`.
+    pub fn generate(
+        coverage: &Coverage,
+        scope: &BadgeScope,
+        rounding: &RoundingPolicy,
+        sink: &mut dyn ReportSink,
+    ) -> io::Result<()> {
+        for badge in coverage.badges(scope) {
+            let mut svg = Vec::new();
+            Self::write_svg(&badge, rounding, &mut svg)?;
+            sink.write(&format!("{}.svg", badge.name), &svg)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The badges of [`BadgeGenerator::generate`] as a [`ReportGenerator`] named
+/// `Badges`, written to the `badges` directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BadgesReport {
+    pub scope: BadgeScope,
+    pub rounding: RoundingPolicy,
+}
+
+impl ReportGenerator for BadgesReport {
+    fn name(&self) -> &str {
+        "Badges"
+    }
+
+    fn generate(&self, coverage: &Coverage, sink: &mut dyn ReportSink) -> io::Result<()> {
+        for badge in coverage.badges(&self.scope) {
+            let mut svg = Vec::new();
+            BadgeGenerator::write_svg(&badge, &self.rounding, &mut svg)?;
+            sink.write(&format!("badges/{}.svg", badge.name), &svg)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use std::collections::HashSet;
+
+use crate::Coverage;
+
+impl Coverage {
+    /// Bring the report into a canonical form, so that reports with the same
+    /// contents are also written the same: [sorted](Self::sort), without
+    /// duplicate sources, and with `condition-coverage` in the usual
+    /// `X% (Y/Z)` form.
+    pub fn canonicalize(&mut self) {
+        self.sort();
+
+        let mut seen = HashSet::new();
+        self.sources
+            .retain(|source| seen.insert(source.path.clone()));
+
+        let lines = self
+            .packages
+            .iter_mut()
+            .flat_map(|p| &mut p.classes)
+            .flat_map(|c| {
+                c.lines
+                    .iter_mut()
+                    .chain(c.methods.iter_mut().flat_map(|m| &mut m.lines))
+            });
+        for line in lines {
+            if let Some((covered, total)) = line.branch_counts() {
+                line.set_branch_counts(covered, total);
+            }
+        }
+    }
+}
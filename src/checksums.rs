@@ -0,0 +1,125 @@
+use std::{
+    collections::BTreeMap,
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::Coverage;
+
+fn sha256_hex(contents: &[u8]) -> String {
+    Sha256::digest(contents)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// The SHA-256 checksums of the source files of a report, by file name as in
+/// the report.
+///
+/// Recorded when a report is generated, they show whether the sources on
+/// disk are still the ones that were covered, before using them for e.g.
+/// [exclusions](crate::Exclusions).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "json",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct SourceChecksums {
+    files: BTreeMap<PathBuf, String>,
+}
+
+/// A source file that doesn't match its recorded checksum.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChecksumMismatch {
+    /// The file has changed since the checksum was recorded.
+    Changed { file: PathBuf },
+    /// The file could not be read anymore.
+    Unreadable { file: PathBuf },
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Changed { file } => write!(
+                f,
+                "Source file {} changed since it was covered",
+                file.display()
+            ),
+            Self::Unreadable { file } => {
+                write!(f, "Source file {} could not be read", file.display())
+            }
+        }
+    }
+}
+
+impl SourceChecksums {
+    /// Compute the checksum of every file of `coverage`. `load_source` is
+    /// called once per distinct file name to load its contents. Files that
+    /// can't be loaded are left out.
+    pub fn compute(
+        coverage: &Coverage,
+        mut load_source: impl FnMut(&Path) -> io::Result<Vec<u8>>,
+    ) -> Self {
+        let mut files = BTreeMap::new();
+
+        for file in coverage.files() {
+            if files.contains_key(file.path) {
+                continue;
+            }
+
+            match load_source(file.path) {
+                Ok(contents) => {
+                    files.insert(file.path.to_path_buf(), sha256_hex(&contents));
+                }
+                Err(e) => log::debug!("Could not read {}: {e}", file.path.display()),
+            }
+        }
+
+        Self { files }
+    }
+
+    /// The checksum of `file`, as a lowercase hex string.
+    pub fn get(&self, file: &Path) -> Option<&str> {
+        self.files.get(file).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Check every recorded file against its contents as loaded by
+    /// `load_source`.
+    pub fn verify(
+        &self,
+        mut load_source: impl FnMut(&Path) -> io::Result<Vec<u8>>,
+    ) -> Vec<ChecksumMismatch> {
+        self.files
+            .iter()
+            .filter_map(|(file, checksum)| match load_source(file) {
+                Ok(contents) if sha256_hex(&contents) == *checksum => None,
+                Ok(_) => Some(ChecksumMismatch::Changed { file: file.clone() }),
+                Err(_) => Some(ChecksumMismatch::Unreadable { file: file.clone() }),
+            })
+            .collect()
+    }
+
+    /// Write the checksums as a JSON object of checksums by file name.
+    #[cfg(feature = "json")]
+    pub fn save(&self, mut out: impl io::Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut out, self).map_err(io::Error::other)?;
+        writeln!(out)
+    }
+
+    /// Load checksums written by [`save`](Self::save).
+    #[cfg(feature = "json")]
+    pub fn load(input: impl io::Read) -> io::Result<Self> {
+        serde_json::from_reader(input).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
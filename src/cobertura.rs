@@ -0,0 +1,259 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use crate::{Class, Coverage, Line, Method, Package};
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            // Would be read back as spaces otherwise.
+            '\t' => escaped.push_str("&#9;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => escaped.push_str("&#13;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Write the unknown attributes of an element, in round-trip mode.
+fn write_extra(
+    out: &mut impl Write,
+    attributes: &BTreeMap<String, String>,
+    round_trip: bool,
+) -> io::Result<()> {
+    if round_trip {
+        for (name, value) in attributes {
+            write!(out, " {name}=\"{}\"", escape(value))?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a report as Cobertura XML, which this crate's parser (and other
+/// Cobertura consumers) can read back.
+pub struct CoberturaGenerator;
+
+/// Where a report written by [`CoberturaGenerator::write_round_trip`] first
+/// differs from the report written after parsing it back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundTripMismatch {
+    /// The 1-based line of the output that differs.
+    pub line: usize,
+    pub written: String,
+    pub reparsed: String,
+}
+
+impl CoberturaGenerator {
+    /// Write the report with only the attributes of the Cobertura DTD.
+    pub fn write(coverage: &Coverage, out: impl Write) -> io::Result<()> {
+        Self::write_with(coverage, false, out)
+    }
+
+    /// Write the report including the [extra attributes](Coverage::extra_attributes)
+    /// of each element, so that parsing the output gives the same report
+    /// again. Use this to pass reports through unchanged, e.g. as a filter in
+    /// a pipeline.
+    pub fn write_round_trip(coverage: &Coverage, out: impl Write) -> io::Result<()> {
+        Self::write_with(coverage, true, out)
+    }
+
+    /// Parse `input`, write it with [`write_round_trip`](Self::write_round_trip),
+    /// parse that again and check that both reports are written the same.
+    /// Returns the first difference, if any.
+    #[cfg(feature = "parser")]
+    pub fn check_round_trip(input: &[u8]) -> Result<Option<RoundTripMismatch>, crate::ParserError> {
+        let mut written = Vec::new();
+        let coverage = crate::Parser::new().parse_bytes(input)?;
+        Self::write_round_trip(&coverage, &mut written).expect("writing to a Vec can't fail");
+
+        let mut reparsed = Vec::new();
+        let coverage = crate::Parser::new().parse_bytes(&written)?;
+        Self::write_round_trip(&coverage, &mut reparsed).expect("writing to a Vec can't fail");
+
+        let written = String::from_utf8_lossy(&written);
+        let reparsed = String::from_utf8_lossy(&reparsed);
+        let mut written_lines = written.lines();
+        let mut reparsed_lines = reparsed.lines();
+
+        for line in 1.. {
+            match (written_lines.next(), reparsed_lines.next()) {
+                (None, None) => return Ok(None),
+                (a, b) if a == b => {}
+                (a, b) => {
+                    return Ok(Some(RoundTripMismatch {
+                        line,
+                        written: a.unwrap_or_default().to_string(),
+                        reparsed: b.unwrap_or_default().to_string(),
+                    }))
+                }
+            }
+        }
+
+        unreachable!()
+    }
+
+    fn write_with(coverage: &Coverage, round_trip: bool, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "<?xml version=\"1.0\" ?>")?;
+        writeln!(
+            out,
+            "<!DOCTYPE coverage SYSTEM \"http://cobertura.sourceforge.net/xml/coverage-04.dtd\">"
+        )?;
+
+        write!(
+            out,
+            "<coverage line-rate=\"{}\" branch-rate=\"{}\" lines-covered=\"{}\" lines-valid=\"{}\" branches-covered=\"{}\" branches-valid=\"{}\" complexity=\"{}\"",
+            coverage.line_rate,
+            coverage.branch_rate,
+            coverage.lines_covered,
+            coverage.lines_valid,
+            coverage.branches_covered,
+            coverage.branches_valid,
+            coverage.complexity,
+        )?;
+        if !coverage.version.is_empty() {
+            write!(out, " version=\"{}\"", escape(&coverage.version))?;
+        }
+        write!(out, " timestamp=\"{}\"", coverage.timestamp)?;
+        write_extra(&mut out, &coverage.extra_attributes, round_trip)?;
+        writeln!(out, ">")?;
+
+        if !coverage.sources.is_empty() {
+            writeln!(out, "\t<sources>")?;
+            for source in &coverage.sources {
+                writeln!(
+                    out,
+                    "\t\t<source>{}</source>",
+                    escape(&source.path.to_string_lossy())
+                )?;
+            }
+            writeln!(out, "\t</sources>")?;
+        }
+
+        writeln!(out, "\t<packages>")?;
+        for package in &coverage.packages {
+            Self::write_package(&mut out, package, round_trip)?;
+        }
+        writeln!(out, "\t</packages>")?;
+
+        writeln!(out, "</coverage>")
+    }
+
+    fn write_package(out: &mut impl Write, package: &Package, round_trip: bool) -> io::Result<()> {
+        write!(
+            out,
+            "\t\t<package name=\"{}\" line-rate=\"{}\" branch-rate=\"{}\" complexity=\"{}\"",
+            escape(&package.name),
+            package.line_rate,
+            package.branch_rate,
+            package.complexity
+        )?;
+        write_extra(out, &package.extra_attributes, round_trip)?;
+        writeln!(out, ">")?;
+
+        writeln!(out, "\t\t\t<classes>")?;
+        for class in &package.classes {
+            Self::write_class(out, class, round_trip)?;
+        }
+        writeln!(out, "\t\t\t</classes>")?;
+
+        writeln!(out, "\t\t</package>")
+    }
+
+    fn write_class(out: &mut impl Write, class: &Class, round_trip: bool) -> io::Result<()> {
+        write!(
+            out,
+            "\t\t\t\t<class name=\"{}\" filename=\"{}\" line-rate=\"{}\" branch-rate=\"{}\" complexity=\"{}\"",
+            escape(&class.name),
+            escape(&class.file_name.to_string_lossy()),
+            class.line_rate,
+            class.branch_rate,
+            class.complexity
+        )?;
+        write_extra(out, &class.extra_attributes, round_trip)?;
+        writeln!(out, ">")?;
+
+        if class.methods.is_empty() {
+            writeln!(out, "\t\t\t\t\t<methods/>")?;
+        } else {
+            writeln!(out, "\t\t\t\t\t<methods>")?;
+            for method in &class.methods {
+                Self::write_method(out, method, round_trip)?;
+            }
+            writeln!(out, "\t\t\t\t\t</methods>")?;
+        }
+
+        Self::write_lines(out, &class.lines, "\t\t\t\t\t", round_trip)?;
+
+        writeln!(out, "\t\t\t\t</class>")
+    }
+
+    fn write_method(out: &mut impl Write, method: &Method, round_trip: bool) -> io::Result<()> {
+        // The DTD requires a signature, so a missing one is written as empty.
+        write!(
+            out,
+            "\t\t\t\t\t\t<method name=\"{}\" signature=\"{}\" line-rate=\"{}\" branch-rate=\"{}\" complexity=\"{}\"",
+            escape(&method.name),
+            escape(method.signature.as_deref().unwrap_or_default()),
+            method.line_rate,
+            method.branch_rate,
+            method.complexity
+        )?;
+        write_extra(out, &method.extra_attributes, round_trip)?;
+        writeln!(out, ">")?;
+        Self::write_lines(out, &method.lines, "\t\t\t\t\t\t\t", round_trip)?;
+        writeln!(out, "\t\t\t\t\t\t</method>")
+    }
+
+    fn write_lines(
+        out: &mut impl Write,
+        lines: &[Line],
+        indent: &str,
+        round_trip: bool,
+    ) -> io::Result<()> {
+        // Methods don't accept an empty `<lines/>`, so always write both tags.
+        writeln!(out, "{indent}<lines>")?;
+
+        for line in lines {
+            write!(
+                out,
+                "{indent}\t<line number=\"{}\" hits=\"{}\" branch=\"{}\"",
+                line.number, line.hits, line.branch
+            )?;
+            if let Some(coverage) = &line.condition_coverage {
+                write!(out, " condition-coverage=\"{}\"", escape(coverage))?;
+            }
+            write_extra(out, &line.extra_attributes, round_trip)?;
+
+            if line.conditions.is_empty() {
+                writeln!(out, "/>")?;
+                continue;
+            }
+
+            writeln!(out, ">")?;
+            writeln!(out, "{indent}\t\t<conditions>")?;
+            for condition in &line.conditions {
+                write!(
+                    out,
+                    "{indent}\t\t\t<condition number=\"{}\" type=\"{}\" coverage=\"{}\"",
+                    condition.number,
+                    escape(condition.r#type.as_str()),
+                    escape(&condition.coverage)
+                )?;
+                write_extra(out, &condition.extra_attributes, round_trip)?;
+                writeln!(out, "/>")?;
+            }
+            writeln!(out, "{indent}\t\t</conditions>")?;
+            writeln!(out, "{indent}\t</line>")?;
+        }
+
+        writeln!(out, "{indent}</lines>")
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{Class, Coverage, Method, Package};
+
+// Whether two floats differ by at most `tolerance`. `NaN`s are equal to each
+// other, so that reports with the same odd values compare equal.
+fn close(a: f64, b: f64, tolerance: f64) -> bool {
+    a == b || (a - b).abs() <= tolerance || (a.is_nan() && b.is_nan())
+}
+
+fn methods_equal(a: &Method, b: &Method, tolerance: f64) -> bool {
+    let Method {
+        lines,
+        name,
+        signature,
+        line_rate,
+        branch_rate,
+        complexity,
+        extra_attributes,
+    } = a;
+
+    *lines == b.lines
+        && *name == b.name
+        && *signature == b.signature
+        && close(*line_rate, b.line_rate, tolerance)
+        && close(*branch_rate, b.branch_rate, tolerance)
+        && close(*complexity, b.complexity, tolerance)
+        && *extra_attributes == b.extra_attributes
+}
+
+fn classes_equal(a: &Class, b: &Class, tolerance: f64) -> bool {
+    let Class {
+        methods,
+        lines,
+        name,
+        file_name,
+        line_rate,
+        branch_rate,
+        complexity,
+        extra_attributes,
+    } = a;
+
+    methods.len() == b.methods.len()
+        && methods
+            .iter()
+            .zip(&b.methods)
+            .all(|(a, b)| methods_equal(a, b, tolerance))
+        && *lines == b.lines
+        && *name == b.name
+        && *file_name == b.file_name
+        && close(*line_rate, b.line_rate, tolerance)
+        && close(*branch_rate, b.branch_rate, tolerance)
+        && close(*complexity, b.complexity, tolerance)
+        && *extra_attributes == b.extra_attributes
+}
+
+fn packages_equal(a: &Package, b: &Package, tolerance: f64) -> bool {
+    let Package {
+        classes,
+        name,
+        line_rate,
+        branch_rate,
+        complexity,
+        project,
+        extra_attributes,
+    } = a;
+
+    classes.len() == b.classes.len()
+        && classes
+            .iter()
+            .zip(&b.classes)
+            .all(|(a, b)| classes_equal(a, b, tolerance))
+        && *name == b.name
+        && close(*line_rate, b.line_rate, tolerance)
+        && close(*branch_rate, b.branch_rate, tolerance)
+        && close(*complexity, b.complexity, tolerance)
+        && *project == b.project
+        && *extra_attributes == b.extra_attributes
+}
+
+impl Coverage {
+    /// Whether `self` and `other` have the same contents, regardless of the
+    /// order of their elements and of the form of `condition-coverage` (see
+    /// [`canonicalize`](Self::canonicalize)), and allowing rates and
+    /// complexities to differ by up to `tolerance`.
+    ///
+    /// Meant for tests, where `==` is too strict for reports that went
+    /// through a merge or a writer.
+    pub fn structurally_equal(&self, other: &Coverage, tolerance: f64) -> bool {
+        let mut a = self.clone();
+        let mut b = other.clone();
+        for coverage in [&mut a, &mut b] {
+            coverage.canonicalize();
+            coverage.sources.sort_by(|a, b| a.path.cmp(&b.path));
+        }
+
+        let Coverage {
+            sources,
+            packages,
+            line_rate,
+            branch_rate,
+            lines_covered,
+            lines_valid,
+            branches_covered,
+            branches_valid,
+            complexity,
+            version,
+            timestamp,
+            extra_attributes,
+            min_hits,
+        } = &a;
+
+        *sources == b.sources
+            && packages.len() == b.packages.len()
+            && packages
+                .iter()
+                .zip(&b.packages)
+                .all(|(a, b)| packages_equal(a, b, tolerance))
+            && close(*line_rate, b.line_rate, tolerance)
+            && close(*branch_rate, b.branch_rate, tolerance)
+            && *lines_covered == b.lines_covered
+            && *lines_valid == b.lines_valid
+            && *branches_covered == b.branches_covered
+            && *branches_valid == b.branches_valid
+            && close(*complexity, b.complexity, tolerance)
+            && *version == b.version
+            && *timestamp == b.timestamp
+            && (*min_hits).max(1) == b.min_hits.max(1)
+            && *extra_attributes == b.extra_attributes
+    }
+}
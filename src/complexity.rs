@@ -0,0 +1,253 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use crate::{Class, Coverage, Method, RankingFilter};
+
+// Keywords that introduce a decision point in most C-like languages and in
+// Python.
+const DECISION_KEYWORDS: &[&str] = &[
+    "if", "elif", "for", "foreach", "while", "case", "catch", "except", "and", "or",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Language {
+    Rust,
+    Python,
+    Other,
+}
+
+impl Language {
+    fn of(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("rs") => Self::Rust,
+            Some("py") => Self::Python,
+            _ => Self::Other,
+        }
+    }
+}
+
+// Remove string literals and comments, so that their contents don't count
+// as decision points. This is a heuristic: it does not handle block comments
+// or multi-line strings.
+fn strip_line(line: &str, language: Language) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    let mut quote: Option<char> = None;
+
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            if c == '\\' {
+                chars.next();
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+
+        match c {
+            '"' => quote = Some('"'),
+            '\'' if language == Language::Python => quote = Some('\''),
+            '/' if chars.peek() == Some(&'/') => break,
+            '#' if language == Language::Python => break,
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+fn decision_points(line: &str, language: Language) -> usize {
+    let line = strip_line(line, language);
+
+    let keywords = line
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| DECISION_KEYWORDS.contains(word))
+        .count();
+
+    let operators = line.matches("&&").count() + line.matches("||").count();
+
+    let extra = match language {
+        // Every match arm is a path. This over-counts by one per `match`,
+        // which makes up for `match` itself not being counted.
+        Language::Rust => line.matches("=>").count(),
+        Language::Python => 0,
+        // The ternary operator, but not `?.` or `??`.
+        Language::Other => line
+            .match_indices('?')
+            .filter(|(idx, _)| {
+                let next = line[idx + 1..].chars().next();
+                let previous = line[..*idx].chars().next_back();
+                !matches!(next, Some('.' | '?')) && previous != Some('?')
+            })
+            .count(),
+    };
+
+    keywords + operators + extra
+}
+
+/// Estimate the cyclomatic complexity of the code on `lines` (1-based,
+/// inclusive) of `source`, as one plus the number of decision points.
+///
+/// This is a language-agnostic heuristic based on keywords and operators, not
+/// a real parse, so it is only meant to fill in for producers that don't
+/// report complexity at all.
+pub fn estimate_complexity(source: &str, path: &Path, lines: RangeInclusive<usize>) -> f64 {
+    let language = Language::of(path);
+
+    let points: usize = source
+        .lines()
+        .enumerate()
+        .filter(|(idx, _)| lines.contains(&(idx + 1)))
+        .map(|(_, line)| decision_points(line, language))
+        .sum();
+
+    (1 + points) as f64
+}
+
+fn line_range(lines: &[crate::Line]) -> Option<RangeInclusive<usize>> {
+    let min = lines.iter().map(|l| l.number).min()?;
+    let max = lines.iter().map(|l| l.number).max()?;
+    Some(min..=max)
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComplexityOutcome {
+    /// The number of methods whose complexity was set.
+    pub methods: usize,
+    /// The number of classes whose complexity was set.
+    pub classes: usize,
+    /// Files that could not be loaded, and were left untouched.
+    pub unreadable: Vec<PathBuf>,
+}
+
+impl Coverage {
+    /// Estimate complexities from source with [`estimate_complexity`].
+    ///
+    /// Methods get the complexity of their line range, classes the mean of
+    /// their methods (or of their whole line range if they have none), and
+    /// packages and the report the mean of their children, as Cobertura
+    /// does. Existing non-zero values are only replaced if `overwrite` is
+    /// set.
+    ///
+    /// `load_source` is called once per distinct file name to load its
+    /// contents.
+    pub fn compute_complexity(
+        &mut self,
+        overwrite: bool,
+        mut load_source: impl FnMut(&Path) -> io::Result<String>,
+    ) -> ComplexityOutcome {
+        let mut sources: HashMap<PathBuf, Option<String>> = HashMap::new();
+        let mut outcome = ComplexityOutcome::default();
+        let should_set = |value: f64| overwrite || value == 0.0;
+
+        for package in &mut self.packages {
+            for class in &mut package.classes {
+                let source = sources
+                    .entry(class.file_name.to_path_buf())
+                    .or_insert_with(|| match load_source(&class.file_name) {
+                        Ok(source) => Some(source),
+                        Err(_) => {
+                            outcome.unreadable.push(class.file_name.to_path_buf());
+                            None
+                        }
+                    });
+
+                let Some(source) = source else {
+                    continue;
+                };
+
+                compute_class_complexity(class, source, should_set, &mut outcome);
+            }
+
+            if should_set(package.complexity) {
+                if let Some(complexity) = mean(package.classes.iter().map(|c| c.complexity)) {
+                    package.complexity = complexity;
+                }
+            }
+        }
+
+        if should_set(self.complexity) {
+            if let Some(complexity) = mean(self.packages.iter().map(|p| p.complexity)) {
+                self.complexity = complexity;
+            }
+        }
+
+        outcome
+    }
+}
+
+fn compute_class_complexity(
+    class: &mut Class,
+    source: &str,
+    should_set: impl Fn(f64) -> bool,
+    outcome: &mut ComplexityOutcome,
+) {
+    for method in &mut class.methods {
+        if !should_set(method.complexity) {
+            continue;
+        }
+
+        if let Some(range) = line_range(&method.lines) {
+            method.complexity = estimate_complexity(source, &class.file_name, range);
+            outcome.methods += 1;
+        }
+    }
+
+    if !should_set(class.complexity) {
+        return;
+    }
+
+    let complexity = mean(class.methods.iter().map(|m| m.complexity)).or_else(|| {
+        line_range(&class.lines).map(|range| estimate_complexity(source, &class.file_name, range))
+    });
+
+    if let Some(complexity) = complexity {
+        class.complexity = complexity;
+        outcome.classes += 1;
+    }
+}
+
+impl Method {
+    /// The CRAP (Change Risk Anti-Patterns) score of this method:
+    /// `complexity² × (1 − coverage)³ + complexity`, with the line rate as
+    /// coverage.
+    pub fn crap_score(&self) -> f64 {
+        let uncovered = 1.0 - self.line_rate.clamp(0.0, 1.0);
+        self.complexity.powi(2) * uncovered.powi(3) + self.complexity
+    }
+}
+
+impl Coverage {
+    /// All methods with their class, ordered by descending CRAP score.
+    pub fn crap_ranking(&self) -> Vec<(&Class, &Method, f64)> {
+        self.crap_ranking_with(&RankingFilter::default())
+    }
+
+    /// Like [`Coverage::crap_ranking`], leaving out the methods of the files
+    /// that `filter` leaves out.
+    pub fn crap_ranking_with(&self, filter: &RankingFilter) -> Vec<(&Class, &Method, f64)> {
+        let ranked: HashSet<_> = self
+            .ranked_files(filter)
+            .into_iter()
+            .map(|file| file.path)
+            .collect();
+
+        let mut ranking: Vec<_> = self
+            .classes()
+            .filter(|c| ranked.contains(&*c.file_name))
+            .flat_map(|c| c.methods.iter().map(move |m| (c, m, m.crap_score())))
+            .collect();
+
+        ranking.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+        ranking
+    }
+}
@@ -0,0 +1,99 @@
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+};
+
+use crate::{Coverage, ReportGenerator, ReportSink};
+
+fn escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+pub struct CsvGenerator;
+
+impl CsvGenerator {
+    /// Write one row per class.
+    pub fn write_classes(coverage: &Coverage, mut out: impl Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "package,class,file,lines_valid,lines_covered,line_rate,branch_rate,complexity"
+        )?;
+
+        for package in &coverage.packages {
+            for class in &package.classes {
+                let lines_valid = class.lines.len();
+                let lines_covered = class
+                    .lines
+                    .iter()
+                    .filter(|l| l.is_covered(coverage.min_hits))
+                    .count();
+
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{}",
+                    escape(&package.name),
+                    escape(&class.name),
+                    escape(&class.file_name.to_string_lossy()),
+                    lines_valid,
+                    lines_covered,
+                    class.line_rate,
+                    class.branch_rate,
+                    class.complexity
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write one row per line.
+    pub fn write_lines(coverage: &Coverage, mut out: impl Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "package,class,file,line,hits,branch,condition_coverage"
+        )?;
+
+        for package in &coverage.packages {
+            for class in &package.classes {
+                let file_name = class.file_name.to_string_lossy();
+
+                for line in &class.lines {
+                    writeln!(
+                        out,
+                        "{},{},{},{},{},{},{}",
+                        escape(&package.name),
+                        escape(&class.name),
+                        escape(&file_name),
+                        line.number,
+                        line.hits,
+                        line.branch,
+                        escape(line.condition_coverage.as_deref().unwrap_or(""))
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The classes of [`CsvGenerator::write_classes`] as a [`ReportGenerator`]
+/// named `Csv`, written to `coverage.csv`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CsvReport;
+
+impl ReportGenerator for CsvReport {
+    fn name(&self) -> &str {
+        "Csv"
+    }
+
+    fn generate(&self, coverage: &Coverage, sink: &mut dyn ReportSink) -> io::Result<()> {
+        let mut csv = Vec::new();
+        CsvGenerator::write_classes(coverage, &mut csv)?;
+        sink.write("coverage.csv", &csv)
+    }
+}
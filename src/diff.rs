@@ -0,0 +1,270 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{match_moved_classes, uncovered::uncovered_ranges, Class, Coverage, UncoveredFile};
+
+/// A rate in two reports. It is `None` on the side where the package or
+/// class does not exist.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RateChange {
+    pub before: Option<f64>,
+    pub after: Option<f64>,
+}
+
+impl RateChange {
+    fn new(before: Option<f64>, after: Option<f64>) -> Self {
+        Self { before, after }
+    }
+
+    /// `after - before`, if the rate exists in both reports.
+    pub fn delta(&self) -> Option<f64> {
+        Some(self.after? - self.before?)
+    }
+}
+
+/// Where a class was in the report before it was renamed or moved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovedClass {
+    pub package: Arc<str>,
+    pub name: Arc<str>,
+    pub file_name: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassDiff {
+    pub name: Arc<str>,
+    pub file_name: PathBuf,
+    pub line_rate: RateChange,
+    pub branch_rate: RateChange,
+    /// The class in `before` that this class was renamed or moved from, see
+    /// [`crate::match_moved_classes`]. The rates before are those of that
+    /// class.
+    pub moved_from: Option<MovedClass>,
+}
+
+impl ClassDiff {
+    /// Whether the class was moved or renamed without its coverage
+    /// changing.
+    pub fn is_moved_unchanged(&self) -> bool {
+        self.moved_from.is_some()
+            && self.line_rate.before == self.line_rate.after
+            && self.branch_rate.before == self.branch_rate.after
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageDiff {
+    pub name: Arc<str>,
+    pub line_rate: RateChange,
+    pub branch_rate: RateChange,
+    pub classes: Vec<ClassDiff>,
+}
+
+/// The differences between two reports of the same code base.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageDiff {
+    pub line_rate: RateChange,
+    pub branch_rate: RateChange,
+    /// All packages of both reports, in the order of `after` followed by the
+    /// packages that were removed. Classes are ordered the same way. Classes
+    /// that were renamed or moved only appear in `after`.
+    pub packages: Vec<PackageDiff>,
+    /// Lines that are uncovered in `after`, but were covered (or did not
+    /// exist) in `before`. Lines are matched by file name and line number,
+    /// following files that were moved.
+    pub newly_uncovered: Vec<UncoveredFile>,
+}
+
+impl CoverageDiff {
+    pub fn new(before: &Coverage, after: &Coverage) -> Self {
+        let mut packages: Vec<PackageDiff> = Vec::new();
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        // The files of `after` that classes were moved to, mapped to the
+        // files of `before` they were moved from.
+        let mut moved_files = HashMap::new();
+        // The classes only in `before`, with their package and position in
+        // `packages`.
+        let mut removed = Vec::new();
+
+        for package in &after.packages {
+            positions.insert(&package.name, packages.len());
+            packages.push(PackageDiff {
+                name: package.name.clone(),
+                line_rate: RateChange::new(None, Some(package.line_rate)),
+                branch_rate: RateChange::new(None, Some(package.branch_rate)),
+                classes: package
+                    .classes
+                    .iter()
+                    .map(|class| class_diff(None, Some(class)))
+                    .collect(),
+            });
+        }
+
+        for package in &before.packages {
+            let Some(&idx) = positions.get(&*package.name) else {
+                for (class_idx, class) in package.classes.iter().enumerate() {
+                    removed.push((package, class, (packages.len(), class_idx)));
+                }
+                packages.push(PackageDiff {
+                    name: package.name.clone(),
+                    line_rate: RateChange::new(Some(package.line_rate), None),
+                    branch_rate: RateChange::new(Some(package.branch_rate), None),
+                    classes: package
+                        .classes
+                        .iter()
+                        .map(|class| class_diff(Some(class), None))
+                        .collect(),
+                });
+                continue;
+            };
+
+            let diff = &mut packages[idx];
+            diff.line_rate.before = Some(package.line_rate);
+            diff.branch_rate.before = Some(package.branch_rate);
+
+            for class in &package.classes {
+                match diff.classes.iter_mut().find(|c| c.name == class.name) {
+                    Some(existing) => {
+                        existing.line_rate.before = Some(class.line_rate);
+                        existing.branch_rate.before = Some(class.branch_rate);
+
+                        if existing.file_name != *class.file_name {
+                            moved_files
+                                .insert(existing.file_name.clone(), class.file_name.to_path_buf());
+                            existing.moved_from = Some(MovedClass {
+                                package: package.name.clone(),
+                                name: class.name.clone(),
+                                file_name: class.file_name.to_path_buf(),
+                            });
+                        }
+                    }
+                    None => {
+                        removed.push((package, class, (idx, diff.classes.len())));
+                        diff.classes.push(class_diff(Some(class), None));
+                    }
+                }
+            }
+        }
+
+        // The classes only in `after`. The packages of `after` come first,
+        // in the same order and with their classes in the same order.
+        let added: Vec<_> = after
+            .packages
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, package)| {
+                package
+                    .classes
+                    .iter()
+                    .enumerate()
+                    .map(move |(class_idx, class)| (class, (idx, class_idx)))
+            })
+            .filter(|(_, (idx, class_idx))| {
+                packages[*idx].classes[*class_idx]
+                    .line_rate
+                    .before
+                    .is_none()
+            })
+            .collect();
+
+        let moves = match_moved_classes(
+            &removed.iter().map(|(_, c, _)| *c).collect::<Vec<_>>(),
+            &added.iter().map(|(c, _)| *c).collect::<Vec<_>>(),
+        );
+
+        let mut moved = HashSet::new();
+        for (r, a) in moves {
+            let (package, from, removed_idx) = removed[r];
+            let (to, (idx, class_idx)) = added[a];
+
+            let diff = &mut packages[idx].classes[class_idx];
+            diff.line_rate.before = Some(from.line_rate);
+            diff.branch_rate.before = Some(from.branch_rate);
+            diff.moved_from = Some(MovedClass {
+                package: package.name.clone(),
+                name: from.name.clone(),
+                file_name: from.file_name.to_path_buf(),
+            });
+
+            if from.file_name != to.file_name {
+                moved_files.insert(to.file_name.to_path_buf(), from.file_name.to_path_buf());
+            }
+            moved.insert(removed_idx);
+        }
+
+        // Drop the classes that were moved from their old place, and the
+        // packages of `before` that only had such classes.
+        for (idx, package) in packages.iter_mut().enumerate() {
+            let mut class_idx = 0;
+            package.classes.retain(|_| {
+                class_idx += 1;
+                !moved.contains(&(idx, class_idx - 1))
+            });
+        }
+        packages.retain(|p| p.line_rate.after.is_some() || !p.classes.is_empty());
+
+        Self {
+            line_rate: RateChange::new(Some(before.line_rate), Some(after.line_rate)),
+            branch_rate: RateChange::new(Some(before.branch_rate), Some(after.branch_rate)),
+            packages,
+            newly_uncovered: newly_uncovered(before, after, &moved_files),
+        }
+    }
+}
+
+fn class_diff(before: Option<&Class>, after: Option<&Class>) -> ClassDiff {
+    let class = after.or(before).expect("a class on either side");
+
+    ClassDiff {
+        name: class.name.clone(),
+        file_name: class.file_name.to_path_buf(),
+        line_rate: RateChange::new(before.map(|c| c.line_rate), after.map(|c| c.line_rate)),
+        branch_rate: RateChange::new(before.map(|c| c.branch_rate), after.map(|c| c.branch_rate)),
+        moved_from: None,
+    }
+}
+
+// Whether every line is covered, by file name.
+fn covered_by_file(coverage: &Coverage) -> BTreeMap<&Path, BTreeMap<usize, bool>> {
+    let mut files: BTreeMap<&Path, BTreeMap<usize, bool>> = BTreeMap::new();
+
+    for (file, line) in coverage.unique_lines() {
+        files
+            .entry(file)
+            .or_default()
+            .insert(line.number, line.is_covered(coverage.min_hits));
+    }
+
+    files
+}
+
+// `moved_files` maps the files of `after` to the files of `before` they were
+// moved from.
+fn newly_uncovered(
+    before: &Coverage,
+    after: &Coverage,
+    moved_files: &HashMap<PathBuf, PathBuf>,
+) -> Vec<UncoveredFile> {
+    let before = covered_by_file(before);
+
+    covered_by_file(after)
+        .into_iter()
+        .filter_map(|(path, covered)| {
+            let previous = before.get(moved_files.get(path).map_or(path, |p| p.as_path()));
+            let ranges = uncovered_ranges(covered.into_iter().map(|(number, covered)| {
+                let was_uncovered = previous
+                    .and_then(|p| p.get(&number))
+                    .is_some_and(|covered| !covered);
+                (number, !covered && !was_uncovered)
+            }));
+
+            (!ranges.is_empty()).then(|| UncoveredFile {
+                path: path.to_path_buf(),
+                ranges,
+            })
+        })
+        .collect()
+}
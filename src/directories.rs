@@ -0,0 +1,95 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{unique::unique_rates, ComputedRates, Coverage, FileSummary};
+
+/// The coverage of the files in a directory and all of its subdirectories.
+#[derive(Debug, Clone)]
+pub struct DirSummary<'a> {
+    pub path: PathBuf,
+    pub rates: ComputedRates,
+    /// The subdirectories, sorted by path.
+    pub directories: Vec<DirSummary<'a>>,
+    /// The files directly in this directory, sorted by path.
+    pub files: Vec<FileSummary<'a>>,
+}
+
+impl DirSummary<'_> {
+    /// The name of this directory relative to its parent directory `parent`.
+    pub fn name(&self, parent: &Path) -> String {
+        self.path
+            .strip_prefix(parent)
+            .unwrap_or(&self.path)
+            .display()
+            .to_string()
+    }
+}
+
+impl Coverage {
+    /// The coverage of each source directory, regardless of the packages
+    /// that the files belong to.
+    ///
+    /// The root is the deepest directory that contains all files.
+    pub fn by_directory(&self) -> DirSummary<'_> {
+        let files: Vec<_> = self.files().collect();
+        let root = common_directory(files.iter().map(|f| f.path));
+
+        summarize(root, files, self.min_hits)
+    }
+}
+
+fn common_directory<'a>(paths: impl Iterator<Item = &'a Path>) -> PathBuf {
+    let mut common: Option<PathBuf> = None;
+
+    for path in paths {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        common = Some(match common {
+            None => dir.to_path_buf(),
+            Some(common) => common
+                .components()
+                .zip(dir.components())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+
+    common.unwrap_or_default()
+}
+
+fn summarize(path: PathBuf, files: Vec<FileSummary<'_>>, min_hits: u64) -> DirSummary<'_> {
+    let rates = unique_rates(
+        files.iter().flat_map(|f| f.classes.iter().copied()),
+        min_hits,
+    );
+
+    let mut direct = Vec::new();
+    let mut subdirectories: BTreeMap<PathBuf, Vec<FileSummary>> = BTreeMap::new();
+
+    for file in files {
+        let relative = file.path.strip_prefix(&path).unwrap_or(file.path);
+        let mut components = relative.components();
+
+        match (components.next(), components.next()) {
+            (Some(directory), Some(_)) => subdirectories
+                .entry(path.join(directory))
+                .or_default()
+                .push(file),
+            _ => direct.push(file),
+        }
+    }
+
+    direct.sort_by(|a, b| a.path.cmp(b.path));
+
+    DirSummary {
+        path,
+        rates,
+        directories: subdirectories
+            .into_iter()
+            .map(|(path, files)| summarize(path, files, min_hits))
+            .collect(),
+        files: direct,
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use quick_xml::events::{BytesEnd, BytesStart};
 
 use crate::FilteredEvent;
@@ -25,6 +27,73 @@ pub enum ParserError {
     },
     MissingRequiredAttribute(String),
     UnexpectedEof,
+    /// An event was consumed after the end of the document.
+    EventAfterEnd,
+    /// The input is not well-formed XML, or could not be read.
+    Xml(quick_xml::Error),
+    /// Parsing was cancelled through the [`Progress`](crate::Progress) of
+    /// the parser.
+    Cancelled,
+}
+
+impl From<quick_xml::Error> for ParserError {
+    fn from(value: quick_xml::Error) -> Self {
+        Self::Xml(value)
+    }
+}
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elements = |names: &[String], open: &str| {
+            names
+                .iter()
+                .map(|n| format!("<{open}{n}>"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match self {
+            Self::ExpectedStart { got, expected } => {
+                write!(f, "expected one of {}, got {got}", elements(expected, ""))
+            }
+            Self::ExpectedEnd { got, expected } => {
+                write!(f, "expected one of {}, got {got}", elements(expected, "/"))
+            }
+            Self::ExpectedStartOrEnd {
+                got,
+                expected_starts,
+                expected_ends,
+            } => {
+                let expected: Vec<_> =
+                    [elements(expected_starts, ""), elements(expected_ends, "/")]
+                        .into_iter()
+                        .filter(|e| !e.is_empty())
+                        .collect();
+                write!(f, "expected one of {}, got {got}", expected.join(", "))
+            }
+            Self::UnexpectedValue(value) => write!(f, "unexpected value `{value}`"),
+            Self::FailedToParseAttribute => write!(f, "failed to parse an attribute"),
+            Self::InvalidValueForAttribute { name, value } => {
+                write!(f, "invalid value `{value}` for attribute `{name}`")
+            }
+            Self::MissingRequiredAttribute(name) => {
+                write!(f, "missing required attribute `{name}`")
+            }
+            Self::UnexpectedEof => write!(f, "unexpected end of the document"),
+            Self::EventAfterEnd => write!(f, "unexpected content after the end of the document"),
+            Self::Xml(e) => write!(f, "invalid XML: {e}"),
+            Self::Cancelled => write!(f, "parsing was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for ParserError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Xml(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 impl ParserError {
@@ -45,7 +114,7 @@ impl ParserError {
         got: impl Into<BasicEvent>,
         expected: impl IntoIterator<Item = impl AsRef<str>>,
     ) -> Self {
-        Self::ExpectedStart {
+        Self::ExpectedEnd {
             got: got.into(),
             expected: expected
                 .into_iter()
@@ -81,6 +150,17 @@ pub enum BasicEvent {
     Text,
 }
 
+impl fmt::Display for BasicEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Start(name) => write!(f, "<{name}>"),
+            Self::End(name) => write!(f, "</{name}>"),
+            Self::Empty(name) => write!(f, "<{name}/>"),
+            Self::Text => write!(f, "text"),
+        }
+    }
+}
+
 impl From<&FilteredEvent<'_>> for BasicEvent {
     fn from(value: &FilteredEvent<'_>) -> Self {
         match value {
@@ -0,0 +1,110 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::Coverage;
+
+/// Marker patterns used to exclude lines from coverage.
+///
+/// A line is excluded if it contains one of the `line` markers, or if it lies
+/// between a line containing one of the `start` markers and a line
+/// containing one of the `stop` markers (both inclusive).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exclusions {
+    pub line: Vec<String>,
+    pub start: Vec<String>,
+    pub stop: Vec<String>,
+}
+
+impl Default for Exclusions {
+    fn default() -> Self {
+        Self {
+            line: vec!["LCOV_EXCL_LINE".into()],
+            start: vec!["LCOV_EXCL_START".into(), "coverage: off".into()],
+            stop: vec!["LCOV_EXCL_STOP".into(), "coverage: on".into()],
+        }
+    }
+}
+
+impl Exclusions {
+    /// The (1-based) numbers of all excluded lines in `source`.
+    pub fn excluded_lines(&self, source: &str) -> BTreeSet<usize> {
+        let contains_any =
+            |line: &str, markers: &[String]| markers.iter().any(|m| line.contains(m.as_str()));
+
+        let mut excluded = BTreeSet::new();
+        let mut in_block = false;
+
+        for (idx, line) in source.lines().enumerate() {
+            let number = idx + 1;
+
+            if !in_block && contains_any(line, &self.start) {
+                in_block = true;
+            }
+
+            if in_block || contains_any(line, &self.line) {
+                excluded.insert(number);
+            }
+
+            if in_block && contains_any(line, &self.stop) {
+                in_block = false;
+            }
+        }
+
+        excluded
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExclusionOutcome {
+    /// The number of lines removed from classes (lines removed from methods
+    /// are not counted separately).
+    pub excluded_lines: usize,
+    /// Files that could not be loaded, and were left untouched.
+    pub unreadable: Vec<PathBuf>,
+}
+
+impl Coverage {
+    /// Remove all lines excluded by `exclusions` from the model, and recompute
+    /// rates afterwards.
+    ///
+    /// `load_source` is called once per distinct file name to load its
+    /// contents.
+    pub fn apply_exclusions(
+        &mut self,
+        exclusions: &Exclusions,
+        mut load_source: impl FnMut(&Path) -> io::Result<String>,
+    ) -> ExclusionOutcome {
+        let mut excluded_by_file: HashMap<PathBuf, Option<BTreeSet<usize>>> = HashMap::new();
+        let mut outcome = ExclusionOutcome::default();
+
+        for class in self.packages.iter_mut().flat_map(|p| &mut p.classes) {
+            let excluded = excluded_by_file
+                .entry(class.file_name.to_path_buf())
+                .or_insert_with(|| match load_source(&class.file_name) {
+                    Ok(source) => Some(exclusions.excluded_lines(&source)),
+                    Err(e) => {
+                        log::debug!("Could not read {}: {e}", class.file_name.display());
+                        outcome.unreadable.push(class.file_name.to_path_buf());
+                        None
+                    }
+                });
+
+            let Some(excluded) = excluded else {
+                continue;
+            };
+
+            let removed = class.remove_lines(self.min_hits, |l| excluded.contains(&l.number));
+            if removed > 0 {
+                log::debug!("Excluded {removed} line(s) of {}", class.name);
+            }
+            outcome.excluded_lines += removed;
+        }
+
+        self.recompute_rates();
+
+        outcome
+    }
+}
@@ -0,0 +1,137 @@
+use std::{io, path::Path};
+
+use crate::{glob::glob_match, Coverage};
+
+#[derive(Debug, Clone, PartialEq)]
+struct IgnoreRule {
+    pattern: String,
+    // Whether the pattern re-includes files, i.e. starts with `!`.
+    negated: bool,
+    // Whether the pattern only matches directories (and so their contents).
+    directory: bool,
+    // Whether the pattern contains a `/` before its end, so that it matches
+    // whole paths from their start instead of single components.
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+
+        let directory = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        let anchored = pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+
+        (!pattern.is_empty()).then(|| Self {
+            pattern: pattern.to_string(),
+            negated,
+            directory,
+            anchored,
+        })
+    }
+
+    // Whether the rule matches `path` itself, which is a directory if
+    // `is_directory` is set. Its parent directories are matched separately.
+    fn matches(&self, path: &str, is_directory: bool) -> bool {
+        if self.directory && !is_directory {
+            return false;
+        }
+
+        if self.anchored {
+            glob_match(&self.pattern, path, '/')
+        } else {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            glob_match(&self.pattern, name, '/')
+        }
+    }
+}
+
+/// Which files to leave out of a report, as listed in a `.coverageignore`
+/// file with the syntax of `.gitignore`:
+///
+/// ```text
+/// # Comments and empty lines are ignored.
+/// *.generated.cs
+/// /tests/
+/// vendor/*
+/// !vendor/patched/
+/// ```
+///
+/// Patterns without a `/` (other than at the end) match the name of a file
+/// or directory at any depth. The others match whole paths from their start,
+/// i.e. from the root of a relative path, so use `**/` to match at any
+/// depth. A trailing `/` only matches directories, which leaves out all
+/// files in them.
+///
+/// `!` re-includes files that an earlier pattern ignored, and the last
+/// matching pattern decides. As in git, files can't be re-included if one
+/// of their directories is ignored, which is why the example ignores
+/// `vendor/*` rather than `vendor/`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoverageFilter {
+    rules: Vec<IgnoreRule>,
+}
+
+impl CoverageFilter {
+    /// The file that the CLI loads from the working directory.
+    pub const IGNORE_FILE: &'static str = ".coverageignore";
+
+    pub fn parse(contents: &str) -> Self {
+        Self {
+            rules: contents.lines().filter_map(IgnoreRule::parse).collect(),
+        }
+    }
+
+    pub fn from_ignore_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether the file `path` is left out, either by itself or because
+    /// one of its directories is.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy().replace('\\', "/");
+        let path = path.trim_start_matches("./");
+
+        // Directories from the outermost, as git does not look into ignored
+        // directories.
+        let directories = path.match_indices('/').map(|(idx, _)| &path[..idx]);
+        directories
+            .filter(|directory| !directory.is_empty())
+            .any(|directory| self.decides_ignored(directory, true))
+            || self.decides_ignored(path, false)
+    }
+
+    // Whether the last rule that matches `path` ignores it.
+    fn decides_ignored(&self, path: &str, is_directory: bool) -> bool {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(path, is_directory))
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+impl Coverage {
+    /// Remove the classes whose files `filter` ignores, and recompute all
+    /// rates. Returns the number of classes removed.
+    pub fn apply_filter(&mut self, filter: &CoverageFilter) -> usize {
+        if filter.is_empty() {
+            return 0;
+        }
+
+        self.retain_classes(|class| !filter.is_ignored(&class.file_name))
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{glob::path_matches, Class, Coverage};
+
+/// The number of lines at the top of a source that are searched for a
+/// marker of generated code.
+const MARKER_LINES: usize = 20;
+
+/// Tells which classes are generated code, by the names of their files or
+/// by a marker near the top of their sources, e.g. `@generated`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedCode {
+    /// Glob patterns of the files with generated code, e.g. `**/*.pb.go`.
+    pub patterns: Vec<String>,
+    /// Markers that make a file generated if one of its first lines contains
+    /// them. Only looked for if `detect_markers` is set.
+    pub markers: Vec<String>,
+    pub detect_markers: bool,
+}
+
+impl Default for GeneratedCode {
+    fn default() -> Self {
+        Self {
+            patterns: [
+                "**/*.g.cs",
+                "**/*.designer.cs",
+                "**/*.Designer.cs",
+                "**/*.pb.go",
+                "**/*.pb.cc",
+                "**/*_pb2.py",
+                "**/*_pb2_grpc.py",
+                "**/generated/**",
+            ]
+            .map(String::from)
+            .to_vec(),
+            markers: [
+                "@generated",
+                "<auto-generated",
+                "Code generated by",
+                "DO NOT EDIT",
+            ]
+            .map(String::from)
+            .to_vec(),
+            detect_markers: false,
+        }
+    }
+}
+
+impl GeneratedCode {
+    /// Whether the file `path` is generated, by its name alone.
+    pub fn matches_path(&self, path: &Path) -> bool {
+        self.patterns.iter().any(|p| path_matches(p, path))
+    }
+
+    /// Whether `source` has one of the markers in its first lines.
+    pub fn has_marker(&self, source: &str) -> bool {
+        source
+            .lines()
+            .take(MARKER_LINES)
+            .any(|line| self.markers.iter().any(|m| line.contains(m.as_str())))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeneratedOutcome {
+    /// A report with only the generated classes.
+    pub generated: Coverage,
+    /// Files that could not be loaded to look for markers, and were taken
+    /// not to be generated unless their names match.
+    pub unreadable: Vec<PathBuf>,
+}
+
+impl Coverage {
+    /// Find the classes that `classifier` considers generated, and return
+    /// them as a report of their own, with recomputed rates.
+    ///
+    /// If markers are detected, `load_source` is called once per distinct
+    /// file name that doesn't match a pattern to load its contents.
+    pub fn find_generated(
+        &self,
+        classifier: &GeneratedCode,
+        mut load_source: impl FnMut(&Path) -> io::Result<String>,
+    ) -> GeneratedOutcome {
+        let mut generated_files: HashMap<Arc<Path>, bool> = HashMap::new();
+        let mut unreadable = Vec::new();
+
+        let mut is_generated = |class: &Class| {
+            *generated_files
+                .entry(class.file_name.clone())
+                .or_insert_with(|| {
+                    if classifier.matches_path(&class.file_name) {
+                        return true;
+                    }
+                    if !classifier.detect_markers {
+                        return false;
+                    }
+                    match load_source(&class.file_name) {
+                        Ok(source) => classifier.has_marker(&source),
+                        Err(_) => {
+                            unreadable.push(class.file_name.to_path_buf());
+                            false
+                        }
+                    }
+                })
+        };
+
+        let packages = self
+            .packages
+            .iter()
+            .filter_map(|package| {
+                let classes: Vec<_> = package
+                    .classes
+                    .iter()
+                    .filter(|c| is_generated(c))
+                    .cloned()
+                    .collect();
+
+                (!classes.is_empty()).then(|| {
+                    let mut package = package.clone();
+                    package.classes = classes;
+                    package
+                })
+            })
+            .collect();
+
+        GeneratedOutcome {
+            generated: self.with_packages(packages),
+            unreadable,
+        }
+    }
+
+    /// Like [`Coverage::find_generated`], and also remove the generated
+    /// classes from this report, so that they don't count towards its rates.
+    pub fn split_generated(
+        &mut self,
+        classifier: &GeneratedCode,
+        load_source: impl FnMut(&Path) -> io::Result<String>,
+    ) -> GeneratedOutcome {
+        let outcome = self.find_generated(classifier, load_source);
+
+        let generated: HashSet<_> = outcome
+            .generated
+            .classes()
+            .map(|c| (c.file_name.clone(), c.name.clone()))
+            .collect();
+        if !generated.is_empty() {
+            self.retain_classes(|c| !generated.contains(&(c.file_name.clone(), c.name.clone())));
+        }
+
+        outcome
+    }
+}
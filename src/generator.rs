@@ -0,0 +1,84 @@
+use std::io;
+
+use crate::{Coverage, ReportSink};
+
+/// A report format that writes its files to a [`ReportSink`], so that
+/// reports can be selected by name, e.g. with `--reporttypes Html,Csv`.
+///
+/// Generators hold their own options, such as the rounding of rates.
+pub trait ReportGenerator {
+    /// The name of the report type, e.g. `Html`. Names are matched without
+    /// regard to case.
+    fn name(&self) -> &str;
+
+    fn generate(&self, coverage: &Coverage, sink: &mut dyn ReportSink) -> io::Result<()>;
+}
+
+/// Report generators by name.
+#[derive(Default)]
+pub struct GeneratorRegistry {
+    generators: Vec<Box<dyn ReportGenerator>>,
+}
+
+impl GeneratorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `generator`, replacing the generator of the same name if there
+    /// is one.
+    pub fn register(&mut self, generator: impl ReportGenerator + 'static) {
+        let generator: Box<dyn ReportGenerator> = Box::new(generator);
+        match self.position(generator.name()) {
+            Some(idx) => self.generators[idx] = generator,
+            None => self.generators.push(generator),
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.generators
+            .iter()
+            .position(|g| g.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn ReportGenerator> {
+        self.position(name).map(|idx| &*self.generators[idx])
+    }
+
+    /// The names of all generators, in the order they were registered.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.generators.iter().map(|g| g.name())
+    }
+
+    /// Run the generators named `names` in order, writing all reports to
+    /// `sink`. Fails before writing anything if a name is unknown.
+    pub fn generate(
+        &self,
+        names: &[impl AsRef<str>],
+        coverage: &Coverage,
+        sink: &mut dyn ReportSink,
+    ) -> io::Result<()> {
+        let generators = names
+            .iter()
+            .map(|name| {
+                self.get(name.as_ref()).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "Unknown report type `{}`, expected one of {}",
+                            name.as_ref(),
+                            self.names().collect::<Vec<_>>().join(", ")
+                        ),
+                    )
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        for generator in generators {
+            log::debug!("Generating the {} report", generator.name());
+            generator.generate(coverage, sink)?;
+        }
+
+        Ok(())
+    }
+}
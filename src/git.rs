@@ -0,0 +1,115 @@
+use std::{io, path::Path, process::Command};
+
+// Run `git` in `repo` with `args`, returning its output. `what` describes the
+// command in errors.
+fn git(repo: &Path, args: &[&str], what: &str) -> io::Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "{what} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    String::from_utf8(output.stdout).map_err(io::Error::other)
+}
+
+/// The files added in the git repository at `repo` since the revision `base`,
+/// including uncommitted changes. Paths are relative to the root of the
+/// repository.
+///
+/// This runs the `git` executable, which must be on the `PATH`.
+#[cfg(feature = "policy")]
+pub fn added_files(repo: impl AsRef<Path>, base: &str) -> io::Result<Vec<std::path::PathBuf>> {
+    let files = git(
+        repo.as_ref(),
+        &[
+            "diff",
+            "--name-only",
+            "--no-renames",
+            "--diff-filter=A",
+            "-z",
+            base,
+            "--",
+        ],
+        &format!("git diff against `{base}`"),
+    )?;
+
+    let files: Vec<_> = files
+        .split('\0')
+        .filter(|f| !f.is_empty())
+        .map(std::path::PathBuf::from)
+        .collect();
+
+    log::debug!("{} file(s) added since {base}", files.len());
+
+    Ok(files)
+}
+
+/// The commit checked out in a git repository, as sent to coverage services.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitInfo {
+    pub commit: String,
+    /// `None` if no branch is checked out, as is common on CI.
+    pub branch: Option<String>,
+    pub author_name: String,
+    pub author_email: String,
+    pub committer_name: String,
+    pub committer_email: String,
+    /// The subject of the commit message.
+    pub message: String,
+    /// The remotes, as `(name, url)`.
+    pub remotes: Vec<(String, String)>,
+}
+
+impl GitInfo {
+    /// Read the commit checked out in the git repository at `repo`.
+    ///
+    /// This runs the `git` executable, which must be on the `PATH`.
+    pub fn read(repo: impl AsRef<Path>) -> io::Result<Self> {
+        let repo = repo.as_ref();
+
+        let log = git(
+            repo,
+            &["log", "-1", "--format=%H%n%an%n%ae%n%cn%n%ce%n%s"],
+            "git log",
+        )?;
+        let mut fields = log.lines().map(String::from);
+        let mut field = || fields.next().unwrap_or_default();
+
+        let branch = git(
+            repo,
+            &["rev-parse", "--abbrev-ref", "HEAD"],
+            "git rev-parse",
+        )?;
+        let branch = Some(branch.trim())
+            .filter(|b| *b != "HEAD")
+            .map(String::from);
+
+        let mut remotes = Vec::new();
+        for line in git(repo, &["remote", "-v"], "git remote")?.lines() {
+            let mut parts = line.split_whitespace();
+            if let (Some(name), Some(url), Some("(fetch)")) =
+                (parts.next(), parts.next(), parts.next())
+            {
+                remotes.push((name.to_string(), url.to_string()));
+            }
+        }
+
+        Ok(Self {
+            commit: field(),
+            branch,
+            author_name: field(),
+            author_email: field(),
+            committer_name: field(),
+            committer_email: field(),
+            message: field(),
+            remotes,
+        })
+    }
+}
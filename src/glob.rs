@@ -0,0 +1,57 @@
+use std::path::Path;
+
+// Minimal glob matching for path and package patterns.
+//
+// `**` matches any number of characters, including separators, `*` matches
+// any number of characters except `separator`, and `?` matches a single
+// character other than `separator`. Everything else matches literally.
+pub(crate) fn glob_match(pattern: &str, text: &str, separator: char) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    matches(&pattern, &text, separator)
+}
+
+// Whether `pattern` matches `path` (with `/` as the separator), or any of its
+// trailing components, so that relative patterns match absolute paths.
+pub(crate) fn path_matches(pattern: &str, path: &Path) -> bool {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let pattern = pattern.trim_start_matches("./");
+
+    glob_match(pattern, &path, '/')
+        || path
+            .match_indices('/')
+            .any(|(idx, _)| glob_match(pattern, &path[idx + 1..], '/'))
+}
+
+fn matches(pattern: &[char], text: &[char], separator: char) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', rest @ ..] => {
+            // Let `**/` also match zero directories.
+            let rest_without_sep = match rest {
+                [c, after @ ..] if *c == separator => Some(after),
+                _ => None,
+            };
+
+            (0..=text.len()).any(|skip| {
+                matches(rest, &text[skip..], separator)
+                    || rest_without_sep.is_some_and(|r| matches(r, &text[skip..], separator))
+            })
+        }
+        ['*', rest @ ..] => {
+            let segment = text
+                .iter()
+                .position(|c| *c == separator)
+                .unwrap_or(text.len());
+            (0..=segment).any(|skip| matches(rest, &text[skip..], separator))
+        }
+        ['?', rest @ ..] => match text {
+            [c, text @ ..] if *c != separator => matches(rest, text, separator),
+            _ => false,
+        },
+        [p, rest @ ..] => match text {
+            [c, text @ ..] if c == p => matches(rest, text, separator),
+            _ => false,
+        },
+    }
+}
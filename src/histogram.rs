@@ -0,0 +1,124 @@
+use std::fmt;
+
+use crate::Coverage;
+
+/// The distribution of the hits of the lines of a report, e.g. to judge how
+/// much the tests overlap.
+///
+/// Lines are counted in [`HitHistogram::BUCKETS`] by their hits: never hit,
+/// hit once, 2 to 10 times, 11 to 100 times, 101 to 1000 times and more than
+/// 1000 times.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HitHistogram {
+    pub lines: usize,
+    /// The number of lines in each of the buckets.
+    pub buckets: [usize; 6],
+    pub total_hits: u64,
+    // The hits of every line, in ascending order.
+    hits: Vec<u64>,
+}
+
+impl HitHistogram {
+    /// The labels of the buckets, by the number of hits of their lines.
+    pub const BUCKETS: [&'static str; 6] = ["0", "1", "2-10", "11-100", "101-1000", ">1000"];
+
+    pub fn from_hits(hits: impl IntoIterator<Item = u64>) -> Self {
+        let mut hits: Vec<_> = hits.into_iter().collect();
+        hits.sort_unstable();
+
+        let mut buckets = [0; 6];
+        for &hits in &hits {
+            let bucket = match hits {
+                0 => 0,
+                1 => 1,
+                2..=10 => 2,
+                11..=100 => 3,
+                101..=1000 => 4,
+                _ => 5,
+            };
+            buckets[bucket] += 1;
+        }
+
+        Self {
+            lines: hits.len(),
+            buckets,
+            total_hits: hits.iter().fold(0u64, |sum, h| sum.saturating_add(*h)),
+            hits,
+        }
+    }
+
+    /// The number of lines that were never hit.
+    pub fn never_hit(&self) -> usize {
+        self.buckets[0]
+    }
+
+    /// The number of lines that were hit exactly once.
+    pub fn hit_once(&self) -> usize {
+        self.buckets[1]
+    }
+
+    /// The number of lines that were hit more than 1000 times.
+    pub fn hit_over_thousand(&self) -> usize {
+        self.buckets[5]
+    }
+
+    /// The hits that `percentile` percent of the lines have at most (with the
+    /// nearest rank), or `None` if there are no lines.
+    pub fn percentile(&self, percentile: f64) -> Option<u64> {
+        if self.hits.is_empty() {
+            return None;
+        }
+
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * self.hits.len() as f64).ceil();
+        let idx = (rank as usize).saturating_sub(1);
+        Some(self.hits[idx.min(self.hits.len() - 1)])
+    }
+
+    pub fn median(&self) -> Option<u64> {
+        self.percentile(50.0)
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        self.hits.last().copied()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.lines > 0).then(|| self.total_hits as f64 / self.lines as f64)
+    }
+}
+
+impl Coverage {
+    /// The distribution of the hits of the [unique lines](Self::unique_lines)
+    /// of this report.
+    pub fn hit_statistics(&self) -> HitHistogram {
+        HitHistogram::from_hits(self.unique_lines().map(|(_, line)| line.hits))
+    }
+}
+
+impl fmt::Display for HitHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |hits: Option<u64>| hits.map_or("-".to_string(), |h| h.to_string());
+
+        writeln!(f, "Lines never hit:    {}", self.never_hit())?;
+        writeln!(f, "Lines hit once:     {}", self.hit_once())?;
+        writeln!(f, "Lines hit >1000x:   {}", self.hit_over_thousand())?;
+        match self.mean() {
+            Some(mean) => writeln!(f, "Mean hits:          {mean:.1}")?,
+            None => writeln!(f, "Mean hits:          -")?,
+        }
+        writeln!(f, "Median hits:        {}", value(self.median()))?;
+        writeln!(f, "90th percentile:    {}", value(self.percentile(90.0)))?;
+        writeln!(f, "99th percentile:    {}", value(self.percentile(99.0)))?;
+        writeln!(f, "Maximum hits:       {}", value(self.max()))?;
+
+        let largest = self.buckets.iter().copied().max().unwrap_or(0).max(1);
+        writeln!(f, "Lines by hits:")?;
+        for (label, count) in Self::BUCKETS.iter().zip(self.buckets) {
+            let bar = "#".repeat((count * 40).div_ceil(largest));
+            let row = format!("  {label:>8}  {count:>8}  {bar}");
+            writeln!(f, "{}", row.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
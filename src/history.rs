@@ -0,0 +1,320 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    time::{Duration, SystemTime},
+};
+#[cfg(feature = "json")]
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{Class, Coverage, ReportMetadata};
+
+/// The overall coverage of one run, as kept in a [`History`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct HistoryEntry {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub line_rate: f64,
+    pub branch_rate: f64,
+    pub lines_covered: usize,
+    pub lines_valid: usize,
+    pub branches_covered: usize,
+    pub branches_valid: usize,
+    #[cfg_attr(feature = "json", serde(default))]
+    pub metadata: ReportMetadata,
+    /// The line rate of every class, by [`HistoryEntry::class_key`].
+    #[cfg_attr(
+        feature = "json",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub classes: BTreeMap<String, f64>,
+}
+
+// The file name in a key of `HistoryEntry::classes`.
+fn file_of(key: &str) -> &str {
+    key.split_once("::").map_or("", |(file, _)| file)
+}
+
+impl HistoryEntry {
+    /// The entry for `coverage` of the build described by `metadata`, at the
+    /// time it was generated, or now if it has no timestamp.
+    pub fn new(coverage: &Coverage, metadata: &ReportMetadata) -> Self {
+        let timestamp = coverage
+            .timestamp_duration()
+            .or_else(|| {
+                SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .ok()
+            })
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            timestamp,
+            line_rate: coverage.line_rate,
+            branch_rate: coverage.branch_rate,
+            lines_covered: coverage.lines_covered,
+            lines_valid: coverage.lines_valid,
+            branches_covered: coverage.branches_covered,
+            branches_valid: coverage.branches_valid,
+            metadata: metadata.clone(),
+            classes: coverage
+                .packages
+                .iter()
+                .flat_map(|p| &p.classes)
+                .map(|class| (Self::class_key(class), class.line_rate))
+                .collect(),
+        }
+    }
+
+    /// The key of `class` in [`Self::classes`], which stays the same across
+    /// runs as long as the class isn't renamed or moved to another file.
+    pub fn class_key(class: &Class) -> String {
+        format!(
+            "{}::{}",
+            class.file_name.to_string_lossy().replace('\\', "/"),
+            class.name
+        )
+    }
+
+    /// The line rate of `class` in this run, if it existed.
+    pub fn class_line_rate(&self, class: &Class) -> Option<f64> {
+        self.classes.get(&Self::class_key(class)).copied()
+    }
+
+    /// The line rate in this run of every class of `coverage` that existed
+    /// then, by [`Self::class_key`] in `coverage`.
+    ///
+    /// The history only keeps the keys of classes, so a class that isn't
+    /// found by its key is followed if it was moved to another file (by its
+    /// name) or renamed within its file (by its file name), as long as only
+    /// one class could have been moved or renamed that way.
+    pub fn class_line_rates(&self, coverage: &Coverage) -> HashMap<String, f64> {
+        let mut rates = HashMap::new();
+        let mut current = HashSet::new();
+        let mut missing = Vec::new();
+
+        for class in coverage.packages.iter().flat_map(|p| &p.classes) {
+            let key = Self::class_key(class);
+            match self.classes.get(&key) {
+                Some(rate) => {
+                    rates.insert(key.clone(), *rate);
+                }
+                None => missing.push((key.clone(), class)),
+            }
+            current.insert(key);
+        }
+
+        // The classes of this run that are gone from `coverage`, as
+        // `(file, name, line rate)`.
+        let gone: Vec<_> = self
+            .classes
+            .iter()
+            .filter(|(key, _)| !current.contains(*key))
+            .filter_map(|(key, rate)| {
+                let (file, name) = key.split_once("::")?;
+                Some((file, name, *rate))
+            })
+            .collect();
+
+        let unique = |matches: &dyn Fn(&str, &str) -> bool| {
+            let mut found = gone.iter().filter(|(file, name, _)| matches(file, name));
+            match (found.next(), found.next()) {
+                (Some((_, _, rate)), None) => Some(*rate),
+                _ => None,
+            }
+        };
+
+        for (key, class) in &missing {
+            let same_name = missing.iter().filter(|(_, c)| c.name == class.name).count();
+            let same_file = missing
+                .iter()
+                .filter(|(k, _)| file_of(k) == file_of(key))
+                .count();
+
+            let rate = (same_name == 1)
+                .then(|| unique(&|_, name| name == &*class.name))
+                .flatten()
+                .or_else(|| {
+                    (same_file == 1)
+                        .then(|| unique(&|file, _| file == file_of(key)))
+                        .flatten()
+                });
+
+            if let Some(rate) = rate {
+                rates.insert(key.clone(), rate);
+            }
+        }
+
+        rates
+    }
+}
+
+/// How often to keep an entry of a history, see [`RetentionPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionInterval {
+    Day,
+    /// Weeks start on Monday (UTC).
+    Week,
+}
+
+impl RetentionInterval {
+    fn bucket(self, timestamp: u64) -> u64 {
+        let day = timestamp / 86_400;
+        match self {
+            RetentionInterval::Day => day,
+            // The epoch was on a Thursday.
+            RetentionInterval::Week => (day + 3) / 7,
+        }
+    }
+}
+
+/// Which entries of a history to keep when pruning it. An entry is kept if
+/// any of the rules keeps it, so the default keeps nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Keep this many of the most recent entries.
+    pub keep_last: usize,
+    /// Keep all entries of this period before now.
+    pub keep_within: Option<Duration>,
+    /// Keep the most recent entry of every day or week, e.g. to thin out
+    /// entries older than `keep_within`.
+    pub keep_one_per: Option<RetentionInterval>,
+}
+
+impl RetentionPolicy {
+    /// Whether to keep each of `entries`, which are sorted oldest first, at
+    /// `now` (in seconds since the Unix epoch).
+    pub fn retain(&self, entries: &[HistoryEntry], now: u64) -> Vec<bool> {
+        let mut keep = vec![false; entries.len()];
+
+        for keep in keep.iter_mut().rev().take(self.keep_last) {
+            *keep = true;
+        }
+
+        if let Some(window) = self.keep_within {
+            let since = now.saturating_sub(window.as_secs());
+            for (keep, entry) in keep.iter_mut().zip(entries) {
+                *keep |= entry.timestamp >= since;
+            }
+        }
+
+        if let Some(interval) = self.keep_one_per {
+            let mut kept_bucket = None;
+            for (keep, entry) in keep.iter_mut().zip(entries).rev() {
+                let bucket = interval.bucket(entry.timestamp);
+                if kept_bucket != Some(bucket) {
+                    *keep = true;
+                    kept_bucket = Some(bucket);
+                }
+            }
+        }
+
+        keep
+    }
+}
+
+/// A directory with the overall coverage of previous runs, one JSON file per
+/// run.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone)]
+pub struct History {
+    dir: PathBuf,
+}
+
+#[cfg(feature = "json")]
+impl History {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// All entries, oldest first. Files that can't be read as an entry are
+    /// skipped. A missing directory has no entries.
+    pub fn entries(&self) -> io::Result<Vec<HistoryEntry>> {
+        Ok(self.entry_files()?.into_iter().map(|(_, e)| e).collect())
+    }
+
+    // All entries with their files, oldest first.
+    fn entry_files(&self) -> io::Result<Vec<(PathBuf, HistoryEntry)>> {
+        let dir = match std::fs::read_dir(&self.dir) {
+            Ok(dir) => dir,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut paths = dir
+            .map(|file| Ok(file?.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        paths.retain(|path| path.extension().is_some_and(|e| e == "json"));
+        // Runs with the same timestamp are numbered in the order they were
+        // recorded, e.g. `coverage-1-1.json` after `coverage-1.json` and
+        // `coverage-1-10.json` after `coverage-1-9.json`.
+        paths.sort_by(|a, b| (a.as_os_str().len(), a).cmp(&(b.as_os_str().len(), b)));
+
+        let mut entries = Vec::new();
+        for path in paths {
+            match serde_json::from_slice(&std::fs::read(&path)?) {
+                Ok(entry) => entries.push((path, entry)),
+                Err(e) => log::warn!("Skipping history entry {}: {e}", path.display()),
+            }
+        }
+
+        entries.sort_by_key(|(_, e): &(PathBuf, HistoryEntry)| e.timestamp);
+        Ok(entries)
+    }
+
+    /// The files of the entries that `policy` doesn't keep at `now` (in
+    /// seconds since the Unix epoch). Files that can't be read as an entry
+    /// are never pruned.
+    pub fn prunable(&self, policy: &RetentionPolicy, now: u64) -> io::Result<Vec<PathBuf>> {
+        let files = self.entry_files()?;
+        let entries: Vec<_> = files.iter().map(|(_, e)| e.clone()).collect();
+        let keep = policy.retain(&entries, now);
+
+        Ok(files
+            .into_iter()
+            .zip(keep)
+            .filter(|(_, keep)| !keep)
+            .map(|((path, _), _)| path)
+            .collect())
+    }
+
+    /// Remove the entries that `policy` doesn't keep at `now`, returning
+    /// their files.
+    pub fn prune(&self, policy: &RetentionPolicy, now: u64) -> io::Result<Vec<PathBuf>> {
+        let prunable = self.prunable(policy, now)?;
+        for path in &prunable {
+            std::fs::remove_file(path)?;
+        }
+
+        log::debug!("Pruned {} history entries", prunable.len());
+        Ok(prunable)
+    }
+
+    /// The most recent entry, if any.
+    pub fn latest(&self) -> io::Result<Option<HistoryEntry>> {
+        Ok(self.entries()?.pop())
+    }
+
+    /// Add `entry`, returning the path of the file it was written to.
+    pub fn record(&self, entry: &HistoryEntry) -> io::Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let path = (0..)
+            .map(|n| match n {
+                0 => format!("coverage-{}.json", entry.timestamp),
+                n => format!("coverage-{}-{n}.json", entry.timestamp),
+            })
+            .map(|name| self.dir.join(name))
+            .find(|path| !Path::exists(path))
+            .expect("an unused file name");
+
+        let json = serde_json::to_vec_pretty(entry).map_err(io::Error::other)?;
+        std::fs::write(&path, json)?;
+        log::debug!("Recorded history entry {}", path.display());
+
+        Ok(path)
+    }
+}
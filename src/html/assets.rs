@@ -0,0 +1,216 @@
+use super::{
+    file_names::fnv1a_64, HtmlGenerator, HtmlOptions, CLASS_HTML, CLASS_JS, HTML_PREFIX,
+    INDEX_HTML, INDEX_JS, REPORT_CSS, STYLESHEET,
+};
+
+/// How the stylesheet and scripts of the report are written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssetOptions {
+    /// Remove comments and whitespace from the stylesheet and scripts.
+    pub minify: bool,
+    /// Add a hash of their contents to the file names of assets (e.g.
+    /// `report.0123abcd.css`), so that they can be cached indefinitely.
+    pub content_hash: bool,
+    /// Inline assets of at most this many bytes into every page, instead of
+    /// writing them to separate files.
+    pub inline_limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AssetKind {
+    Stylesheet,
+    Script,
+}
+
+/// A stylesheet or script of the report, after minifying and hashing.
+#[derive(Debug, Clone)]
+pub(crate) struct Asset {
+    kind: AssetKind,
+    // The name that the templates refer to.
+    name: &'static str,
+    pub(crate) file_name: String,
+    pub(crate) contents: String,
+    pub(crate) inline: bool,
+}
+
+impl Asset {
+    fn new(kind: AssetKind, name: &'static str, source: &str, options: &AssetOptions) -> Self {
+        let contents = match (options.minify, kind) {
+            (false, _) => source.to_string(),
+            (true, AssetKind::Stylesheet) => minify_css(source),
+            (true, AssetKind::Script) => minify_js(source),
+        };
+
+        let file_name = match name.rsplit_once('.') {
+            Some((stem, extension)) if options.content_hash => {
+                let hash = format!("{:016x}", fnv1a_64(&contents));
+                format!("{stem}.{}.{extension}", &hash[..8])
+            }
+            _ => name.to_string(),
+        };
+
+        Self {
+            kind,
+            name,
+            file_name,
+            inline: options
+                .inline_limit
+                .is_some_and(|limit| contents.len() <= limit),
+            contents,
+        }
+    }
+
+    // The tag that includes the asset in the templates.
+    fn template_tag(&self) -> String {
+        match self.kind {
+            AssetKind::Stylesheet => STYLESHEET.to_string(),
+            AssetKind::Script => {
+                format!(
+                    "<script type=\"module\" src=\"./{}\" defer></script>",
+                    self.name
+                )
+            }
+        }
+    }
+
+    /// The tag that includes the asset in a page.
+    pub(crate) fn tag(&self) -> String {
+        match (self.kind, self.inline) {
+            (AssetKind::Stylesheet, false) => {
+                format!("<link rel=\"stylesheet\" href=\"./{}\">", self.file_name)
+            }
+            (AssetKind::Stylesheet, true) => format!("<style>\n{}</style>", self.contents),
+            (AssetKind::Script, false) => format!(
+                "<script type=\"module\" src=\"./{}\" defer></script>",
+                self.file_name
+            ),
+            (AssetKind::Script, true) => {
+                format!("<script type=\"module\">\n{}</script>", self.contents)
+            }
+        }
+    }
+}
+
+/// All assets of the report.
+#[derive(Debug, Clone)]
+pub(crate) struct Assets {
+    pub(crate) css: Asset,
+    pub(crate) index_js: Asset,
+    pub(crate) class_js: Asset,
+}
+
+impl Assets {
+    pub(crate) fn new(options: &AssetOptions) -> Self {
+        Self {
+            css: Asset::new(AssetKind::Stylesheet, "report.css", REPORT_CSS, options),
+            index_js: Asset::new(AssetKind::Script, "index.js", INDEX_JS, options),
+            class_js: Asset::new(AssetKind::Script, "class.js", CLASS_JS, options),
+        }
+    }
+
+    pub(crate) fn all(&self) -> [&Asset; 3] {
+        [&self.css, &self.index_js, &self.class_js]
+    }
+
+    /// Point the asset tags of `head` to the assets as they are written.
+    pub(crate) fn apply(&self, head: &str) -> String {
+        let mut head = head.to_string();
+        for asset in self.all() {
+            head = head.replace(&asset.template_tag(), &asset.tag());
+        }
+        head
+    }
+}
+
+/// The starts of the pages of a report, with its assets.
+pub(crate) struct Heads {
+    pub(crate) page: String,
+    pub(crate) class: String,
+    pub(crate) index: String,
+}
+
+impl Heads {
+    pub(crate) fn new(options: &HtmlOptions, assets: &Assets) -> Self {
+        let head = |template| assets.apply(&HtmlGenerator::head(template, options));
+
+        Self {
+            page: head(HTML_PREFIX),
+            class: head(CLASS_HTML),
+            index: head(INDEX_HTML),
+        }
+    }
+}
+
+/// Remove comments and all whitespace that isn't needed from `css`.
+fn minify_css(css: &str) -> String {
+    // No whitespace is needed after or before these.
+    const AFTER: &str = "{};,>:(";
+    const BEFORE: &str = "{};,>)";
+
+    let mut out = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+    let mut space = false;
+
+    while let Some(c) = chars.next() {
+        if c == '/' && chars.peek() == Some(&'*') {
+            chars.next();
+            let mut previous = '\0';
+            for c in chars.by_ref() {
+                if previous == '*' && c == '/' {
+                    break;
+                }
+                previous = c;
+            }
+            space = true;
+            continue;
+        }
+
+        if c.is_whitespace() {
+            space = true;
+            continue;
+        }
+
+        if space
+            && !BEFORE.contains(c)
+            && out.chars().next_back().is_some_and(|p| !AFTER.contains(p))
+        {
+            out.push(' ');
+        }
+        space = false;
+
+        if c == '}' && out.ends_with(';') {
+            out.pop();
+        }
+        out.push(c);
+
+        if c == '"' || c == '\'' {
+            let mut escaped = false;
+            for s in chars.by_ref() {
+                out.push(s);
+                match s {
+                    '\\' if !escaped => escaped = true,
+                    s if s == c && !escaped => break,
+                    _ => escaped = false,
+                }
+            }
+        }
+    }
+
+    out.push('\n');
+    out
+}
+
+/// Remove indentation, blank lines and lines that only hold a comment from
+/// `js`. Line breaks are kept, so that automatic semicolon insertion still
+/// works, which also means that this breaks template literals spanning
+/// several lines. Our scripts don't have any.
+fn minify_js(js: &str) -> String {
+    let mut out = String::with_capacity(js.len());
+    for line in js.lines().map(str::trim) {
+        if !line.is_empty() && !line.starts_with("//") {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
@@ -0,0 +1,78 @@
+use std::io::{self, Write};
+
+use crate::{Class, Line};
+
+use super::{HtmlGenerator, Translation};
+
+fn branch_lines(class: &Class) -> impl Iterator<Item = &Line> {
+    class.lines.iter().filter(|l| l.branch)
+}
+
+pub(crate) fn has_branches(class: &Class) -> bool {
+    branch_lines(class).next().is_some()
+}
+
+/// Write the body of the branch detail page for `class`: one row per
+/// branch line, with one entry per condition.
+pub(crate) fn write_branch_table(
+    out: &mut impl Write,
+    class: &Class,
+    strings: &Translation,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        "\n\t<h1>{}</h1>",
+        HtmlGenerator::escape(&strings.format("branches_of", &[("name", &class.name)]))
+    )?;
+    writeln!(out, "\t<table>")?;
+    writeln!(
+        out,
+        "\t\t<tr><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{}</th></tr>",
+        strings.html("line"),
+        strings.html("hits"),
+        strings.html("coverage"),
+        strings.html("missed"),
+        strings.html("conditions")
+    )?;
+
+    for line in branch_lines(class) {
+        let coverage = line.condition_coverage.as_deref().unwrap_or("");
+        let missed = line
+            .branch_counts()
+            .map(|(covered, total)| total.saturating_sub(covered).to_string())
+            .unwrap_or_default();
+
+        write!(
+            out,
+            "\t\t<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>",
+            line.number,
+            line.hits,
+            HtmlGenerator::escape(coverage),
+            missed
+        )?;
+
+        if !line.conditions.is_empty() {
+            write!(out, "<ul>")?;
+            for condition in &line.conditions {
+                write!(
+                    out,
+                    "<li>#{} {}: {}</li>",
+                    condition.number,
+                    HtmlGenerator::escape(condition.r#type.as_str()),
+                    HtmlGenerator::escape(&condition.coverage)
+                )?;
+            }
+            write!(out, "</ul>")?;
+        } else if let Some(missing) = line.missing_branches().filter(|m| !m.is_empty()) {
+            write!(
+                out,
+                "{}",
+                HtmlGenerator::escape(&strings.format("missing_branches", &[("lines", missing)]))
+            )?;
+        }
+
+        writeln!(out, "</td></tr>")?;
+    }
+
+    writeln!(out, "\t</table>")
+}
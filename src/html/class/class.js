@@ -0,0 +1,47 @@
+// Sort the rows of tables with the `sortable` class when a header is clicked.
+// Cells may provide the value to sort by in a `data-sort` attribute.
+function sortValue(cell) {
+    const value = cell.dataset.sort ?? cell.textContent.trim();
+    const number = Number(value);
+    return value !== "" && !Number.isNaN(number) ? number : value.toLowerCase();
+}
+
+for (const table of document.querySelectorAll("table.sortable")) {
+    const body = table.tBodies[0];
+
+    const headers = table.querySelectorAll("thead th");
+
+    headers.forEach((header, column) => {
+        // Make the headers reachable and usable with the keyboard, and tell
+        // assistive technology how the table is sorted.
+        header.tabIndex = 0;
+        header.setAttribute("aria-sort", "none");
+
+        const sort = () => {
+            const ascending = header.dataset.order !== "asc";
+            header.dataset.order = ascending ? "asc" : "desc";
+
+            for (const other of headers) {
+                other.setAttribute("aria-sort", "none");
+            }
+            header.setAttribute("aria-sort", ascending ? "ascending" : "descending");
+
+            const rows = [...body.rows].sort((a, b) => {
+                const x = sortValue(a.cells[column]);
+                const y = sortValue(b.cells[column]);
+                const order = x < y ? -1 : x > y ? 1 : 0;
+                return ascending ? order : -order;
+            });
+
+            body.append(...rows);
+        };
+
+        header.addEventListener("click", sort);
+        header.addEventListener("keydown", (event) => {
+            if (event.key === "Enter" || event.key === " ") {
+                event.preventDefault();
+                sort();
+            }
+        });
+    });
+}
@@ -0,0 +1,119 @@
+use std::io::{self, Write};
+
+use crate::{CoverageDiff, RateChange, RoundingPolicy};
+
+use super::{HtmlGenerator, Translation};
+
+fn format_change(
+    out: &mut impl Write,
+    change: &RateChange,
+    rounding: &RoundingPolicy,
+    strings: &Translation,
+) -> io::Result<()> {
+    let format = |rate: Option<f64>| rate.map(|r| rounding.format_rate(r)).unwrap_or_default();
+
+    let delta = match change.delta() {
+        Some(delta) if rounding.round(delta * 100.0) > 0.0 => {
+            format!("&#9650; +{}", rounding.format_rate(delta))
+        }
+        Some(delta) if rounding.round(delta * 100.0) < 0.0 => {
+            format!("&#9660; {}", rounding.format_rate(delta))
+        }
+        Some(_) => "=".to_string(),
+        None if change.before.is_none() => strings.html("new"),
+        None => strings.html("removed"),
+    };
+
+    write!(
+        out,
+        "<td>{}</td><td>{}</td><td>{delta}</td>",
+        format(change.before),
+        format(change.after),
+    )
+}
+
+/// Write the body of the comparison page: the rates of each package and
+/// class side by side, followed by the newly uncovered lines.
+pub(crate) fn write_comparison(
+    out: &mut impl Write,
+    diff: &CoverageDiff,
+    rounding: &RoundingPolicy,
+    strings: &Translation,
+) -> io::Result<()> {
+    writeln!(out, "\n\t<h1>{}</h1>", strings.html("comparison"))?;
+    writeln!(out, "\t<table>")?;
+    writeln!(
+        out,
+        "\t\t<tr><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{after}</th><th scope=\"col\">{change}</th><th scope=\"col\">{}</th><th scope=\"col\">{after}</th><th scope=\"col\">{change}</th></tr>",
+        strings.html("name"),
+        strings.html("line_coverage_before"),
+        strings.html("branch_coverage_before"),
+        after = strings.html("after"),
+        change = strings.html("change"),
+    )?;
+
+    write!(
+        out,
+        "\t\t<tr><th scope=\"row\">{}</th>",
+        strings.html("total")
+    )?;
+    format_change(out, &diff.line_rate, rounding, strings)?;
+    format_change(out, &diff.branch_rate, rounding, strings)?;
+    writeln!(out, "</tr>")?;
+
+    for package in &diff.packages {
+        write!(
+            out,
+            "\t\t<tr class=\"package\"><th scope=\"row\">{}</th>",
+            HtmlGenerator::escape(&package.name)
+        )?;
+        format_change(out, &package.line_rate, rounding, strings)?;
+        format_change(out, &package.branch_rate, rounding, strings)?;
+        writeln!(out, "</tr>")?;
+
+        for class in &package.classes {
+            let moved = match &class.moved_from {
+                Some(from) => format!(
+                    " <span class=\"moved\">{}</span>",
+                    HtmlGenerator::escape(&strings.format(
+                        "moved_from",
+                        &[
+                            ("name", &from.name),
+                            ("file", &from.file_name.to_string_lossy()),
+                        ],
+                    ))
+                ),
+                None => String::new(),
+            };
+            write!(
+                out,
+                "\t\t<tr><td>{}{moved}</td>",
+                HtmlGenerator::escape(&class.name)
+            )?;
+            format_change(out, &class.line_rate, rounding, strings)?;
+            format_change(out, &class.branch_rate, rounding, strings)?;
+            writeln!(out, "</tr>")?;
+        }
+    }
+
+    writeln!(out, "\t</table>")?;
+
+    writeln!(
+        out,
+        "\n\t<h2>{}</h2>",
+        strings.html("newly_uncovered_lines")
+    )?;
+    if diff.newly_uncovered.is_empty() {
+        return writeln!(out, "\t<p>{}</p>", strings.html("none"));
+    }
+
+    writeln!(out, "\t<ul>")?;
+    for file in &diff.newly_uncovered {
+        writeln!(
+            out,
+            "\t\t<li><code>{}</code></li>",
+            HtmlGenerator::escape(&file.to_string())
+        )?;
+    }
+    writeln!(out, "\t</ul>")
+}
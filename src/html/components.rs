@@ -0,0 +1,47 @@
+use crate::RoundingPolicy;
+
+use super::HtmlGenerator;
+
+/// A bar showing `rate`, with the covered part in green and the uncovered
+/// part in red, and the percentage on top. Rates that can't be computed are
+/// shown as `-`.
+pub(crate) fn coverage_bar(rate: Option<f64>, rounding: &RoundingPolicy) -> String {
+    let Some(rate) = rate else {
+        return "<span class=\"coverage-bar empty\">-</span>".to_string();
+    };
+
+    let percentage = rounding.format_rate(rate);
+    let width = rate.clamp(0.0, 1.0) * 100.0;
+
+    format!(
+        "<span class=\"coverage-bar\" role=\"img\" aria-label=\"{percentage}\"><span class=\"coverage-bar-fill\" style=\"width: {width:.2}%\"></span><span class=\"coverage-bar-label\">{percentage}</span></span>"
+    )
+}
+
+/// A small line chart of `rates` over consecutive runs, oldest first, with
+/// `None` for runs without the rate. `label` describes the chart for screen
+/// readers. Empty if fewer than two rates are known.
+pub(crate) fn sparkline(rates: &[Option<f64>], label: &str) -> String {
+    const WIDTH: f64 = 60.0;
+    const HEIGHT: f64 = 16.0;
+
+    if rates.iter().flatten().count() < 2 {
+        return String::new();
+    }
+
+    let step = WIDTH / (rates.len() - 1) as f64;
+    let points: Vec<_> = rates
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, rate)| {
+            let y = (1.0 - (*rate)?.clamp(0.0, 1.0)) * (HEIGHT - 2.0) + 1.0;
+            Some(format!("{:.1},{y:.1}", idx as f64 * step))
+        })
+        .collect();
+
+    format!(
+        "<svg class=\"sparkline\" role=\"img\" aria-label=\"{}\" width=\"{WIDTH}\" height=\"{HEIGHT}\" viewBox=\"0 0 {WIDTH} {HEIGHT}\"><polyline points=\"{}\"/></svg>",
+        HtmlGenerator::escape(label),
+        points.join(" ")
+    )
+}
@@ -0,0 +1,150 @@
+use std::io::{self, Write};
+
+use crate::{Coverage, RoundingPolicy, WorstCoveredOptions, WorstOrder};
+
+use super::{HtmlGenerator, HtmlOptions};
+
+// The colors of the light scheme of `report.css`. Mail clients neither
+// support stylesheets nor custom properties, so every element is styled
+// inline.
+const TEXT: &str = "#1f2328";
+const MUTED: &str = "#59636e";
+const COVERED: &str = "#1a7f37";
+const UNCOVERED: &str = "#cf222e";
+
+/// The number of files listed in a digest.
+const WORST_FILES: usize = 10;
+
+const CELL: &str = "padding: 4px 8px; border-bottom: 1px solid #d1d9e0;";
+const NUMBER_CELL: &str = "padding: 4px 8px; border-bottom: 1px solid #d1d9e0; text-align: right;";
+
+// The change of a rate since the previous run, colored by its direction.
+fn change(rate: f64, previous: Option<f64>, rounding: &RoundingPolicy) -> String {
+    let Some(previous) = previous else {
+        return format!("<span style=\"color: {MUTED};\">-</span>");
+    };
+
+    let delta = rate - previous;
+    let color = match rounding.round(delta * 100.0) {
+        d if d > 0.0 => COVERED,
+        d if d < 0.0 => UNCOVERED,
+        _ => MUTED,
+    };
+    format!(
+        "<span style=\"color: {color};\">{}</span>",
+        rounding.format_change(delta)
+    )
+}
+
+impl HtmlGenerator {
+    /// Write a compact digest of `coverage` that can be sent by e-mail: a
+    /// single page without scripts and with inline styles only. It shows
+    /// the title and build information of `options.metadata`, the overall
+    /// coverage with its change since `options.previous`, and the files
+    /// with the lowest line coverage.
+    pub fn generate_digest(
+        coverage: &Coverage,
+        options: &HtmlOptions,
+        mut out: impl Write,
+    ) -> io::Result<()> {
+        let strings = &options.translation;
+        let rounding = &options.rounding;
+        let previous = options.previous.as_ref();
+        let title = options
+            .metadata
+            .title
+            .as_deref()
+            .map_or_else(|| strings.html("coverage"), Self::escape);
+
+        write!(
+            out,
+            "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body style=\"margin: 0; padding: 16px; font-family: Arial, Helvetica, sans-serif; font-size: 14px; color: {TEXT};\">\n<h1 style=\"margin: 0 0 8px; font-size: 20px;\">{title}</h1>\n",
+            strings.html("lang"),
+        )?;
+
+        let details: Vec<_> = options
+            .metadata
+            .details()
+            .map(|(key, value)| {
+                let value = Self::escape(value);
+                let value = if key == "build_url" {
+                    format!("<a href=\"{value}\" style=\"color: {TEXT};\">{value}</a>")
+                } else {
+                    format!("<code>{value}</code>")
+                };
+                format!("{}: {value}", strings.html(key))
+            })
+            .collect();
+        if !details.is_empty() {
+            writeln!(
+                out,
+                "<p style=\"margin: 0 0 16px; color: {MUTED};\">{}</p>",
+                details.join(" &middot; ")
+            )?;
+        }
+
+        writeln!(
+            out,
+            "<table cellspacing=\"0\" style=\"border-collapse: collapse; margin-bottom: 16px;\">"
+        )?;
+        for (key, rate, previous, covered, valid) in [
+            (
+                "line_coverage",
+                coverage.line_rate,
+                previous.map(|p| p.line_rate),
+                coverage.lines_covered,
+                coverage.lines_valid,
+            ),
+            (
+                "branch_coverage",
+                coverage.branch_rate,
+                previous.map(|p| p.branch_rate),
+                coverage.branches_covered,
+                coverage.branches_valid,
+            ),
+        ] {
+            writeln!(
+                out,
+                "<tr><th scope=\"row\" style=\"{CELL} text-align: left;\">{}</th><td style=\"{NUMBER_CELL} font-weight: bold;\">{}</td><td style=\"{NUMBER_CELL}\">{}</td><td style=\"{NUMBER_CELL} color: {MUTED};\">{covered} / {valid}</td></tr>",
+                strings.html(key),
+                rounding.format_rate(rate),
+                change(rate, previous, rounding),
+            )?;
+        }
+        writeln!(out, "</table>")?;
+
+        let files = coverage.worst_covered(&WorstCoveredOptions {
+            count: WORST_FILES,
+            order: WorstOrder::Rate,
+            ranking: options.ranking.clone(),
+            ..Default::default()
+        });
+
+        if !files.is_empty() {
+            writeln!(
+                out,
+                "<h2 style=\"margin: 0 0 8px; font-size: 16px;\">{}</h2>",
+                strings.html("least_covered_files")
+            )?;
+            writeln!(
+                out,
+                "<table cellspacing=\"0\" style=\"border-collapse: collapse;\">\n<tr><th style=\"{CELL} text-align: left;\">{}</th><th style=\"{NUMBER_CELL}\">{}</th><th style=\"{NUMBER_CELL}\">{}</th></tr>",
+                strings.html("name"),
+                strings.html("line_coverage"),
+                strings.html("missed"),
+            )?;
+            for file in files {
+                writeln!(
+                    out,
+                    "<tr><td style=\"{CELL}\"><code>{}</code></td><td style=\"{NUMBER_CELL}\">{}</td><td style=\"{NUMBER_CELL} color: {UNCOVERED};\">{}</td></tr>",
+                    Self::escape(&file.name),
+                    rounding.format_rate(file.rates.line_rate.unwrap_or(0.0)),
+                    file.uncovered(),
+                )?;
+            }
+            writeln!(out, "</table>")?;
+        }
+
+        writeln!(out, "</body>\n</html>")
+    }
+}
@@ -0,0 +1,64 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{DirSummary, RoundingPolicy};
+
+use super::{components::coverage_bar, HtmlGenerator, Translation};
+
+/// Write the body of the directories page: a collapsible tree of `root`,
+/// with the coverage of every directory and file. Files link to the page of
+/// their (first) class in `pages`.
+pub(crate) fn write_directories(
+    out: &mut impl Write,
+    root: &DirSummary,
+    pages: &HashMap<&Path, String>,
+    rounding: &RoundingPolicy,
+    strings: &Translation,
+) -> io::Result<()> {
+    writeln!(out, "\n\t<h1>{}</h1>", strings.html("directories"))?;
+    writeln!(
+        out,
+        "\t<p><code>{}</code> {}</p>",
+        HtmlGenerator::escape(&root.path.display().to_string()),
+        coverage_bar(root.rates.line_rate, rounding)
+    )?;
+
+    write_contents(out, root, pages, rounding, 1)
+}
+
+fn write_contents(
+    out: &mut impl Write,
+    dir: &DirSummary,
+    pages: &HashMap<&Path, String>,
+    rounding: &RoundingPolicy,
+    depth: usize,
+) -> io::Result<()> {
+    let indent = "\t".repeat(depth);
+
+    for child in &dir.directories {
+        writeln!(
+            out,
+            "{indent}<details class=\"directory\">\n{indent}\t<summary>{} {}</summary>",
+            HtmlGenerator::escape(&child.name(&dir.path)),
+            coverage_bar(child.rates.line_rate, rounding)
+        )?;
+        write_contents(out, child, pages, rounding, depth + 1)?;
+        writeln!(out, "{indent}</details>")?;
+    }
+
+    for file in &dir.files {
+        let name = file.path.file_name().unwrap_or(file.path.as_os_str());
+        let name = HtmlGenerator::escape(&name.to_string_lossy());
+        let rate = coverage_bar(file.line_rate, rounding);
+
+        match pages.get(file.path) {
+            Some(href) => writeln!(out, "{indent}<p><a href=\"{href}\">{name}</a> {rate}</p>")?,
+            None => writeln!(out, "{indent}<p>{name} {rate}</p>")?,
+        }
+    }
+
+    Ok(())
+}
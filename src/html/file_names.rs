@@ -0,0 +1,125 @@
+use std::collections::HashSet;
+
+// Leave some room for suffixes and extensions within common file name limits.
+const MAX_STEM_LEN: usize = 120;
+
+const RESERVED_STEMS: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// 64-bit FNV-1a hash. Used wherever we need a hash that is stable across
+/// runs, platforms and compiler versions.
+pub(crate) fn fnv1a_64(data: impl AsRef<[u8]>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data.as_ref() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Turn an arbitrary display name into something that is safe to use as a
+/// file name stem on all common platforms.
+///
+/// The result only contains ASCII alphanumerics, `-`, `_` and `.`, never
+/// starts with a `.`, and is never empty.
+pub(crate) fn sanitize(name: &str) -> String {
+    let mut out = String::with_capacity(name.len().min(MAX_STEM_LEN));
+
+    for c in name.chars() {
+        if out.len() >= MAX_STEM_LEN {
+            break;
+        }
+
+        let c = match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+            '.' if !out.is_empty() => c,
+            _ => '_',
+        };
+
+        out.push(c);
+    }
+
+    // Trailing dots are silently stripped on Windows.
+    while out.ends_with('.') {
+        out.pop();
+    }
+
+    // Windows reserves device names regardless of any extension, so
+    // `con.Foo.html` is just as unusable as `con.html`.
+    let device = out.split('.').next().unwrap_or_default();
+    if out.is_empty() || RESERVED_STEMS.contains(&device.to_ascii_lowercase().as_str()) {
+        out.insert(0, '_');
+    }
+
+    out
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileNameEntry {
+    pub display_name: String,
+    pub file_name: String,
+}
+
+/// Maps display names (e.g. class names) to unique, sanitized file names.
+///
+/// File names are assigned deterministically in insertion order. If two
+/// display names sanitize to the same file name (compared case-insensitively),
+/// the later one gets a hash suffix.
+#[derive(Debug, Clone, Default)]
+pub struct FileNameIndex {
+    extension: String,
+    entries: Vec<FileNameEntry>,
+    used: HashSet<String>,
+}
+
+impl FileNameIndex {
+    pub fn new(extension: impl Into<String>) -> Self {
+        Self {
+            extension: extension.into(),
+            entries: Vec::new(),
+            used: HashSet::new(),
+        }
+    }
+
+    /// Assign a new file name to `display_name`.
+    ///
+    /// Inserting the same display name twice yields two distinct file names.
+    pub fn insert(&mut self, display_name: &str) -> &str {
+        let stem = sanitize(display_name);
+        let mut file_name = format!("{stem}.{}", self.extension);
+        let mut attempt = 0u32;
+
+        while self.used.contains(&file_name.to_ascii_lowercase()) {
+            let hash = if attempt == 0 {
+                fnv1a_64(display_name)
+            } else {
+                fnv1a_64(format!("{display_name}#{attempt}"))
+            };
+
+            file_name = format!("{stem}-{hash:016x}.{}", self.extension);
+            attempt += 1;
+        }
+
+        self.used.insert(file_name.to_ascii_lowercase());
+        self.entries.push(FileNameEntry {
+            display_name: display_name.to_string(),
+            file_name,
+        });
+
+        &self.entries.last().unwrap().file_name
+    }
+
+    /// The file name assigned to the first occurrence of `display_name`.
+    pub fn get(&self, display_name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|e| e.display_name == display_name)
+            .map(|e| e.file_name.as_str())
+    }
+
+    pub fn entries(&self) -> &[FileNameEntry] {
+        &self.entries
+    }
+}
@@ -0,0 +1,39 @@
+use std::io::{self, Write};
+
+use crate::{Coverage, RoundingPolicy};
+
+use super::{components::coverage_bar, HtmlGenerator, Translation};
+
+/// Write the section of the index that lists the classes of generated code.
+pub(crate) fn write_generated(
+    out: &mut impl Write,
+    generated: &Coverage,
+    rounding: &RoundingPolicy,
+    strings: &Translation,
+) -> io::Result<()> {
+    write!(out, "\n\t<section class=\"generated\">")?;
+    write!(out, "\n\t<h2>{}</h2>", strings.html("generated_code"))?;
+    write!(out, "\n\t<table>")?;
+    write!(
+        out,
+        "\n\t\t<tr><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{}</th></tr>",
+        strings.html("class"),
+        strings.html("file"),
+        strings.html("line_coverage"),
+        strings.html("branch_coverage"),
+    )?;
+
+    for class in generated.classes() {
+        let rates = class.computed_rates(generated.min_hits);
+        write!(
+            out,
+            "\n\t\t<tr><td>{}</td><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+            HtmlGenerator::escape(&class.name),
+            HtmlGenerator::escape(&class.file_name.to_string_lossy()),
+            coverage_bar(rates.line_rate, rounding),
+            coverage_bar(rates.branch_rate, rounding),
+        )?;
+    }
+
+    write!(out, "\n\t</table>\n\t</section>")
+}
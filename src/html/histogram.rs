@@ -0,0 +1,49 @@
+use std::io::{self, Write};
+
+use crate::HitHistogram;
+
+use super::{HtmlGenerator, Translation};
+
+/// Write the section of the index with a bar chart of the lines by their
+/// hits.
+pub(crate) fn write_histogram(
+    out: &mut impl Write,
+    histogram: &HitHistogram,
+    strings: &Translation,
+) -> io::Result<()> {
+    let value = |hits: Option<u64>| hits.map_or("-".to_string(), |h| h.to_string());
+
+    write!(out, "\n\t<section class=\"hits\">")?;
+    write!(out, "\n\t<h2>{}</h2>", strings.html("lines_by_hits"))?;
+    write!(
+        out,
+        "\n\t<p>{}</p>",
+        HtmlGenerator::escape(&strings.format(
+            "hit_summary",
+            &[
+                ("median", &value(histogram.median())),
+                ("p90", &value(histogram.percentile(90.0))),
+                ("max", &value(histogram.max())),
+            ],
+        ))
+    )?;
+    write!(out, "\n\t<table class=\"hit-histogram\">")?;
+    write!(
+        out,
+        "\n\t\t<tr><th scope=\"col\">{}</th><th scope=\"col\">{}</th></tr>",
+        strings.html("hits"),
+        strings.html("lines"),
+    )?;
+
+    let largest = histogram.buckets.iter().copied().max().unwrap_or(0).max(1);
+    for (label, count) in HitHistogram::BUCKETS.iter().zip(histogram.buckets) {
+        write!(
+            out,
+            "\n\t\t<tr><td>{}</td><td><span class=\"hit-bar\" style=\"width: {:.2}rem\"></span> {count}</td></tr>",
+            HtmlGenerator::escape(label),
+            count as f64 / largest as f64 * 16.0,
+        )?;
+    }
+
+    write!(out, "\n\t</table>\n\t</section>")
+}
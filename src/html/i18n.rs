@@ -0,0 +1,333 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use super::HtmlGenerator;
+
+// Every string of the report, by key. Other languages may leave out keys, in
+// which case the English string is used.
+static ENGLISH: &[(&str, &str)] = &[
+    // The language code of the strings, for the `lang` attribute.
+    ("lang", "en"),
+    ("line_coverage", "Line coverage"),
+    ("branch_coverage", "Branch coverage"),
+    (
+        "class_summary",
+        "Line coverage: {line}, branch coverage: {branch}",
+    ),
+    ("branches", "Branches"),
+    ("branches_of", "Branches of {name}"),
+    ("line", "Line"),
+    ("hits", "Hits"),
+    ("coverage", "Coverage"),
+    ("missed", "Missed"),
+    ("conditions", "Conditions"),
+    ("condition_coverage", "Condition coverage"),
+    ("methods", "Methods"),
+    ("method", "Method"),
+    ("complexity", "Complexity"),
+    ("crap", "CRAP"),
+    ("tests", "Tests"),
+    ("comparison", "Coverage comparison"),
+    ("name", "Name"),
+    ("line_coverage_before", "Line coverage before"),
+    ("branch_coverage_before", "Branch coverage before"),
+    ("after", "after"),
+    ("change", "Change"),
+    ("total", "Total"),
+    ("new", "new"),
+    ("removed", "removed"),
+    ("newly_uncovered_lines", "Newly uncovered lines"),
+    ("none", "None"),
+    ("other_project", "Other"),
+    ("directories", "Directories"),
+    ("search_classes", "Search classes"),
+    ("all_packages", "All packages"),
+    ("default_package", "(default package)"),
+    ("line_coverage_range", "Line coverage {min}% - {max}%"),
+    ("package_filter", "Package"),
+    ("minimum_coverage", "Minimum line coverage"),
+    ("maximum_coverage", "Maximum line coverage"),
+    ("classes_shown", "{count} of {total} classes shown"),
+    ("owners", "Owners"),
+    ("owner", "Owner"),
+    ("files", "Files"),
+    ("no_owner", "(no owner)"),
+    ("components", "Components"),
+    ("component", "Component"),
+    ("no_component", "(no component)"),
+    ("tag", "Tag"),
+    ("commit", "Commit"),
+    ("branch", "Branch"),
+    ("build_url", "Build"),
+    ("packages", "Packages"),
+    ("package", "Package"),
+    ("class", "Class"),
+    ("lines_covered", "{covered} of {total} lines covered"),
+    ("missing_branches", "Missed jumps to line(s) {lines}"),
+    (
+        "line_coverage_trend",
+        "Line coverage of the last runs: {rates}",
+    ),
+    ("moved_from", "(moved from {name} in {file})"),
+    ("least_covered_files", "Least covered files"),
+    ("least_covered_classes", "Least covered classes"),
+    ("generated_code", "Generated code"),
+    ("file", "File"),
+    ("lines_by_hits", "Lines by hits"),
+    ("lines", "Lines"),
+    (
+        "hit_summary",
+        "Median: {median} hits, 90th percentile: {p90} hits, maximum: {max} hits",
+    ),
+];
+
+static GERMAN: &[(&str, &str)] = &[
+    ("lang", "de"),
+    ("line_coverage", "Zeilenabdeckung"),
+    ("branch_coverage", "Zweigabdeckung"),
+    (
+        "class_summary",
+        "Zeilenabdeckung: {line}, Zweigabdeckung: {branch}",
+    ),
+    ("branches", "Zweige"),
+    ("branches_of", "Zweige von {name}"),
+    ("line", "Zeile"),
+    ("hits", "Treffer"),
+    ("coverage", "Abdeckung"),
+    ("missed", "Verfehlt"),
+    ("conditions", "Bedingungen"),
+    ("condition_coverage", "Bedingungsabdeckung"),
+    ("methods", "Methoden"),
+    ("method", "Methode"),
+    ("complexity", "Komplexität"),
+    ("comparison", "Abdeckungsvergleich"),
+    ("line_coverage_before", "Zeilenabdeckung vorher"),
+    ("branch_coverage_before", "Zweigabdeckung vorher"),
+    ("after", "nachher"),
+    ("change", "Änderung"),
+    ("total", "Gesamt"),
+    ("new", "neu"),
+    ("removed", "entfernt"),
+    ("newly_uncovered_lines", "Neu nicht abgedeckte Zeilen"),
+    ("none", "Keine"),
+    ("other_project", "Sonstige"),
+    ("directories", "Verzeichnisse"),
+    ("search_classes", "Klassen suchen"),
+    ("all_packages", "Alle Pakete"),
+    ("default_package", "(Standardpaket)"),
+    ("line_coverage_range", "Zeilenabdeckung {min}% - {max}%"),
+    ("package_filter", "Paket"),
+    ("minimum_coverage", "Minimale Zeilenabdeckung"),
+    ("maximum_coverage", "Maximale Zeilenabdeckung"),
+    ("classes_shown", "{count} von {total} Klassen angezeigt"),
+    ("owners", "Verantwortliche"),
+    ("owner", "Verantwortlich"),
+    ("files", "Dateien"),
+    ("no_owner", "(niemand)"),
+    ("components", "Komponenten"),
+    ("component", "Komponente"),
+    ("no_component", "(keine Komponente)"),
+    ("tag", "Tag"),
+    ("commit", "Commit"),
+    ("branch", "Branch"),
+    ("build_url", "Build"),
+    ("packages", "Pakete"),
+    ("package", "Paket"),
+    ("class", "Klasse"),
+    ("lines_covered", "{covered} von {total} Zeilen abgedeckt"),
+    ("missing_branches", "Verpasste Sprünge zu Zeile(n) {lines}"),
+    (
+        "line_coverage_trend",
+        "Zeilenabdeckung der letzten Läufe: {rates}",
+    ),
+    ("moved_from", "(verschoben von {name} in {file})"),
+    ("least_covered_files", "Am wenigsten abgedeckte Dateien"),
+    ("least_covered_classes", "Am wenigsten abgedeckte Klassen"),
+    ("generated_code", "Generierter Code"),
+    ("file", "Datei"),
+    ("lines_by_hits", "Zeilen nach Treffern"),
+    ("lines", "Zeilen"),
+    (
+        "hit_summary",
+        "Median: {median} Treffer, 90. Perzentil: {p90} Treffer, Maximum: {max} Treffer",
+    ),
+];
+
+static FRENCH: &[(&str, &str)] = &[
+    ("lang", "fr"),
+    ("line_coverage", "Couverture des lignes"),
+    ("branch_coverage", "Couverture des branches"),
+    (
+        "class_summary",
+        "Couverture des lignes : {line}, couverture des branches : {branch}",
+    ),
+    ("branches", "Branches"),
+    ("branches_of", "Branches de {name}"),
+    ("line", "Ligne"),
+    ("hits", "Exécutions"),
+    ("coverage", "Couverture"),
+    ("missed", "Manquées"),
+    ("conditions", "Conditions"),
+    ("condition_coverage", "Couverture des conditions"),
+    ("methods", "Méthodes"),
+    ("method", "Méthode"),
+    ("complexity", "Complexité"),
+    ("comparison", "Comparaison de la couverture"),
+    ("name", "Nom"),
+    ("line_coverage_before", "Couverture des lignes avant"),
+    ("branch_coverage_before", "Couverture des branches avant"),
+    ("after", "après"),
+    ("change", "Évolution"),
+    ("new", "nouveau"),
+    ("removed", "supprimé"),
+    ("newly_uncovered_lines", "Lignes nouvellement non couvertes"),
+    ("none", "Aucune"),
+    ("other_project", "Autres"),
+    ("directories", "Répertoires"),
+    ("search_classes", "Rechercher des classes"),
+    ("all_packages", "Tous les paquets"),
+    ("default_package", "(paquet par défaut)"),
+    (
+        "line_coverage_range",
+        "Couverture des lignes {min} % - {max} %",
+    ),
+    ("package_filter", "Paquet"),
+    ("minimum_coverage", "Couverture des lignes minimale"),
+    ("maximum_coverage", "Couverture des lignes maximale"),
+    ("classes_shown", "{count} classes sur {total} affichées"),
+    ("owners", "Responsables"),
+    ("owner", "Responsable"),
+    ("files", "Fichiers"),
+    ("no_owner", "(aucun responsable)"),
+    ("components", "Composants"),
+    ("component", "Composant"),
+    ("no_component", "(aucun composant)"),
+    ("tag", "Étiquette"),
+    ("commit", "Commit"),
+    ("branch", "Branche"),
+    ("build_url", "Build"),
+    ("packages", "Paquets"),
+    ("package", "Paquet"),
+    ("class", "Classe"),
+    ("lines_covered", "{covered} lignes couvertes sur {total}"),
+    (
+        "missing_branches",
+        "Sauts manqués vers la/les ligne(s) {lines}",
+    ),
+    (
+        "line_coverage_trend",
+        "Couverture des lignes des dernières exécutions : {rates}",
+    ),
+    ("moved_from", "(déplacée depuis {name} dans {file})"),
+    ("least_covered_files", "Fichiers les moins couverts"),
+    ("least_covered_classes", "Classes les moins couvertes"),
+    ("generated_code", "Code généré"),
+    ("file", "Fichier"),
+    ("lines_by_hits", "Lignes par nombre d'exécutions"),
+    ("lines", "Lignes"),
+    (
+        "hit_summary",
+        "Médiane : {median} exécutions, 90e centile : {p90} exécutions, maximum : {max} exécutions",
+    ),
+];
+
+/// The built-in languages of the HTML report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+    French,
+}
+
+impl Language {
+    /// The language with the ISO 639-1 code `code`, e.g. `de`.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "en" => Some(Self::English),
+            "de" => Some(Self::German),
+            "fr" => Some(Self::French),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::German => "de",
+            Self::French => "fr",
+        }
+    }
+
+    fn strings(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::English => ENGLISH,
+            Self::German => GERMAN,
+            Self::French => FRENCH,
+        }
+    }
+}
+
+/// The strings used in the HTML report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Translation {
+    strings: BTreeMap<String, String>,
+}
+
+impl Default for Translation {
+    fn default() -> Self {
+        Self::builtin(Language::English)
+    }
+}
+
+impl Translation {
+    pub fn builtin(language: Language) -> Self {
+        let strings = ENGLISH
+            .iter()
+            .chain(language.strings())
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        Self { strings }
+    }
+
+    /// Load a translation from a JSON object of strings by key, such as
+    /// `{"line_coverage": "Line coverage"}`. Keys that are left out keep
+    /// their English string. Placeholders like `{name}` are filled in.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let custom: BTreeMap<String, String> =
+            serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        let mut translation = Self::default();
+        for (key, value) in custom {
+            match translation.strings.get_mut(&key) {
+                Some(string) => *string = value,
+                None => return Err(format!("unknown string `{key}`")),
+            }
+        }
+
+        Ok(translation)
+    }
+
+    /// The string for `key`.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings.get(key).map(String::as_str).unwrap_or(key)
+    }
+
+    /// The string for `key`, escaped for use in HTML.
+    pub(crate) fn html(&self, key: &str) -> String {
+        HtmlGenerator::escape(self.get(key))
+    }
+
+    /// The string for `key`, with each `{name}` placeholder replaced by its
+    /// value in `values`.
+    pub fn format(&self, key: &str, values: &[(&str, &str)]) -> String {
+        let mut string = self.get(key).to_string();
+        for (name, value) in values {
+            string = string.replace(&format!("{{{name}}}"), value);
+        }
+        string
+    }
+}
@@ -0,0 +1,129 @@
+const entries = index_data.classes;
+
+const filters = document.getElementById("filters");
+const results = document.getElementById("results");
+
+const search = document.createElement("input");
+search.type = "search";
+search.placeholder = strings.search_classes;
+search.setAttribute("aria-label", strings.search_classes);
+search.setAttribute("aria-keyshortcuts", "/");
+
+const minCoverage = document.createElement("input");
+minCoverage.type = "range";
+minCoverage.min = 0;
+minCoverage.max = 100;
+minCoverage.value = 0;
+minCoverage.setAttribute("aria-label", strings.minimum_coverage);
+
+const maxCoverage = document.createElement("input");
+maxCoverage.type = "range";
+maxCoverage.min = 0;
+maxCoverage.max = 100;
+maxCoverage.value = 100;
+maxCoverage.setAttribute("aria-label", strings.maximum_coverage);
+
+const coverageLabel = document.createElement("span");
+
+const packageFilter = document.createElement("select");
+packageFilter.setAttribute("aria-label", strings.package_filter);
+const allPackages = document.createElement("option");
+allPackages.value = "";
+allPackages.textContent = strings.all_packages;
+packageFilter.appendChild(allPackages);
+
+for (const name of [...new Set(entries.map((e) => e.package))].sort()) {
+    const option = document.createElement("option");
+    option.value = name;
+    option.textContent = name === "" ? strings.default_package : name;
+    packageFilter.appendChild(option);
+}
+
+filters.append(search, packageFilter, minCoverage, maxCoverage, coverageLabel);
+
+function apply() {
+    const query = search.value.trim().toLowerCase();
+    const min = Math.min(Number(minCoverage.value), Number(maxCoverage.value));
+    const max = Math.max(Number(minCoverage.value), Number(maxCoverage.value));
+    const packageName = packageFilter.value;
+
+    coverageLabel.textContent = strings.line_coverage_range
+        .replace("{min}", min)
+        .replace("{max}", max);
+
+    let shown = 0;
+
+    entries.forEach((entry, idx) => {
+        const visible =
+            (query === "" || entry.name.toLowerCase().includes(query)) &&
+            (packageName === "" || entry.package === packageName) &&
+            entry.line_coverage >= min &&
+            entry.line_coverage <= max;
+
+        document.querySelector(`[data-index="${idx}"]`).hidden = !visible;
+        shown += visible;
+    });
+
+    results.textContent = strings.classes_shown
+        .replace("{count}", shown)
+        .replace("{total}", entries.length);
+
+    for (const namespace of document.querySelectorAll("details.namespace")) {
+        namespace.hidden = namespace.querySelector("[data-index]:not([hidden])") === null;
+        if (query !== "") {
+            namespace.open = true;
+        }
+    }
+
+    for (const section of document.querySelectorAll("section.project")) {
+        section.hidden = section.querySelector("[data-index]:not([hidden])") === null;
+    }
+}
+
+for (const input of [search, minCoverage, maxCoverage, packageFilter]) {
+    input.addEventListener("input", apply);
+}
+
+apply();
+
+// The links of the classes that are currently shown, in document order.
+function visibleLinks() {
+    return [...document.querySelectorAll("[data-index] > a")].filter(
+        (link) => link.closest("[hidden]") === null,
+    );
+}
+
+// `/` focuses the search, and the arrow keys move between the shown classes.
+document.addEventListener("keydown", (event) => {
+    if (event.key === "/" && document.activeElement !== search) {
+        event.preventDefault();
+        search.focus();
+        return;
+    }
+
+    if (event.key !== "ArrowDown" && event.key !== "ArrowUp") {
+        return;
+    }
+
+    const inList = document.activeElement.parentElement?.hasAttribute("data-index");
+    if (document.activeElement !== search && !inList) {
+        return;
+    }
+
+    const links = visibleLinks();
+    const current = links.indexOf(document.activeElement);
+    const next = event.key === "ArrowDown" ? current + 1 : current - 1;
+
+    event.preventDefault();
+    if (next < 0) {
+        search.focus();
+    } else if (next < links.length) {
+        // Expand the namespaces around the class, so that it can be focused.
+        let details = links[next].closest("details");
+        while (details !== null) {
+            details.open = true;
+            details = details.parentElement.closest("details");
+        }
+        links[next].focus();
+    }
+});
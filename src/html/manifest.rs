@@ -0,0 +1,76 @@
+use std::{collections::BTreeMap, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ReportSink;
+
+use super::file_names::fnv1a_64;
+
+const MANIFEST_FILE: &str = ".manifest.json";
+
+/// The hashes of all pages written to an output directory, used to skip
+/// pages that did not change since the previous run.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Manifest {
+    pages: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    /// Load the manifest of `sink`. A missing or unreadable manifest is
+    /// treated as empty, which simply causes all pages to be written.
+    pub(crate) fn load(sink: &dyn ReportSink) -> Self {
+        sink.read(MANIFEST_FILE)
+            .ok()
+            .flatten()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self, sink: &mut dyn ReportSink) -> io::Result<()> {
+        let data = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        sink.write(MANIFEST_FILE, &data)
+    }
+
+    /// Record the page `file_name`, and return whether it needs to be
+    /// written to `sink`, i.e. unless `previous` shows that it already has
+    /// the same contents.
+    pub(crate) fn record_page(
+        &mut self,
+        previous: &Manifest,
+        sink: &dyn ReportSink,
+        file_name: &str,
+        contents: &[u8],
+    ) -> bool {
+        let hash = format!("{:016x}", fnv1a_64(contents));
+
+        let unchanged = previous.pages.get(file_name) == Some(&hash) && sink.contains(file_name);
+        self.pages.insert(file_name.to_string(), hash);
+
+        if unchanged {
+            log::trace!("Skipping unchanged page {file_name}");
+        }
+        !unchanged
+    }
+
+    /// Remove the pages that are in `previous`, but were not written in this
+    /// run.
+    pub(crate) fn remove_stale(
+        &self,
+        previous: &Manifest,
+        sink: &mut dyn ReportSink,
+    ) -> io::Result<()> {
+        for file_name in previous.pages.keys() {
+            // Only ever remove plain file names, even if the manifest has been
+            // tampered with.
+            let is_plain = Path::new(file_name).file_name() == Some(file_name.as_ref());
+            if self.pages.contains_key(file_name) || !is_plain {
+                continue;
+            }
+
+            log::debug!("Removing stale page {file_name}");
+            sink.remove(file_name)?;
+        }
+
+        Ok(())
+    }
+}
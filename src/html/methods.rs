@@ -0,0 +1,165 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{Class, LineProvenance, Method, RoundingPolicy, TestAttribution};
+
+use super::{components::coverage_bar, HtmlGenerator, Translation};
+
+fn anchor(id_prefix: &str, idx: usize) -> String {
+    format!("{id_prefix}method-{idx}")
+}
+
+/// Write the method table of `class`, linking to the detail section of each
+/// method.
+pub(crate) fn write_method_table(
+    out: &mut impl Write,
+    class: &Class,
+    rounding: &RoundingPolicy,
+    strings: &Translation,
+    id_prefix: &str,
+) -> io::Result<()> {
+    if class.methods.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(out, "\n\t<h2>{}</h2>", strings.html("methods"))?;
+    writeln!(out, "\t<table class=\"sortable\">")?;
+    writeln!(
+        out,
+        "\t\t<thead><tr><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{}</th></tr></thead>",
+        strings.html("method"),
+        strings.html("line_coverage"),
+        strings.html("branch_coverage"),
+        strings.html("complexity"),
+        strings.html("crap")
+    )?;
+    writeln!(out, "\t\t<tbody>")?;
+
+    for (idx, method) in class.methods.iter().enumerate() {
+        let crap = method.crap_score();
+
+        writeln!(
+            out,
+            "\t\t<tr><td><a href=\"#{}\">{}</a></td><td data-sort=\"{}\">{}</td><td data-sort=\"{}\">{}</td><td>{}</td><td data-sort=\"{crap}\">{crap:.1}</td></tr>",
+            anchor(id_prefix, idx),
+            HtmlGenerator::escape(&method.name),
+            method.line_rate,
+            coverage_bar(Some(method.line_rate), rounding),
+            method.branch_rate,
+            coverage_bar(Some(method.branch_rate), rounding),
+            method.complexity,
+        )?;
+    }
+
+    writeln!(out, "\t\t</tbody>")?;
+
+    writeln!(out, "\t</table>")
+}
+
+/// Write one anchored section per method of `class`, with the readable
+/// signature (and the raw one as its title) and the hits and condition
+/// coverage of each of its lines, and the tests that cover them if `tests` is
+/// given. With `provenance`, the hits have a tooltip listing the inputs they
+/// came from.
+pub(crate) fn write_method_sections(
+    out: &mut impl Write,
+    class: &Class,
+    strings: &Translation,
+    id_prefix: &str,
+    tests: Option<&TestAttribution>,
+    provenance: Option<&LineProvenance>,
+    min_hits: u64,
+) -> io::Result<()> {
+    for (idx, method) in class.methods.iter().enumerate() {
+        let id = anchor(id_prefix, idx);
+        write_method_section(
+            out,
+            &id,
+            &class.file_name,
+            method,
+            strings,
+            tests,
+            provenance,
+            min_hits,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn write_method_section(
+    out: &mut impl Write,
+    id: &str,
+    file: &Path,
+    method: &Method,
+    strings: &Translation,
+    tests: Option<&TestAttribution>,
+    provenance: Option<&LineProvenance>,
+    min_hits: u64,
+) -> io::Result<()> {
+    writeln!(out, "\n\t<section id=\"{id}\">")?;
+    writeln!(
+        out,
+        "\t<h3>{}</h3>\n\t<p><code title=\"{}{}\">{}{}</code></p>",
+        HtmlGenerator::escape(&method.name),
+        HtmlGenerator::escape(&method.name),
+        HtmlGenerator::escape(method.signature.as_deref().unwrap_or_default()),
+        HtmlGenerator::escape(&method.name),
+        HtmlGenerator::escape(&method.pretty_signature())
+    )?;
+    writeln!(out, "\t<table>")?;
+    write!(
+        out,
+        "\t\t<tr><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{}</th>",
+        strings.html("line"),
+        strings.html("hits"),
+        strings.html("condition_coverage")
+    )?;
+    if tests.is_some() {
+        write!(out, "<th scope=\"col\">{}</th>", strings.html("tests"))?;
+    }
+    writeln!(out, "</tr>")?;
+
+    for line in &method.lines {
+        let inputs: Vec<_> = provenance
+            .into_iter()
+            .flat_map(|p| p.inputs_for_line(file, line.number))
+            .map(|(input, hits)| format!("{input}: {hits}"))
+            .collect();
+        let hits_title = if inputs.is_empty() {
+            String::new()
+        } else {
+            format!(" title=\"{}\"", HtmlGenerator::escape(&inputs.join("\n")))
+        };
+
+        write!(
+            out,
+            "\t\t<tr class=\"{}\"><td>{}</td><td{hits_title}>{}</td><td>{}</td>",
+            if line.is_covered(min_hits) {
+                "covered"
+            } else {
+                "uncovered"
+            },
+            line.number,
+            line.hits,
+            HtmlGenerator::escape(line.condition_coverage.as_deref().unwrap_or(""))
+        )?;
+
+        if let Some(tests) = tests {
+            let names: Vec<_> = tests.tests_for_line(file, line.number).collect();
+            write!(
+                out,
+                "<td title=\"{}\">{}</td>",
+                HtmlGenerator::escape(&names.join("\n")),
+                names.len()
+            )?;
+        }
+
+        writeln!(out, "</tr>")?;
+    }
+
+    writeln!(out, "\t</table>\n\t</section>")
+}
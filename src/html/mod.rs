@@ -1,83 +1,782 @@
 use std::{
-    fs::File,
-    io::{BufWriter, Write},
+    collections::{BTreeMap, HashMap},
+    io::Write,
     path::PathBuf,
 };
 
 use serde::Serialize;
 
-use crate::Coverage;
+use crate::{
+    Class, CodeOwners, ComponentSummary, Coverage, CoverageDiff, DirectorySink, HistoryEntry,
+    LineProvenance, MemorySink, Package, Progress, ProgressUpdate, RankingFilter, ReportGenerator,
+    ReportMetadata, ReportSink, RoundingPolicy, SummaryGenerator, TestAttribution,
+    WorstCoveredOptions,
+};
+
+mod assets;
+mod branches;
+mod comparison;
+mod components;
+mod digest;
+mod directories;
+mod file_names;
+mod generated;
+mod histogram;
+mod i18n;
+mod manifest;
+mod methods;
+mod owners;
+mod packages;
+mod pages;
+mod tree;
+mod worst;
+
+use assets::{Assets, Heads};
+use pages::{Page, PageLinks, PageWriter};
+use tree::IndexEntry;
+
+pub use assets::AssetOptions;
+pub use file_names::{FileNameEntry, FileNameIndex};
+pub use i18n::{Language, Translation};
+
+static HTML_PREFIX: &str = include_str!("./prefix.part.html");
+static HTML_POSTFIX: &str = include_str!("./postfix.part.html");
+static CLASS_JS: &str = include_str!("./class/class.js");
+static CLASS_HTML: &str = include_str!("./class/class.html");
+static INDEX_JS: &str = include_str!("./index/index.js");
+static INDEX_HTML: &str = include_str!("./index/index.html");
+static REPORT_CSS: &str = include_str!("./report.css");
 
-static HTML_PREFIX: &'static str = include_str!("./prefix.part.html");
-static HTML_POSTFIX: &'static str = include_str!("./postfix.part.html");
-static CLASS_JS: &'static str = include_str!("./class/class.js");
-static CLASS_HTML: &'static str = include_str!("./class/class.html");
+// The external script of the index, which is inlined (together with the class
+// script) in single file reports.
+static INDEX_SCRIPT: &str = r#"<script type="module" src="./index.js" defer></script>"#;
+// The external stylesheet of all pages, which is inlined in single file
+// reports.
+static STYLESHEET: &str = r#"<link rel="stylesheet" href="./report.css">"#;
+// Only show the page that is linked to, or the index if there is none.
+static SINGLE_FILE_STYLE: &str = "\n\t<style>article:not(:target, :has(:target)) { display: none; } body:has(:target) > :not(article) { display: none; }</style>";
+
+// The number of class pages that are rendered before they are written, which
+// bounds the memory used for pages.
+const PAGE_BATCH: usize = 256;
+
+// A package with the names of its page and the pages of its classes. All
+// names are taken before any page is rendered, so that they don't depend on
+// the order in which pages are rendered.
+struct PackagePages<'a> {
+    package: &'a Package,
+    file_name: String,
+    href: String,
+    classes: Vec<ClassPages<'a>>,
+}
+
+struct ClassPages<'a> {
+    class: &'a Class,
+    file_name: String,
+    branches_file_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HtmlOptions {
+    pub rounding: RoundingPolicy,
+    /// Only rewrite class pages whose contents changed since the previous
+    /// run, according to the manifest in the output directory.
+    pub incremental: bool,
+    /// Write a single self-contained `index.html`, with all pages inlined.
+    pub single_file: bool,
+    /// Show which tests cover each line.
+    pub tests: Option<TestAttribution>,
+    /// Show which inputs contributed the hits of each line, as a tooltip.
+    pub provenance: Option<LineProvenance>,
+    /// Group the classes in the index into a collapsible tree of namespaces,
+    /// by splitting their names on this separator (e.g. `.`, `::` or `/`).
+    pub namespace_separator: Option<String>,
+    /// The strings of the report.
+    pub translation: Translation,
+    /// Add a section with the coverage of each owner to the index.
+    pub owners: Option<CodeOwners>,
+    /// Add a section with the coverage of each component to the index, e.g.
+    /// from `Coverage::by_component`.
+    pub components: Vec<ComponentSummary>,
+    /// The previous run, to show the change in coverage since then.
+    pub previous: Option<HistoryEntry>,
+    /// Previous runs, oldest first, to show a sparkline of the line coverage
+    /// of each class over these runs and this one in the index.
+    pub trend: Vec<HistoryEntry>,
+    /// Always use the high contrast color scheme, instead of only when the
+    /// browser asks for more contrast.
+    pub high_contrast: bool,
+    /// The title and build information shown at the top of the index.
+    pub metadata: ReportMetadata,
+    /// Reports the class pages written, and stops generating once
+    /// cancelled.
+    pub progress: Progress,
+    /// Minify, hash or inline the stylesheet and scripts.
+    pub assets: AssetOptions,
+    /// Add a section that lists the classes of generated code to the index,
+    /// e.g. from `Coverage::split_generated`.
+    pub generated: Option<Coverage>,
+    /// Add a chart of the lines by their hits to the index.
+    pub hit_histogram: bool,
+    /// Which files to leave out of ranked views, such as the least covered
+    /// files of the digest.
+    pub ranking: RankingFilter,
+    /// Add a section with the worst covered files or classes to the index.
+    pub worst_covered: Option<WorstCoveredOptions>,
+}
 
 pub struct HtmlGenerator;
 
+/// The report of [`HtmlGenerator::generate_to`] as a [`ReportGenerator`]
+/// named `Html`.
+#[derive(Debug, Clone, Default)]
+pub struct HtmlReport {
+    pub options: HtmlOptions,
+}
+
+impl ReportGenerator for HtmlReport {
+    fn name(&self) -> &str {
+        "Html"
+    }
+
+    fn generate(&self, coverage: &Coverage, sink: &mut dyn ReportSink) -> std::io::Result<()> {
+        HtmlGenerator::generate_to(coverage, &self.options, sink).map(|_| ())
+    }
+}
+
 impl HtmlGenerator {
-    fn create_full(path: PathBuf, data: &[u8]) -> std::io::Result<()> {
-        let mut file = std::fs::File::create(path)?;
-        file.write_all(data)
+    pub(crate) fn escape(input: &str) -> String {
+        let mut out = String::with_capacity(input.len());
+        for c in input.chars() {
+            match c {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '"' => out.push_str("&quot;"),
+                '\'' => out.push_str("&#39;"),
+                c => out.push(c),
+            }
+        }
+        out
+    }
+
+    /// Write a `<script>` defining the constant `name` as `value`.
+    fn write_json_script(
+        out: &mut impl Write,
+        name: &str,
+        value: &impl Serialize,
+    ) -> std::io::Result<()> {
+        let data = serde_json::to_string(value).map_err(std::io::Error::other)?;
+        // JSON is valid JavaScript, we only need to make sure that the data
+        // can't close the script element.
+        let data = data.replace("</", "<\\/");
+
+        write!(out, "<script>\nconst {name} = {data};\n</script>")
+    }
+
+    pub fn generate_pages(coverage: &Coverage) -> std::io::Result<FileNameIndex> {
+        Self::generate_pages_with(coverage, &HtmlOptions::default())
+    }
+
+    /// Generate the report in the `output-rs` directory.
+    pub fn generate_pages_with(
+        coverage: &Coverage,
+        options: &HtmlOptions,
+    ) -> std::io::Result<FileNameIndex> {
+        Self::generate_to(coverage, options, &mut DirectorySink::new("output-rs"))
+    }
+
+    /// Render the report into memory, without touching the file system, e.g.
+    /// to serve it from a web server or an app. Maps the paths of the pages
+    /// and assets, relative to the root of the report, to their contents.
+    pub fn generate_to_map(coverage: &Coverage) -> std::io::Result<BTreeMap<PathBuf, Vec<u8>>> {
+        Self::generate_to_map_with(coverage, &HtmlOptions::default())
+    }
+
+    /// Like [`Self::generate_to_map`], with `options`.
+    pub fn generate_to_map_with(
+        coverage: &Coverage,
+        options: &HtmlOptions,
+    ) -> std::io::Result<BTreeMap<PathBuf, Vec<u8>>> {
+        let mut sink = MemorySink::new();
+        Self::generate_to(coverage, options, &mut sink)?;
+
+        Ok(sink
+            .files
+            .into_iter()
+            .map(|(path, contents)| (PathBuf::from(path), contents))
+            .collect())
+    }
+
+    // The start of a page from `template`, in the language of the report and
+    // with the configured color scheme.
+    fn head(template: &str, options: &HtmlOptions) -> String {
+        let class = if options.high_contrast {
+            " class=\"high-contrast\""
+        } else {
+            ""
+        };
+
+        template.replacen(
+            "<html>",
+            &format!(
+                "<html lang=\"{}\"{class}>",
+                options.translation.html("lang")
+            ),
+            1,
+        )
     }
 
-    pub fn generate_pages(coverage: &Coverage) -> std::io::Result<()> {
-        let output_dir = PathBuf::from("output-rs");
+    /// Generate the report, writing all files to `sink`.
+    pub fn generate_to(
+        coverage: &Coverage,
+        options: &HtmlOptions,
+        sink: &mut dyn ReportSink,
+    ) -> std::io::Result<FileNameIndex> {
+        let mut index_html = Vec::new();
+        let assets = Assets::new(&options.assets);
+        let heads = Heads::new(options, &assets);
+
+        let mut pages = if options.single_file {
+            let script = format!(
+                "<script type=\"module\">\n{}\n{}</script>",
+                assets.index_js.contents, assets.class_js.contents
+            );
+            let style = format!("<style>\n{}</style>", assets.css.contents);
+            let head = Self::head(INDEX_HTML, options)
+                .replace(INDEX_SCRIPT, &script)
+                .replace(STYLESHEET, &style);
+            index_html.write_all(head.as_bytes())?;
+            index_html.write_all(SINGLE_FILE_STYLE.as_bytes())?;
+
+            PageWriter::single_file(sink)
+        } else {
+            let mut pages = PageWriter::files(sink, options.incremental);
+            for asset in assets.all() {
+                if !asset.inline {
+                    pages.write_file(&asset.file_name, asset.contents.as_bytes())?;
+                }
+            }
+            index_html.write_all(heads.index.as_bytes())?;
+
+            pages
+        };
+
+        let mut index_data = IndexJsonData {
+            classes: Vec::new(),
+        };
+
+        let trend: Vec<_> = options
+            .trend
+            .iter()
+            .map(|entry| entry.class_line_rates(coverage))
+            .collect();
+
+        let mut file_names = FileNameIndex::new("html");
+        // Reserved first, so that no class page can take its name.
+        let directories_file = file_names.insert("directories").to_string();
+        let mut file_pages = HashMap::new();
+
+        let projects: Vec<_> = coverage
+            .projects()
+            .into_iter()
+            .map(|project| {
+                let packages: Vec<_> = coverage
+                    .packages_in_project(project)
+                    .map(|package| Self::package_pages(package, &mut file_names, &pages))
+                    .collect();
+                (project, packages)
+            })
+            .collect();
+
+        Self::write_class_pages(&projects, &mut pages, &heads, options, coverage.min_hits)?;
+
+        Self::write_metadata(&mut index_html, &options.metadata, options)?;
+
+        let previous = options.previous.as_ref();
+        let summary = options.translation.format(
+            "class_summary",
+            &[
+                (
+                    "line",
+                    &SummaryGenerator::format_rate(
+                        coverage.line_rate,
+                        previous.map(|p| p.line_rate),
+                        &options.rounding,
+                    ),
+                ),
+                (
+                    "branch",
+                    &SummaryGenerator::format_rate(
+                        coverage.branch_rate,
+                        previous.map(|p| p.branch_rate),
+                        &options.rounding,
+                    ),
+                ),
+            ],
+        );
+        write!(
+            index_html,
+            "\n\t<p class=\"summary\">{}</p>",
+            Self::escape(&summary)
+        )?;
+
+        write!(
+            index_html,
+            "\n\t<p><a href=\"{}\">{}</a></p>",
+            pages.href(&directories_file),
+            options.translation.html("directories")
+        )?;
+
+        let grouped = projects.iter().any(|(project, _)| project.is_some());
+
+        for (project, packages) in &projects {
+            let project = *project;
+            index_html.write_all(b"\n\t<section class=\"project\">")?;
+
+            if grouped {
+                let title = match project {
+                    Some(project) => Self::escape(project),
+                    None => options.translation.html("other_project"),
+                };
+                index_html.write_all(format!("\n\t<h2>{title}</h2>").as_bytes())?;
+            }
+
+            let mut entries = Vec::new();
+            let mut package_links = Vec::new();
 
-        if !output_dir.exists() {
-            std::fs::create_dir(&output_dir)?;
+            for package_pages in packages {
+                let package = package_pages.package;
+                let mut package_classes = Vec::new();
+
+                for class_pages in &package_pages.classes {
+                    let class = class_pages.class;
+                    let href = pages.href(&class_pages.file_name);
+                    file_pages
+                        .entry(&*class.file_name)
+                        .or_insert_with(|| href.clone());
+                    package_classes.push((href.clone(), class));
+
+                    entries.push(IndexEntry {
+                        index: index_data.classes.len(),
+                        href,
+                        class,
+                        sparkline: Self::class_sparkline(class, &trend, options),
+                    });
+
+                    index_data.classes.push(IndexClass {
+                        name: &class.name,
+                        package: &package.name,
+                        project,
+                        file: class_pages.file_name.clone(),
+                        line_coverage: class.line_rate * 100.0,
+                        branch_coverage: class.branch_rate * 100.0,
+                    });
+                }
+
+                let mut package_page = Vec::new();
+                packages::write_package(
+                    &mut package_page,
+                    package,
+                    &package_classes,
+                    coverage.min_hits,
+                    &options.rounding,
+                    &options.translation,
+                )?;
+                pages.write_page(&package_pages.file_name, &heads.page, &package_page)?;
+                package_links.push((package_pages.href.clone(), package));
+            }
+
+            packages::write_package_list(
+                &mut index_html,
+                &package_links,
+                &options.rounding,
+                &options.translation,
+            )?;
+
+            match &options.namespace_separator {
+                Some(separator) => tree::write_tree(
+                    &mut index_html,
+                    &entries,
+                    separator,
+                    &options.rounding,
+                    coverage.min_hits,
+                )?,
+                None => {
+                    index_html.write_all(b"\n\t<ul class=\"classes\">")?;
+                    for entry in &entries {
+                        tree::write_entry(
+                            &mut index_html,
+                            entry,
+                            &entry.class.name,
+                            &options.rounding,
+                            "\t\t",
+                        )?;
+                    }
+                    index_html.write_all(b"\n\t</ul>")?;
+                }
+            }
+
+            index_html.write_all(b"\n\t</section>")?;
         }
 
-        Self::create_full(output_dir.join("class.js"), CLASS_JS.as_bytes())?;
+        if let Some(owners) = &options.owners {
+            owners::write_owners(
+                &mut index_html,
+                &coverage.by_owner(owners),
+                &options.rounding,
+                &options.translation,
+            )?;
+        }
+
+        if !options.components.is_empty() {
+            owners::write_components(
+                &mut index_html,
+                &options.components,
+                &options.rounding,
+                &options.translation,
+            )?;
+        }
+
+        if let Some(generated) = options
+            .generated
+            .as_ref()
+            .filter(|g| !g.packages.is_empty())
+        {
+            generated::write_generated(
+                &mut index_html,
+                generated,
+                &options.rounding,
+                &options.translation,
+            )?;
+        }
 
-        let index_html = File::create(output_dir.join("index.html"))?;
-        let mut index_html = BufWriter::new(index_html);
-        index_html.write_all(HTML_PREFIX.as_bytes())?;
+        if let Some(worst_covered) = &options.worst_covered {
+            worst::write_worst_covered(
+                &mut index_html,
+                coverage,
+                worst_covered,
+                &options.rounding,
+                &options.translation,
+            )?;
+        }
 
-        for class in coverage.packages.iter().flat_map(|c| &c.classes) {
-            index_html.write_all(
-                format!(
-                    "\n\t<p><a href=\"./{}.html\">{}</a></p>",
-                    class.name, class.name
-                )
-                .as_bytes(),
+        if options.hit_histogram {
+            histogram::write_histogram(
+                &mut index_html,
+                &coverage.hit_statistics(),
+                &options.translation,
             )?;
+        }
+
+        let mut directories_page = Vec::new();
+        directories::write_directories(
+            &mut directories_page,
+            &coverage.by_directory(),
+            &file_pages,
+            &options.rounding,
+            &options.translation,
+        )?;
+        pages.write_page(&directories_file, &heads.page, &directories_page)?;
+
+        pages.finish(&mut index_html)?;
+
+        Self::write_json_script(&mut index_html, "index_data", &index_data)?;
+        Self::write_json_script(&mut index_html, "strings", &options.translation)?;
+        index_html.write_all(HTML_POSTFIX.as_bytes())?;
+        sink.write("index.html", &index_html)?;
+
+        log::info!(
+            "Generated HTML report with {} class page(s)",
+            index_data.classes.len()
+        );
+
+        Ok(file_names)
+    }
+
+    /// Write `comparison.html` to `sink`, comparing the two reports of
+    /// `diff`.
+    pub fn generate_comparison(
+        diff: &CoverageDiff,
+        options: &HtmlOptions,
+        sink: &mut dyn ReportSink,
+    ) -> std::io::Result<()> {
+        let assets = Assets::new(&options.assets);
+        if !assets.css.inline {
+            sink.write(&assets.css.file_name, assets.css.contents.as_bytes())?;
+        }
+
+        let mut page = Vec::new();
+        page.extend_from_slice(assets.apply(&Self::head(HTML_PREFIX, options)).as_bytes());
+        comparison::write_comparison(&mut page, diff, &options.rounding, &options.translation)?;
+        page.extend_from_slice(HTML_POSTFIX.as_bytes());
+
+        sink.write("comparison.html", &page)
+    }
+
+    // The sparkline of the line coverage of `class` in the runs of `trend`
+    // (the line rates of each of `options.trend`) and this one.
+    fn class_sparkline(
+        class: &Class,
+        trend: &[HashMap<String, f64>],
+        options: &HtmlOptions,
+    ) -> String {
+        if trend.is_empty() {
+            return String::new();
+        }
 
-            // TODO: sanitze name
-            let path = output_dir.join(format!("{}.html", class.name));
-            let mut class_file = BufWriter::new(File::create(path)?);
-            class_file.write_all(CLASS_HTML.as_bytes())?;
-
-            let class_json_data = ClassJsonData {
-                methods: class
-                    .methods
-                    .iter()
-                    .map(|m| Method {
-                        name: &m.name,
-                        signature: &m.signature,
-                        line_coverage: m.line_rate * 100.0,
-                        branch_coverage: m.branch_rate * 100.0,
-                    })
-                    .collect(),
+        let key = HistoryEntry::class_key(class);
+        let rates: Vec<_> = trend
+            .iter()
+            .map(|rates| rates.get(&key).copied())
+            .chain([Some(class.line_rate)])
+            .collect();
+
+        let label = rates
+            .iter()
+            .map(|rate| match rate {
+                Some(rate) => options.rounding.format_rate(*rate),
+                None => "-".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let label = options
+            .translation
+            .format("line_coverage_trend", &[("rates", &label)]);
+
+        components::sparkline(&rates, &label)
+    }
+
+    fn write_metadata(
+        out: &mut Vec<u8>,
+        metadata: &ReportMetadata,
+        options: &HtmlOptions,
+    ) -> std::io::Result<()> {
+        if let Some(title) = &metadata.title {
+            write!(out, "\n\t<h1>{}</h1>", Self::escape(title))?;
+        }
+
+        let mut details = metadata.details().peekable();
+        if details.peek().is_none() {
+            return Ok(());
+        }
+
+        write!(out, "\n\t<dl class=\"metadata\">")?;
+        for (key, value) in details {
+            let value = Self::escape(value);
+            let value = if key == "build_url" {
+                format!("<a href=\"{value}\">{value}</a>")
+            } else {
+                format!("<code>{value}</code>")
             };
 
-            let data = serde_json::to_string(&class_json_data).unwrap();
+            write!(
+                out,
+                "\n\t\t<dt>{}</dt><dd>{value}</dd>",
+                options.translation.html(key)
+            )?;
+        }
+        write!(out, "\n\t</dl>")
+    }
+
+    fn package_pages<'a>(
+        package: &'a Package,
+        file_names: &mut FileNameIndex,
+        pages: &PageWriter,
+    ) -> PackagePages<'a> {
+        let file_name = file_names
+            .insert(&format!("{} (package)", package.name))
+            .to_string();
 
-            class_file.write_all(b"<script>\nconst class_data = JSON.parse(`")?;
-            class_file.write_all(data.as_bytes())?;
-            class_file.write_all(b"`);\n</script>")?;
+        let classes = package
+            .classes
+            .iter()
+            .map(|class| ClassPages {
+                class,
+                file_name: file_names.insert(&class.name).to_string(),
+                branches_file_name: branches::has_branches(class).then(|| {
+                    file_names
+                        .insert(&format!("{} (branches)", class.name))
+                        .to_string()
+                }),
+            })
+            .collect();
+
+        PackagePages {
+            package,
+            href: pages.href(&file_name),
+            file_name,
+            classes,
         }
+    }
 
-        index_html.write_all(HTML_POSTFIX.as_bytes())?;
+    /// Render and write the pages of all classes, in batches that are
+    /// rendered in parallel with the `parallel` feature.
+    fn write_class_pages(
+        projects: &[(Option<&str>, Vec<PackagePages>)],
+        pages: &mut PageWriter,
+        heads: &Heads,
+        options: &HtmlOptions,
+        min_hits: u64,
+    ) -> std::io::Result<()> {
+        let classes: Vec<_> = projects
+            .iter()
+            .flat_map(|(_, packages)| packages)
+            .flat_map(|package| package.classes.iter().map(move |class| (package, class)))
+            .collect();
+
+        let mut progress = ProgressUpdate {
+            total_items: Some(classes.len() as u64),
+            ..Default::default()
+        };
+        let links = pages.links();
+
+        for batch in classes.chunks(PAGE_BATCH) {
+            let rendered = map_pages(batch, |(package, class)| {
+                options.progress.check()?;
+                Self::render_class_page(class, package, links, heads, options, min_hits)
+            });
+
+            let mut batch_pages = Vec::new();
+            for class_pages in rendered {
+                batch_pages.extend(class_pages?);
+            }
+            pages.write_pages(batch_pages)?;
+
+            progress.items += batch.len() as u64;
+            options.progress.report(&progress);
+        }
 
         Ok(())
     }
+
+    /// The page of a class, and its branches page if it has one.
+    fn render_class_page(
+        class_pages: &ClassPages,
+        package_pages: &PackagePages,
+        links: PageLinks,
+        heads: &Heads,
+        options: &HtmlOptions,
+        min_hits: u64,
+    ) -> std::io::Result<Vec<Page>> {
+        let ClassPages {
+            class,
+            file_name,
+            branches_file_name,
+        } = class_pages;
+        let package = package_pages.package;
+        let package_href = &package_pages.href;
+
+        let mut rendered = Vec::new();
+        let mut class_file = Vec::new();
+
+        let summary = options.translation.format(
+            "class_summary",
+            &[
+                ("line", &options.rounding.format_rate(class.line_rate)),
+                ("branch", &options.rounding.format_rate(class.branch_rate)),
+            ],
+        );
+
+        write!(
+            class_file,
+            "\n\t<h1>{}</h1>\n\t<p>{}</p>",
+            Self::escape(&class.name),
+            Self::escape(&summary)
+        )?;
+        write!(
+            class_file,
+            "\n\t<p>{}: <a href=\"{package_href}\">{}</a></p>",
+            options.translation.html("package"),
+            packages::package_name(package, &options.translation)
+        )?;
+
+        if let Some(branches_file_name) = branches_file_name {
+            write!(
+                class_file,
+                "\n\t<p><a href=\"{}\">{}</a></p>",
+                links.href(branches_file_name),
+                options.translation.html("branches")
+            )?;
+
+            let mut branches_file = Vec::new();
+            branches::write_branch_table(&mut branches_file, class, &options.translation)?;
+            rendered.push(Page {
+                file_name: branches_file_name.clone(),
+                head: heads.page.clone(),
+                body: branches_file,
+            });
+        }
+
+        let id_prefix = links.id_prefix(file_name);
+        methods::write_method_table(
+            &mut class_file,
+            class,
+            &options.rounding,
+            &options.translation,
+            &id_prefix,
+        )?;
+        methods::write_method_sections(
+            &mut class_file,
+            class,
+            &options.translation,
+            &id_prefix,
+            options.tests.as_ref(),
+            options.provenance.as_ref(),
+            min_hits,
+        )?;
+
+        // The class data is only used by `class.js`, which is not included in
+        // single file reports (and would redefine the constant).
+        if links.is_single_file() {
+            rendered.push(Page {
+                file_name: file_name.clone(),
+                head: heads.class.clone(),
+                body: class_file,
+            });
+            return Ok(rendered);
+        }
+
+        let class_json_data = ClassJsonData {
+            methods: class
+                .methods
+                .iter()
+                .map(|m| Method {
+                    name: &m.name,
+                    signature: m.signature.as_deref(),
+                    line_coverage: m.line_rate * 100.0,
+                    branch_coverage: m.branch_rate * 100.0,
+                })
+                .collect(),
+        };
+
+        Self::write_json_script(&mut class_file, "class_data", &class_json_data)?;
+        rendered.push(Page {
+            file_name: file_name.clone(),
+            head: heads.class.clone(),
+            body: class_file,
+        });
+
+        Ok(rendered)
+    }
+}
+
+#[cfg(feature = "parallel")]
+fn map_pages<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    use rayon::prelude::*;
+
+    items.par_iter().map(f).collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn map_pages<T, R>(items: &[T], f: impl Fn(&T) -> R) -> Vec<R> {
+    items.iter().map(f).collect()
 }
 
 #[derive(Debug, Serialize)]
 pub struct Method<'a> {
     pub name: &'a str,
-    pub signature: &'a str,
+    pub signature: Option<&'a str>,
     pub line_coverage: f64,
     pub branch_coverage: f64,
 }
@@ -86,3 +785,18 @@ pub struct Method<'a> {
 pub struct ClassJsonData<'a> {
     pub methods: Vec<Method<'a>>,
 }
+
+#[derive(Debug, Serialize)]
+pub struct IndexClass<'a> {
+    pub name: &'a str,
+    pub package: &'a str,
+    pub project: Option<&'a str>,
+    pub file: String,
+    pub line_coverage: f64,
+    pub branch_coverage: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexJsonData<'a> {
+    pub classes: Vec<IndexClass<'a>>,
+}
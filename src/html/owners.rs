@@ -0,0 +1,74 @@
+use std::io::{self, Write};
+
+use crate::{ComponentSummary, ComputedRates, OwnerSummary, RoundingPolicy};
+
+use super::{components::coverage_bar, HtmlGenerator, Translation};
+
+/// Write the section of the index with the coverage of each owner.
+pub(crate) fn write_owners(
+    out: &mut impl Write,
+    summaries: &[OwnerSummary],
+    rounding: &RoundingPolicy,
+    strings: &Translation,
+) -> io::Result<()> {
+    let rows = summaries.iter().map(|summary| {
+        let owner = match &summary.owner {
+            Some(owner) => HtmlGenerator::escape(owner),
+            None => strings.html("no_owner"),
+        };
+        (owner, summary.files, &summary.rates)
+    });
+
+    write_groups(out, ["owners", "owner"], rows, rounding, strings)
+}
+
+/// Write the section of the index with the coverage of each component.
+pub(crate) fn write_components(
+    out: &mut impl Write,
+    summaries: &[ComponentSummary],
+    rounding: &RoundingPolicy,
+    strings: &Translation,
+) -> io::Result<()> {
+    let rows = summaries.iter().map(|summary| {
+        let component = match &summary.component {
+            Some(component) => HtmlGenerator::escape(component),
+            None => strings.html("no_component"),
+        };
+        (component, summary.files, &summary.rates)
+    });
+
+    write_groups(out, ["components", "component"], rows, rounding, strings)
+}
+
+// A section with a table of groups of files, titled and headed by the strings
+// `[plural, singular]`.
+fn write_groups<'a>(
+    out: &mut impl Write,
+    [plural, singular]: [&str; 2],
+    rows: impl Iterator<Item = (String, usize, &'a ComputedRates)>,
+    rounding: &RoundingPolicy,
+    strings: &Translation,
+) -> io::Result<()> {
+    write!(out, "\n\t<section class=\"{plural}\">")?;
+    write!(out, "\n\t<h2>{}</h2>", strings.html(plural))?;
+    write!(out, "\n\t<table>")?;
+    write!(
+        out,
+        "\n\t\t<tr><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{}</th></tr>",
+        strings.html(singular),
+        strings.html("files"),
+        strings.html("line_coverage"),
+        strings.html("branch_coverage"),
+    )?;
+
+    for (name, files, rates) in rows {
+        write!(
+            out,
+            "\n\t\t<tr><td>{name}</td><td>{files}</td><td>{}</td><td>{}</td></tr>",
+            coverage_bar(rates.line_rate, rounding),
+            coverage_bar(rates.branch_rate, rounding),
+        )?;
+    }
+
+    write!(out, "\n\t</table>\n\t</section>")
+}
@@ -0,0 +1,106 @@
+use std::io::{self, Write};
+
+use crate::{Class, Package, RoundingPolicy};
+
+use super::{components::coverage_bar, HtmlGenerator, Translation};
+
+/// The name of `package` for display.
+pub(crate) fn package_name(package: &Package, strings: &Translation) -> String {
+    if package.name.is_empty() {
+        strings.html("default_package")
+    } else {
+        HtmlGenerator::escape(&package.name)
+    }
+}
+
+fn write_rate(out: &mut impl Write, rate: f64, rounding: &RoundingPolicy) -> io::Result<()> {
+    write!(out, "<td>{}</td>", coverage_bar(Some(rate), rounding))
+}
+
+/// Write the list of packages of a project in the index, linking to their
+/// pages.
+pub(crate) fn write_package_list(
+    out: &mut impl Write,
+    packages: &[(String, &Package)],
+    rounding: &RoundingPolicy,
+    strings: &Translation,
+) -> io::Result<()> {
+    write!(
+        out,
+        "\n\t<ul class=\"packages\" aria-label=\"{}\">",
+        strings.html("packages")
+    )?;
+    for (href, package) in packages {
+        write!(
+            out,
+            "\n\t\t<li><a href=\"{href}\">{}</a> {}</li>",
+            package_name(package, strings),
+            coverage_bar(Some(package.line_rate), rounding)
+        )?;
+    }
+    write!(out, "\n\t</ul>")
+}
+
+/// Write the body of the page of `package`: its totals, and a table of its
+/// classes, which link to their pages.
+pub(crate) fn write_package(
+    out: &mut impl Write,
+    package: &Package,
+    classes: &[(String, &Class)],
+    min_hits: u64,
+    rounding: &RoundingPolicy,
+    strings: &Translation,
+) -> io::Result<()> {
+    let rates = package.computed_rates(min_hits);
+    let summary = strings.format(
+        "class_summary",
+        &[
+            ("line", &rounding.format_rate(package.line_rate)),
+            ("branch", &rounding.format_rate(package.branch_rate)),
+        ],
+    );
+    let lines = strings.format(
+        "lines_covered",
+        &[
+            ("covered", &rates.lines_covered.to_string()),
+            ("total", &rates.lines_valid.to_string()),
+        ],
+    );
+
+    write!(
+        out,
+        "\n\t<h1>{}</h1>\n\t<p class=\"summary\">{}</p>\n\t<p>{}</p>",
+        package_name(package, strings),
+        HtmlGenerator::escape(&summary),
+        HtmlGenerator::escape(&lines)
+    )?;
+
+    write!(out, "\n\t<table>")?;
+    write!(
+        out,
+        "\n\t\t<tr><th scope=\"col\">{}</th><th scope=\"col\">{}</th><th scope=\"col\">{}</th></tr>",
+        strings.html("class"),
+        strings.html("line_coverage"),
+        strings.html("branch_coverage"),
+    )?;
+
+    for (href, class) in classes {
+        write!(
+            out,
+            "\n\t\t<tr><th scope=\"row\"><a href=\"{href}\">{}</a></th>",
+            HtmlGenerator::escape(&class.name)
+        )?;
+        write_rate(out, class.line_rate, rounding)?;
+        write_rate(out, class.branch_rate, rounding)?;
+        write!(out, "</tr>")?;
+    }
+
+    write!(
+        out,
+        "\n\t\t<tr><th scope=\"row\">{}</th>",
+        strings.html("total")
+    )?;
+    write_rate(out, package.line_rate, rounding)?;
+    write_rate(out, package.branch_rate, rounding)?;
+    write!(out, "</tr>\n\t</table>")
+}
@@ -0,0 +1,170 @@
+use std::io::{self, Write};
+
+use crate::ReportSink;
+
+use super::{manifest::Manifest, HTML_POSTFIX};
+
+/// A page that is rendered, but not written yet.
+pub(crate) struct Page {
+    pub(crate) file_name: String,
+    pub(crate) head: String,
+    pub(crate) body: Vec<u8>,
+}
+
+/// How pages link to each other, which only depends on whether they end up
+/// in a single file. Unlike the [`PageWriter`], it can be shared between
+/// threads.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PageLinks {
+    single_file: bool,
+}
+
+impl PageLinks {
+    pub(crate) fn is_single_file(&self) -> bool {
+        self.single_file
+    }
+
+    /// The link to the page `file_name`.
+    pub(crate) fn href(&self, file_name: &str) -> String {
+        if self.single_file {
+            format!("#{file_name}")
+        } else {
+            format!("./{file_name}")
+        }
+    }
+
+    /// A prefix for element ids within the page `file_name`, so that they
+    /// stay unique when all pages end up in one document.
+    pub(crate) fn id_prefix(&self, file_name: &str) -> String {
+        if self.single_file {
+            format!("{file_name}-")
+        } else {
+            String::new()
+        }
+    }
+}
+
+/// Where the pages of a report end up: either as separate files in the sink,
+/// or as `<article>`s that are appended to a single `index.html`.
+pub(crate) struct PageWriter<'a> {
+    sink: &'a mut dyn ReportSink,
+    previous: Manifest,
+    manifest: Manifest,
+    articles: Option<Vec<u8>>,
+}
+
+impl<'a> PageWriter<'a> {
+    pub(crate) fn files(sink: &'a mut dyn ReportSink, incremental: bool) -> Self {
+        let previous = if incremental {
+            Manifest::load(sink)
+        } else {
+            Manifest::default()
+        };
+
+        Self {
+            sink,
+            previous,
+            manifest: Manifest::default(),
+            articles: None,
+        }
+    }
+
+    pub(crate) fn single_file(sink: &'a mut dyn ReportSink) -> Self {
+        Self {
+            sink,
+            previous: Manifest::default(),
+            manifest: Manifest::default(),
+            articles: Some(Vec::new()),
+        }
+    }
+
+    pub(crate) fn links(&self) -> PageLinks {
+        PageLinks {
+            single_file: self.articles.is_some(),
+        }
+    }
+
+    /// The link to the page `file_name`.
+    pub(crate) fn href(&self, file_name: &str) -> String {
+        self.links().href(file_name)
+    }
+
+    /// Write the page `file_name`, consisting of `head` followed by `body`.
+    /// `head` is left out in single file mode.
+    pub(crate) fn write_page(
+        &mut self,
+        file_name: &str,
+        head: &str,
+        body: &[u8],
+    ) -> io::Result<()> {
+        if let Some(articles) = &mut self.articles {
+            write!(articles, "\n<article id=\"{file_name}\">")?;
+            articles.write_all(body)?;
+            return articles.write_all(b"\n</article>");
+        }
+
+        let contents = Self::contents(head, body);
+        if self
+            .manifest
+            .record_page(&self.previous, self.sink, file_name, &contents)
+        {
+            self.sink.write(file_name, &contents)?;
+        }
+        Ok(())
+    }
+
+    /// Write a file other than a page, e.g. an asset. Like pages, files that
+    /// are not written anymore are removed from incremental reports.
+    pub(crate) fn write_file(&mut self, file_name: &str, contents: &[u8]) -> io::Result<()> {
+        if self
+            .manifest
+            .record_page(&self.previous, self.sink, file_name, contents)
+        {
+            self.sink.write(file_name, contents)?;
+        }
+        Ok(())
+    }
+
+    /// Write all `pages`, letting the sink write them concurrently if it
+    /// can.
+    pub(crate) fn write_pages(&mut self, pages: Vec<Page>) -> io::Result<()> {
+        if self.articles.is_some() {
+            for page in pages {
+                self.write_page(&page.file_name, &page.head, &page.body)?;
+            }
+            return Ok(());
+        }
+
+        let mut changed = Vec::new();
+        for page in pages {
+            let contents = Self::contents(&page.head, &page.body);
+            if self
+                .manifest
+                .record_page(&self.previous, self.sink, &page.file_name, &contents)
+            {
+                changed.push((page.file_name, contents));
+            }
+        }
+
+        self.sink.write_batch(&changed)
+    }
+
+    fn contents(head: &str, body: &[u8]) -> Vec<u8> {
+        let mut contents = Vec::with_capacity(head.len() + body.len() + HTML_POSTFIX.len());
+        contents.extend_from_slice(head.as_bytes());
+        contents.extend_from_slice(body);
+        contents.extend_from_slice(HTML_POSTFIX.as_bytes());
+        contents
+    }
+
+    /// Finish writing: append all articles to `index` in single file mode, or
+    /// update the manifest and remove stale pages otherwise.
+    pub(crate) fn finish(self, index: &mut impl Write) -> io::Result<()> {
+        if let Some(articles) = &self.articles {
+            return index.write_all(articles);
+        }
+
+        self.manifest.remove_stale(&self.previous, self.sink)?;
+        self.manifest.save(self.sink)
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use crate::{unique::unique_rates, Class, RoundingPolicy};
+
+use super::{components::coverage_bar, HtmlGenerator};
+
+/// A class in the index, with its position in the index data and the link to
+/// its page.
+pub(crate) struct IndexEntry<'a> {
+    pub index: usize,
+    pub href: String,
+    pub class: &'a Class,
+    /// The sparkline of the line coverage of the class in previous runs, if
+    /// any.
+    pub sparkline: String,
+}
+
+/// Write the list item linking to the page of `entry`, labelled `label`.
+pub(crate) fn write_entry(
+    out: &mut impl Write,
+    entry: &IndexEntry,
+    label: &str,
+    rounding: &RoundingPolicy,
+    indent: &str,
+) -> io::Result<()> {
+    write!(
+        out,
+        "\n{indent}<li data-index=\"{}\"><a href=\"{}\">{}</a> {}{}</li>",
+        entry.index,
+        entry.href,
+        HtmlGenerator::escape(label),
+        coverage_bar(Some(entry.class.line_rate), rounding),
+        entry.sparkline
+    )
+}
+
+#[derive(Default)]
+struct Node<'a, 'b> {
+    children: BTreeMap<&'b str, Node<'a, 'b>>,
+    // The classes directly in this namespace, with their unqualified names.
+    classes: Vec<(&'b str, &'b IndexEntry<'a>)>,
+}
+
+impl<'a, 'b> Node<'a, 'b> {
+    fn insert(&mut self, segments: &[&'b str], entry: &'b IndexEntry<'a>) {
+        match segments {
+            [] => {}
+            [name] => self.classes.push((name, entry)),
+            [namespace, rest @ ..] => self
+                .children
+                .entry(namespace)
+                .or_default()
+                .insert(rest, entry),
+        }
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = &'b IndexEntry<'a>> + '_> {
+        Box::new(
+            self.classes
+                .iter()
+                .map(|(_, e)| *e)
+                .chain(self.children.values().flat_map(|c| c.entries())),
+        )
+    }
+
+    fn write(
+        &self,
+        out: &mut impl Write,
+        separator: &str,
+        rounding: &RoundingPolicy,
+        min_hits: u64,
+        depth: usize,
+    ) -> io::Result<()> {
+        let indent = "\t".repeat(depth + 1);
+
+        for (name, child) in &self.children {
+            // Show namespaces that only contain a single namespace as one node,
+            // e.g. `com.example` instead of `com` containing `example`.
+            let mut label = name.to_string();
+            let mut child = child;
+            while child.classes.is_empty() && child.children.len() == 1 {
+                let (name, grandchild) = child.children.iter().next().unwrap();
+                label = format!("{label}{separator}{name}");
+                child = grandchild;
+            }
+
+            let rates = unique_rates(child.entries().map(|e| e.class), min_hits);
+            let rate = coverage_bar(rates.line_rate, rounding);
+
+            write!(
+                out,
+                "\n{indent}<details class=\"namespace\">\n{indent}\t<summary>{} {rate}</summary>",
+                HtmlGenerator::escape(&label),
+            )?;
+            child.write(out, separator, rounding, min_hits, depth + 1)?;
+            write!(out, "\n{indent}</details>")?;
+        }
+
+        if !self.classes.is_empty() {
+            write!(out, "\n{indent}<ul class=\"classes\">")?;
+            for (name, entry) in &self.classes {
+                write_entry(out, entry, name, rounding, &format!("{indent}\t"))?;
+            }
+            write!(out, "\n{indent}</ul>")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Write `entries` as a tree of collapsible namespaces, splitting class names
+/// on `separator`. Each namespace shows the line coverage of all classes in
+/// it, counting lines hit fewer than `min_hits` times as uncovered.
+pub(crate) fn write_tree(
+    out: &mut impl Write,
+    entries: &[IndexEntry],
+    separator: &str,
+    rounding: &RoundingPolicy,
+    min_hits: u64,
+) -> io::Result<()> {
+    let mut root = Node::default();
+
+    for entry in entries {
+        let segments: Vec<_> = if separator.is_empty() {
+            vec![&*entry.class.name]
+        } else {
+            entry.class.name.split(separator).collect()
+        };
+
+        root.insert(&segments, entry);
+    }
+
+    root.write(out, separator, rounding, min_hits, 0)
+}
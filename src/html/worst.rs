@@ -0,0 +1,61 @@
+use std::io::{self, Write};
+
+use crate::{Coverage, RoundingPolicy, WorstCoveredOptions};
+
+use super::{components::coverage_bar, HtmlGenerator, Translation};
+
+/// Write the section of the index with the worst covered files or classes.
+pub(crate) fn write_worst_covered(
+    out: &mut impl Write,
+    coverage: &Coverage,
+    options: &WorstCoveredOptions,
+    rounding: &RoundingPolicy,
+    strings: &Translation,
+) -> io::Result<()> {
+    let (title, name) = if options.classes {
+        ("least_covered_classes", "class")
+    } else {
+        ("least_covered_files", "file")
+    };
+
+    write!(out, "\n\t<section class=\"worst-covered\">")?;
+    write!(out, "\n\t<h2>{}</h2>", strings.html(title))?;
+    write!(out, "\n\t<table>")?;
+    write!(
+        out,
+        "\n\t\t<tr><th scope=\"col\">{}</th>",
+        strings.html(name)
+    )?;
+    if options.classes {
+        write!(out, "<th scope=\"col\">{}</th>", strings.html("file"))?;
+    }
+    write!(
+        out,
+        "<th scope=\"col\">{}</th><th scope=\"col\">{}</th></tr>",
+        strings.html("missed"),
+        strings.html("line_coverage"),
+    )?;
+
+    for worst in coverage.worst_covered(options) {
+        write!(
+            out,
+            "\n\t\t<tr><td><code>{}</code></td>",
+            HtmlGenerator::escape(&worst.name)
+        )?;
+        if let Some(file) = &worst.file {
+            write!(
+                out,
+                "<td><code>{}</code></td>",
+                HtmlGenerator::escape(&file.to_string_lossy())
+            )?;
+        }
+        write!(
+            out,
+            "<td>{}</td><td>{}</td></tr>",
+            worst.uncovered(),
+            coverage_bar(worst.rates.line_rate, rounding),
+        )?;
+    }
+
+    write!(out, "\n\t</table>\n\t</section>")
+}
@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use crate::Class;
+
+/// The lowest [`class_similarity`] at which two classes are taken to be the
+/// same class.
+pub const MIN_CLASS_SIMILARITY: f64 = 0.6;
+
+// What identifies a class, apart from its name and file name.
+struct Fingerprint<'a> {
+    methods: HashSet<(&'a str, Option<&'a str>)>,
+    lines: HashSet<usize>,
+}
+
+impl<'a> Fingerprint<'a> {
+    fn new(class: &'a Class) -> Self {
+        Self {
+            methods: class
+                .methods
+                .iter()
+                .map(|m| (&*m.name, m.signature.as_deref()))
+                .collect(),
+            lines: class.lines.iter().map(|l| l.number).collect(),
+        }
+    }
+}
+
+// The share of the elements of `a` and `b` that are in both, 0 if both are
+// empty.
+fn jaccard<T: Eq + std::hash::Hash>(a: &HashSet<T>, b: &HashSet<T>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+fn similarity(a: (&Class, &Fingerprint), b: (&Class, &Fingerprint)) -> f64 {
+    let ((a, a_print), (b, b_print)) = (a, b);
+
+    // Classes without methods (e.g. from coverage.py) can only be told apart
+    // by their lines, which stay the same when a file is only moved.
+    let contents = if a.methods.is_empty() && b.methods.is_empty() {
+        jaccard(&a_print.lines, &b_print.lines)
+    } else {
+        jaccard(&a_print.methods, &b_print.methods)
+    };
+
+    let path = if a.file_name == b.file_name {
+        1.0
+    } else if a.name == b.name || a.file_name.file_name() == b.file_name.file_name() {
+        0.5
+    } else {
+        0.0
+    };
+
+    0.75 * contents + 0.25 * path
+}
+
+/// How likely it is that `a` and `b` are the same class in two runs, from 0
+/// to 1, e.g. after it was renamed or moved to another file. This is mostly
+/// the overlap of their methods (or lines, if neither has methods), and
+/// partly how close their names and file names are.
+pub fn class_similarity(a: &Class, b: &Class) -> f64 {
+    similarity((a, &Fingerprint::new(a)), (b, &Fingerprint::new(b)))
+}
+
+/// Match the classes that were `removed` from a report to the ones that were
+/// `added`, pairing the most similar classes first. Returns the indices of
+/// the matched classes, as `(removed, added)`. Each class is matched at most
+/// once, and only if their similarity is at least [`MIN_CLASS_SIMILARITY`].
+pub fn match_moved_classes(removed: &[&Class], added: &[&Class]) -> Vec<(usize, usize)> {
+    let removed: Vec<_> = removed.iter().map(|c| (*c, Fingerprint::new(c))).collect();
+    let added: Vec<_> = added.iter().map(|c| (*c, Fingerprint::new(c))).collect();
+
+    let mut candidates = Vec::new();
+    for (r, (removed, removed_print)) in removed.iter().enumerate() {
+        for (a, (added, added_print)) in added.iter().enumerate() {
+            let similarity = similarity((removed, removed_print), (added, added_print));
+            if similarity >= MIN_CLASS_SIMILARITY {
+                candidates.push((similarity, r, a));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut matched_removed = vec![false; removed.len()];
+    let mut matched_added = vec![false; added.len()];
+    let mut pairs = Vec::new();
+
+    for (_, r, a) in candidates {
+        if !matched_removed[r] && !matched_added[a] {
+            matched_removed[r] = true;
+            matched_added[a] = true;
+            pairs.push((r, a));
+        }
+    }
+
+    pairs
+}
@@ -0,0 +1,75 @@
+use std::io::{self, BufRead};
+
+#[cfg(feature = "json")]
+use crate::{Class, Coverage, Interner, Package};
+
+/// The first bytes of a binary snapshot, see `Coverage::save_snapshot`.
+pub(crate) const SNAPSHOT_MAGIC: &[u8] = b"CRSNAP";
+
+/// The supported input formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    Cobertura,
+    Istanbul,
+    LlvmCov,
+    Snapshot,
+}
+
+impl InputFormat {
+    /// Guess the format of the input from its first bytes, without consuming
+    /// them.
+    pub fn detect(reader: &mut impl BufRead) -> io::Result<Option<Self>> {
+        let head = reader.fill_buf()?;
+        if head.starts_with(SNAPSHOT_MAGIC) {
+            return Ok(Some(Self::Snapshot));
+        }
+
+        let trimmed = head.trim_ascii_start();
+
+        let format = match trimmed.first() {
+            Some(b'<') => Some(Self::Cobertura),
+            Some(b'{') => {
+                // llvm-cov writes its keys sorted, so the `type` marker is
+                // usually at the very end, but `data` always comes first.
+                let marker = b"llvm.coverage.json.export";
+                let first_key = trimmed[1..].trim_ascii_start();
+                if first_key.starts_with(b"\"data\"")
+                    || head.windows(marker.len()).any(|w| w == marker)
+                {
+                    Some(Self::LlvmCov)
+                } else {
+                    Some(Self::Istanbul)
+                }
+            }
+            _ => None,
+        };
+
+        Ok(format)
+    }
+}
+
+#[cfg(feature = "json")]
+/// Add `class` to the package named after the directory of its file, creating
+/// the package if needed. Used by input formats that have no notion of
+/// packages.
+pub(crate) fn push_class_by_directory(
+    coverage: &mut Coverage,
+    interner: &mut Interner,
+    class: Class,
+) {
+    let directory = class
+        .file_name
+        .parent()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = interner.intern(&directory);
+
+    match coverage.packages.iter_mut().find(|p| p.name == name) {
+        Some(package) => package.classes.push(class),
+        None => coverage.packages.push(Package {
+            name,
+            classes: vec![class],
+            ..Default::default()
+        }),
+    }
+}
@@ -0,0 +1,49 @@
+use std::{collections::HashSet, path::Path, sync::Arc};
+
+/// A string interner, so that repeated names (e.g. method names and
+/// signatures) and file names share a single allocation.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+    paths: HashSet<Arc<Path>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&mut self, value: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(value) {
+            return interned.clone();
+        }
+
+        let interned: Arc<str> = Arc::from(value);
+        self.strings.insert(interned.clone());
+        interned
+    }
+
+    pub fn intern_path(&mut self, value: &Path) -> Arc<Path> {
+        if let Some(interned) = self.paths.get(value) {
+            return interned.clone();
+        }
+
+        let interned: Arc<Path> = Arc::from(value);
+        self.paths.insert(interned.clone());
+        interned
+    }
+
+    /// The number of distinct strings and paths interned.
+    pub fn len(&self) -> usize {
+        self.strings.len() + self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty() && self.paths.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.strings.clear();
+        self.paths.clear();
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{Class, Condition, ConditionType, Coverage, Interner, Line, Method};
+
+#[derive(Debug, Deserialize)]
+struct Position {
+    line: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct Location {
+    start: Position,
+    end: Position,
+}
+
+#[derive(Debug, Deserialize)]
+struct Function {
+    name: String,
+    loc: Location,
+}
+
+#[derive(Debug, Deserialize)]
+struct Branch {
+    loc: Location,
+    #[serde(rename = "type")]
+    ty: String,
+    line: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileCoverage {
+    path: Option<PathBuf>,
+    statement_map: HashMap<String, Location>,
+    #[serde(default)]
+    fn_map: HashMap<String, Function>,
+    #[serde(default)]
+    branch_map: HashMap<String, Branch>,
+    s: HashMap<String, u64>,
+    #[serde(default)]
+    b: HashMap<String, Vec<usize>>,
+}
+
+// Istanbul uses stringified integers as ids. Sort numerically where possible,
+// so that the output order is stable.
+fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&str, &V)> {
+    let mut entries: Vec<_> = map.iter().map(|(k, v)| (k.as_str(), v)).collect();
+    entries.sort_by(
+        |(a, _), (b, _)| match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            _ => a.cmp(b),
+        },
+    );
+    entries
+}
+
+fn percentage(covered: usize, total: usize) -> usize {
+    (covered * 100).checked_div(total).unwrap_or(100)
+}
+
+/// A parser for Istanbul/nyc `coverage-final.json` files.
+///
+/// Each file becomes a class (named after its path) in a package named after
+/// its directory. Statements are mapped to lines by their start line, taking
+/// the highest hit count of all statements starting on a line.
+pub struct IstanbulParser;
+
+impl IstanbulParser {
+    pub fn parse(reader: impl Read) -> serde_json::Result<Coverage> {
+        let files: BTreeMap<String, FileCoverage> = serde_json::from_reader(reader)?;
+        Ok(Self::convert(files))
+    }
+
+    pub fn parse_str(input: &str) -> serde_json::Result<Coverage> {
+        let files: BTreeMap<String, FileCoverage> = serde_json::from_str(input)?;
+        Ok(Self::convert(files))
+    }
+
+    fn convert(files: BTreeMap<String, FileCoverage>) -> Coverage {
+        let mut interner = Interner::new();
+        let mut coverage = Coverage {
+            version: "istanbul".to_string(),
+            ..Default::default()
+        };
+
+        for (key, file) in files {
+            let path = file.path.clone().unwrap_or_else(|| PathBuf::from(&key));
+            let class = Self::convert_file(&mut interner, &path, &file);
+
+            crate::input::push_class_by_directory(&mut coverage, &mut interner, class);
+        }
+
+        coverage.recompute_rates();
+        coverage
+    }
+
+    fn convert_file(interner: &mut Interner, path: &Path, file: &FileCoverage) -> Class {
+        let mut lines: BTreeMap<usize, Line> = BTreeMap::new();
+
+        for (id, location) in sorted(&file.statement_map) {
+            let hits = file.s.get(id).copied().unwrap_or(0);
+            let number = location.start.line;
+
+            let line = lines.entry(number).or_insert_with(|| Line {
+                number,
+                ..Default::default()
+            });
+            line.hits = line.hits.max(hits);
+        }
+
+        for (id, branch) in sorted(&file.branch_map) {
+            let counts = file.b.get(id).map(Vec::as_slice).unwrap_or_default();
+            let number = branch.line.unwrap_or(branch.loc.start.line);
+
+            let line = lines.entry(number).or_insert_with(|| Line {
+                number,
+                ..Default::default()
+            });
+
+            let (mut covered, mut total) = line.branch_counts().unwrap_or((0, 0));
+            let branch_covered = counts.iter().filter(|c| **c > 0).count();
+            covered += branch_covered;
+            total += counts.len();
+
+            line.set_branch_counts(covered, total);
+            line.conditions.push(Condition {
+                number: id.parse().unwrap_or(line.conditions.len()),
+                r#type: match branch.ty.as_str() {
+                    "switch" => ConditionType::Switch {
+                        cases: counts.len() as u32,
+                    },
+                    other => ConditionType::parse(other),
+                },
+                coverage: format!("{}%", percentage(branch_covered, counts.len())),
+                ..Default::default()
+            });
+        }
+
+        let methods = sorted(&file.fn_map)
+            .into_iter()
+            .map(|(_, function)| {
+                let range = function.loc.start.line..=function.loc.end.line;
+                Method {
+                    name: interner.intern(&function.name),
+                    signature: None,
+                    lines: lines.range(range).map(|(_, l)| l.clone()).collect(),
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        Class {
+            name: interner.intern(&path.to_string_lossy()),
+            file_name: path.into(),
+            lines: lines.into_values().collect(),
+            methods,
+            ..Default::default()
+        }
+    }
+}
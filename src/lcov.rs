@@ -0,0 +1,104 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{Class, Coverage};
+
+/// Writes LCOV tracefiles (`.info`), e.g. for `genhtml` or Coveralls.
+pub struct LcovGenerator;
+
+impl LcovGenerator {
+    /// Write one record per file, with its functions (`FN`/`FNDA`), branches
+    /// (`BRDA`) and lines (`DA`).
+    ///
+    /// Cobertura only has the number of covered branches of a line, not
+    /// which ones were taken, so the first branches of a line are written
+    /// as taken. Functions are named by their name and signature, and their
+    /// hits are those of their first line. Lines hit fewer than
+    /// [`Coverage::min_hits`] times are written as not hit.
+    pub fn write(coverage: &Coverage, mut out: impl Write) -> io::Result<()> {
+        let mut classes: BTreeMap<&Path, Vec<&Class>> = BTreeMap::new();
+        for class in coverage.classes() {
+            classes.entry(&class.file_name).or_default().push(class);
+        }
+
+        for file in coverage.file_coverage() {
+            writeln!(out, "TN:")?;
+            writeln!(out, "SF:{}", file.path.display())?;
+
+            let methods = classes
+                .get(file.path)
+                .into_iter()
+                .flatten()
+                .flat_map(|c| &c.methods);
+            let mut functions = Vec::new();
+            for method in methods {
+                let Some(first) = method.lines.iter().min_by_key(|l| l.number) else {
+                    continue;
+                };
+                let name = format!(
+                    "{}{}",
+                    method.name,
+                    method.signature.as_deref().unwrap_or_default()
+                );
+                writeln!(out, "FN:{},{name}", first.number)?;
+                functions.push((name, first.covered_hits(coverage.min_hits)));
+            }
+            for (name, hits) in &functions {
+                writeln!(out, "FNDA:{hits},{name}")?;
+            }
+            writeln!(out, "FNF:{}", functions.len())?;
+            writeln!(
+                out,
+                "FNH:{}",
+                functions.iter().filter(|(_, hits)| *hits > 0).count()
+            )?;
+
+            let (mut branches, mut branches_hit) = (0, 0);
+            for line in &file.lines {
+                let Some((covered, total)) = line.branch_counts() else {
+                    continue;
+                };
+
+                for branch in 0..total {
+                    let taken = match (line.covered_hits(coverage.min_hits), branch < covered) {
+                        (0, _) => "-",
+                        (_, true) => "1",
+                        (_, false) => "0",
+                    };
+                    writeln!(out, "BRDA:{},0,{branch},{taken}", line.number)?;
+                }
+                branches += total;
+                if line.is_covered(coverage.min_hits) {
+                    branches_hit += covered.min(total);
+                }
+            }
+            writeln!(out, "BRF:{branches}")?;
+            writeln!(out, "BRH:{branches_hit}")?;
+
+            for line in &file.lines {
+                writeln!(
+                    out,
+                    "DA:{},{}",
+                    line.number,
+                    line.covered_hits(coverage.min_hits)
+                )?;
+            }
+            writeln!(out, "LF:{}", file.lines.len())?;
+            writeln!(
+                out,
+                "LH:{}",
+                file.lines
+                    .iter()
+                    .filter(|l| l.is_covered(coverage.min_hits))
+                    .count()
+            )?;
+
+            writeln!(out, "end_of_record")?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,14 +1,184 @@
+mod annotations;
+mod attribution;
+mod badge;
+mod canonical;
+#[cfg(feature = "checksums")]
+mod checksums;
+mod cobertura;
+mod compare;
+mod complexity;
+mod components;
+mod csv;
+mod diff;
+mod directories;
+#[cfg(feature = "parser")]
 mod error;
+mod exclusions;
+mod filter;
+mod generated;
+mod generator;
+#[cfg(any(feature = "policy", feature = "upload"))]
+mod git;
+mod glob;
+mod histogram;
+mod history;
+#[cfg(feature = "html")]
 mod html;
+mod identity;
+mod input;
+mod intern;
+#[cfg(feature = "json")]
+mod istanbul;
+mod lcov;
+#[cfg(feature = "json")]
+mod llvm_cov;
+mod merge;
+mod metadata;
+mod mutate;
+#[cfg(feature = "json")]
+mod notify;
+mod owners;
+#[cfg(feature = "parser")]
 mod parser;
+#[cfg(feature = "policy")]
+mod policy;
+mod profile;
+mod progress;
+mod provenance;
+mod query;
+mod ranking;
+mod rates;
+mod reconcile;
+mod rounding;
+mod signature;
+#[cfg(feature = "signing")]
+mod signing;
+mod sink;
+#[cfg(feature = "snapshot")]
+mod snapshot;
+mod sort;
+mod sources;
+mod split;
+mod stats;
+mod summary;
+mod teamcity;
+mod timestamp;
+mod trivial;
+mod uncovered;
+mod unique;
+#[cfg(feature = "upload")]
+mod upload;
+#[cfg(feature = "parser")]
+mod validate;
+mod verify;
+#[cfg(feature = "parser")]
+mod visitor;
+#[cfg(feature = "parser")]
+mod warning;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod worst;
 
+pub use annotations::CoverageStatus;
+pub use attribution::TestAttribution;
+pub use badge::{Badge, BadgeGenerator, BadgeScope, BadgesReport};
+#[cfg(feature = "checksums")]
+pub use checksums::{ChecksumMismatch, SourceChecksums};
+pub use cobertura::{CoberturaGenerator, RoundTripMismatch};
+pub use complexity::{estimate_complexity, ComplexityOutcome};
+#[cfg(feature = "components")]
+pub use components::ComponentMap;
+pub use components::{ComponentGenerator, ComponentSummary};
+pub use csv::{CsvGenerator, CsvReport};
+pub use diff::{ClassDiff, CoverageDiff, MovedClass, PackageDiff, RateChange};
+pub use directories::DirSummary;
+#[cfg(feature = "parser")]
 pub use error::ParserError;
-pub use html::HtmlGenerator;
-pub use parser::{FilteredEvent, Parser};
+pub use exclusions::{ExclusionOutcome, Exclusions};
+pub use filter::CoverageFilter;
+pub use generated::{GeneratedCode, GeneratedOutcome};
+pub use generator::{GeneratorRegistry, ReportGenerator};
+#[cfg(feature = "policy")]
+pub use git::added_files;
+#[cfg(any(feature = "policy", feature = "upload"))]
+pub use git::GitInfo;
+pub use histogram::HitHistogram;
+#[cfg(feature = "json")]
+pub use history::History;
+pub use history::{HistoryEntry, RetentionInterval, RetentionPolicy};
+#[cfg(feature = "html")]
+pub use html::{
+    AssetOptions, FileNameEntry, FileNameIndex, HtmlGenerator, HtmlOptions, HtmlReport, Language,
+    Translation,
+};
+pub use identity::{class_similarity, match_moved_classes, MIN_CLASS_SIMILARITY};
+pub use input::InputFormat;
+pub use intern::Interner;
+#[cfg(feature = "json")]
+pub use istanbul::IstanbulParser;
+pub use lcov::LcovGenerator;
+#[cfg(feature = "json")]
+pub use llvm_cov::LlvmCovParser;
+pub use merge::{BranchStrategy, HitsStrategy, MergeOptions};
+pub use metadata::ReportMetadata;
+#[cfg(feature = "json")]
+pub use notify::{NotificationOptions, SlackGenerator, TeamsGenerator};
+pub use owners::{CodeOwners, OwnerSummary, OwnershipGenerator};
+#[cfg(feature = "parser")]
+pub use parser::{CoverageSummary, FilteredEvent, PackageSummary, Parser};
+#[cfg(feature = "policy")]
+pub use policy::{NewFilesRule, Policy, PolicyChecker, PolicyRule, PolicyViolation};
+pub use profile::Profile;
+pub use progress::{CancellationToken, Cancelled, Progress, ProgressUpdate};
+pub use provenance::LineProvenance;
+pub use query::{CoverageIndex, FileSummary};
+pub use ranking::RankingFilter;
+pub use rates::ComputedRates;
+pub use reconcile::{LineConflict, LinePrecedence, ReconciliationReport};
+pub use rounding::{RoundingMode, RoundingPolicy};
+pub use signature::prettify_signature;
+#[cfg(feature = "signing")]
+pub use signing::{ManifestMismatch, ManifestSink, ReportManifest};
+pub use sink::{
+    content_type, DirectorySink, MemorySink, ObjectStore, ObjectStoreSink, ReportSink, ZipSink,
+};
+pub use sources::SourceResolver;
+pub use stats::Statistics;
+pub use summary::{MarkdownSummaryReport, SummaryGenerator};
+pub use teamcity::TeamCityGenerator;
+pub use trivial::{EffectiveCoverage, TrivialLines};
+pub use uncovered::{UncoveredFile, UncoveredLinesGenerator};
+pub use unique::FileCoverage;
+#[cfg(feature = "upload")]
+pub use upload::{CodecovGenerator, CoverallsGenerator, CoverallsOptions};
+#[cfg(feature = "parser")]
+pub use validate::{DtdValidator, DtdViolation, DtdViolationKind};
+pub use verify::{Discrepancy, Rate, Scope, VerificationReport};
+#[cfg(feature = "parser")]
+pub use visitor::CoverageVisitor;
+#[cfg(feature = "parser")]
+pub use warning::{ParseOutcome, ParseReport, Warning};
+#[cfg(feature = "wasm")]
+pub use wasm::{render_html, summarize};
+pub use worst::{
+    WorstCovered, WorstCoveredGenerator, WorstCoveredOptions, WorstCoveredReport, WorstOrder,
+};
 
-use std::path::PathBuf;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
-#[derive(Debug, Clone, Default)]
+/// A Cobertura report.
+///
+/// This and the other types of the model are `#[non_exhaustive]`, so that
+/// fields can be added without breaking other crates. Outside of this crate,
+/// start from `Default::default()` and set the fields instead.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct Coverage {
     pub sources: Vec<Source>,
     pub packages: Vec<Package>,
@@ -22,6 +192,13 @@ pub struct Coverage {
     pub complexity: f64,
     pub version: String,
     pub timestamp: u64,
+    /// Attributes this crate doesn't know, by name, kept so that reports
+    /// can be written back without losing them.
+    pub extra_attributes: BTreeMap<String, String>,
+    /// Lines hit fewer times than this count as uncovered in all rates,
+    /// although their hits are kept as they are. 0 and 1 both count every
+    /// hit line as covered. See [`Coverage::apply_min_hits`].
+    pub min_hits: u64,
 }
 
 impl Coverage {
@@ -31,57 +208,212 @@ impl Coverage {
             .flat_map(|p| &p.classes)
             .flat_map(|c| c.lines.iter())
     }
+
+    pub fn classes(&self) -> impl Iterator<Item = &Class> {
+        self.packages.iter().flat_map(|p| &p.classes)
+    }
+
+    pub fn methods(&self) -> impl Iterator<Item = &Method> {
+        self.classes().flat_map(|c| &c.methods)
+    }
 }
 
-#[derive(Debug, Clone, Default)]
+/// A `<source>` root, relative to which class file names are resolved.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct Source {
-    // Until we find a difference.
-    _data: String,
+    pub path: PathBuf,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct Package {
     pub classes: Vec<Class>,
-    pub name: String,
+    pub name: Arc<str>,
     pub line_rate: f64,
     pub branch_rate: f64,
     pub complexity: f64,
+    // The project (e.g. crate or assembly) this package was merged from, if any.
+    pub project: Option<String>,
+    pub extra_attributes: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct Class {
     pub methods: Vec<Method>,
     pub lines: Vec<Line>,
-    pub name: String,
-    pub file_name: PathBuf,
+    pub name: Arc<str>,
+    pub file_name: Arc<Path>,
     pub line_rate: f64,
     pub branch_rate: f64,
     pub complexity: f64,
+    pub extra_attributes: BTreeMap<String, String>,
+}
+
+impl Default for Class {
+    fn default() -> Self {
+        Self {
+            methods: Vec::new(),
+            lines: Vec::new(),
+            name: Arc::default(),
+            file_name: Arc::from(Path::new("")),
+            line_rate: 0.0,
+            branch_rate: 0.0,
+            complexity: 0.0,
+            extra_attributes: BTreeMap::new(),
+        }
+    }
+}
+
+impl Class {
+    /// The lines hit fewer than `min_hits` times, see [`Line::is_covered`].
+    pub fn uncovered_lines(&self, min_hits: u64) -> impl Iterator<Item = &Line> {
+        self.lines.iter().filter(move |l| !l.is_covered(min_hits))
+    }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct Method {
     pub lines: Vec<Line>,
-    pub name: String,
-    pub signature: String,
+    pub name: Arc<str>,
+    /// `None` if the report leaves it out, as some exporters do.
+    pub signature: Option<Arc<str>>,
     pub line_rate: f64,
     pub branch_rate: f64,
+    pub complexity: f64,
+    pub extra_attributes: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct Line {
     pub conditions: Vec<Condition>,
     pub number: usize,
-    pub hits: usize,
+    pub hits: u64,
     pub branch: bool,
     // Almost always in the following form `X% (Y/Z)`
     pub condition_coverage: Option<String>,
+    pub extra_attributes: BTreeMap<String, String>,
+}
+
+impl Line {
+    /// A line that is not a branch.
+    pub fn new(number: usize, hits: u64) -> Self {
+        Self {
+            number,
+            hits,
+            ..Default::default()
+        }
+    }
+
+    /// Whether the line was hit at least `min_hits` times, and at least once.
+    pub fn is_covered(&self, min_hits: u64) -> bool {
+        self.hits >= min_hits.max(1)
+    }
+
+    /// The hits of the line if it is [covered](Self::is_covered), and 0
+    /// otherwise, for formats that take every line that was hit as covered.
+    pub fn covered_hits(&self, min_hits: u64) -> u64 {
+        if self.is_covered(min_hits) {
+            self.hits
+        } else {
+            0
+        }
+    }
+
+    /// The `(covered, total)` branch counts of this line, parsed from
+    /// `condition_coverage`.
+    pub fn branch_counts(&self) -> Option<(usize, usize)> {
+        let coverage = self.condition_coverage.as_deref()?;
+        let (_, counts) = coverage.split_once('(')?;
+        let (covered, total) = counts.trim_end().strip_suffix(')')?.split_once('/')?;
+
+        Some((covered.trim().parse().ok()?, total.trim().parse().ok()?))
+    }
+
+    /// The lines that missed branches of this line jump to, as listed by
+    /// coverage.py in its `missing-branches` attribute (instead of writing
+    /// `<conditions>`). Negative numbers are exits from the function.
+    pub fn missing_branches(&self) -> Option<&str> {
+        self.extra_attributes
+            .get("missing-branches")
+            .map(String::as_str)
+    }
+
+    /// Mark this line as a branch line, and set `condition_coverage` to
+    /// `covered` out of `total` branches.
+    pub fn set_branch_counts(&mut self, covered: usize, total: usize) {
+        let percentage = (covered * 100).checked_div(total).unwrap_or(100);
+        self.branch = true;
+        self.condition_coverage = Some(format!("{percentage}% ({covered}/{total})"));
+    }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub struct Condition {
     pub number: usize,
-    pub r#type: String,
+    pub r#type: ConditionType,
     // Always like `X%`?
     pub coverage: String,
+    pub extra_attributes: BTreeMap<String, String>,
+}
+
+/// The `type` of a [`Condition`]. More types may be added, which are
+/// [`ConditionType::Other`] until then.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ConditionType {
+    /// A conditional jump, `jump`.
+    Jump,
+    /// A switch, `switch`.
+    ///
+    /// Cobertura reports only give the branches of a whole line, not of each
+    /// of its conditions. `cases` is therefore only filled in when the line
+    /// has exactly one condition, whose cases are all the branches of the
+    /// line, or when the input format gives them per condition (as Istanbul
+    /// does). Otherwise it is 0, i.e. unknown.
+    Switch { cases: u32 },
+    /// Any other type, as written in the report.
+    Other(String),
+}
+
+impl ConditionType {
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "jump" => Self::Jump,
+            "switch" => Self::Switch { cases: 0 },
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    /// The value of the `type` attribute.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Jump => "jump",
+            Self::Switch { .. } => "switch",
+            Self::Other(other) => other,
+        }
+    }
+}
+
+impl Default for ConditionType {
+    fn default() -> Self {
+        Self::Other(String::new())
+    }
+}
+
+impl fmt::Display for ConditionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
@@ -0,0 +1,265 @@
+use std::{collections::BTreeMap, io::Read, path::PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{input::push_class_by_directory, Class, Coverage, Interner, Line, Method};
+
+#[derive(Debug, Deserialize)]
+struct Export {
+    #[serde(rename = "type")]
+    ty: String,
+    version: String,
+    data: Vec<Data>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Data {
+    files: Vec<File>,
+    #[serde(default)]
+    functions: Vec<Function>,
+}
+
+#[derive(Debug, Deserialize)]
+struct File {
+    filename: PathBuf,
+    #[serde(default)]
+    segments: Vec<Vec<Value>>,
+    #[serde(default)]
+    branches: Vec<Vec<Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Function {
+    name: String,
+    #[serde(default)]
+    regions: Vec<Vec<Value>>,
+    #[serde(default)]
+    filenames: Vec<PathBuf>,
+}
+
+// `[line, col, count, has_count, is_region_entry, is_gap_region]`. Older
+// exports omit `is_gap_region`.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    line: usize,
+    count: u64,
+    has_count: bool,
+    is_region_entry: bool,
+    is_gap_region: bool,
+}
+
+impl Segment {
+    fn from_value(value: &[Value]) -> Option<Self> {
+        let int = |idx: usize| value.get(idx).and_then(Value::as_u64);
+        let flag = |idx: usize| {
+            value
+                .get(idx)
+                .and_then(|v| v.as_bool().or_else(|| v.as_u64().map(|v| v != 0)))
+        };
+
+        Some(Self {
+            line: int(0)? as usize,
+            count: int(2)?,
+            has_count: flag(3)?,
+            is_region_entry: flag(4)?,
+            is_gap_region: flag(5).unwrap_or(false),
+        })
+    }
+
+    fn is_start_of_region(&self) -> bool {
+        !self.is_gap_region && self.has_count && self.is_region_entry
+    }
+}
+
+/// A parser for the JSON output of `llvm-cov export` (and `cargo llvm-cov
+/// --json`).
+///
+/// Each file becomes a class (named after its path) in a package named after
+/// its directory. Line hits are derived from the region segments the same way
+/// `llvm-cov report` does, and branch regions are attributed to the line they
+/// start on.
+pub struct LlvmCovParser;
+
+impl LlvmCovParser {
+    pub fn parse(reader: impl Read) -> serde_json::Result<Coverage> {
+        let export: Export = serde_json::from_reader(reader)?;
+        Self::convert(export)
+    }
+
+    pub fn parse_str(input: &str) -> serde_json::Result<Coverage> {
+        let export: Export = serde_json::from_str(input)?;
+        Self::convert(export)
+    }
+
+    fn convert(export: Export) -> serde_json::Result<Coverage> {
+        if export.ty != "llvm.coverage.json.export" {
+            return Err(serde::de::Error::custom(format!(
+                "unexpected export type {:?}",
+                export.ty
+            )));
+        }
+
+        let mut interner = Interner::new();
+        let mut coverage = Coverage {
+            version: format!("llvm-cov {}", export.version),
+            ..Default::default()
+        };
+
+        let mut files: BTreeMap<PathBuf, Class> = BTreeMap::new();
+
+        for data in &export.data {
+            for file in &data.files {
+                let class = Self::convert_file(&mut interner, file);
+                files.insert(file.filename.clone(), class);
+            }
+
+            for function in &data.functions {
+                Self::add_function(&mut interner, &mut files, function);
+            }
+        }
+
+        for (_, class) in files {
+            push_class_by_directory(&mut coverage, &mut interner, class);
+        }
+
+        coverage.recompute_rates();
+        Ok(coverage)
+    }
+
+    fn convert_file(interner: &mut Interner, file: &File) -> Class {
+        let segments: Vec<_> = file
+            .segments
+            .iter()
+            .filter_map(|s| Segment::from_value(s))
+            .collect();
+
+        let mut lines: BTreeMap<usize, Line> = BTreeMap::new();
+
+        // Mirrors `LineCoverageStats` in LLVM: a line is covered by the
+        // regions that start on it, and by the region that wraps into it
+        // from a previous line.
+        let mut wrapped: Option<Segment> = None;
+        let mut idx = 0;
+        let last_line = segments.last().map(|s| s.line).unwrap_or(0);
+        let mut number = segments.first().map(|s| s.line).unwrap_or(1);
+
+        while number <= last_line {
+            let start = idx;
+            while idx < segments.len() && segments[idx].line == number {
+                idx += 1;
+            }
+            let on_line = &segments[start..idx];
+
+            let region_starts = on_line.iter().filter(|s| s.is_start_of_region()).count();
+            let skipped = on_line
+                .first()
+                .map(|s| !s.has_count && s.is_region_entry)
+                .unwrap_or(false);
+            let mapped =
+                !skipped && (wrapped.map(|w| w.has_count).unwrap_or(false) || region_starts > 0);
+
+            if mapped {
+                let mut hits = wrapped.map(|w| w.count).unwrap_or(0);
+                if region_starts > 0 {
+                    hits = on_line
+                        .iter()
+                        .filter(|s| s.is_start_of_region())
+                        .map(|s| s.count)
+                        .fold(hits, u64::max);
+                }
+
+                lines.insert(
+                    number,
+                    Line {
+                        number,
+                        hits,
+                        ..Default::default()
+                    },
+                );
+            }
+
+            if let Some(last) = on_line.last() {
+                wrapped = Some(*last);
+            }
+            number += 1;
+        }
+
+        // `[line_start, col_start, line_end, col_end, true_count, false_count,
+        // file_id, expanded_file_id, kind]`
+        for branch in &file.branches {
+            let int = |idx: usize| branch.get(idx).and_then(Value::as_u64);
+            let (Some(number), Some(taken), Some(not_taken)) = (int(0), int(4), int(5)) else {
+                continue;
+            };
+            let number = number as usize;
+
+            let line = lines.entry(number).or_insert_with(|| Line {
+                number,
+                ..Default::default()
+            });
+
+            let (covered, total) = line.branch_counts().unwrap_or((0, 0));
+            let covered = covered + (taken > 0) as usize + (not_taken > 0) as usize;
+            line.set_branch_counts(covered, total + 2);
+        }
+
+        Class {
+            name: interner.intern(&file.filename.to_string_lossy()),
+            file_name: file.filename.as_path().into(),
+            lines: lines.into_values().collect(),
+            ..Default::default()
+        }
+    }
+
+    // `[line_start, col_start, line_end, col_end, count, file_id,
+    // expanded_file_id, kind]`. Only regions in the function's own file
+    // (`file_id` 0) determine which lines belong to it.
+    fn add_function(
+        interner: &mut Interner,
+        files: &mut BTreeMap<PathBuf, Class>,
+        function: &Function,
+    ) {
+        let Some(class) = function
+            .filenames
+            .first()
+            .and_then(|file| files.get_mut(file))
+        else {
+            return;
+        };
+
+        let mut range: Option<(usize, usize)> = None;
+        for region in &function.regions {
+            let int = |idx: usize| region.get(idx).and_then(Value::as_u64);
+            let (Some(start), Some(end), Some(0)) = (int(0), int(2), int(5)) else {
+                continue;
+            };
+            let (start, end) = (start as usize, end as usize);
+            range = Some(match range {
+                Some((s, e)) => (s.min(start), e.max(end)),
+                None => (start, end),
+            });
+        }
+
+        let Some((start, end)) = range else {
+            return;
+        };
+
+        let name = interner.intern(&function.name);
+        if class.methods.iter().any(|m| m.name == name) {
+            return;
+        }
+
+        class.methods.push(Method {
+            name,
+            signature: None,
+            lines: class
+                .lines
+                .iter()
+                .filter(|l| (start..=end).contains(&l.number))
+                .cloned()
+                .collect(),
+            ..Default::default()
+        });
+    }
+}
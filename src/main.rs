@@ -1,63 +1,2579 @@
-use std::collections::HashMap;
+use std::{
+    fmt,
+    fs::File,
+    hash::{BuildHasher, RandomState},
+    io::{BufRead, BufReader, BufWriter, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process::{ExitCode, Stdio},
+    sync::{mpsc, Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
 
+use clap::{ArgAction, Args, Parser as _, Subcommand, ValueEnum};
 use cobertura_rs::*;
+use log::LevelFilter;
+use notify::{RecursiveMode, Watcher};
 use quick_xml::Reader;
 
-fn main() -> std::io::Result<()> {
-    let file = std::env::args()
-        .nth(1)
-        .expect("First argument should be the path to the cobertura coverage file.");
+const STATS_TOP_FILES: usize = 10;
 
-    let mut reader = Reader::from_file(file).expect("Failed to open file.");
-    let mut state = Parser::new();
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(250);
 
-    let coverage = state
-        .parse(&mut reader)
-        .expect("Failed to parse coverage file.");
+// How long `serve` waits for a client to send its request.
+const SERVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// The number of connections `serve` answers at the same time.
+const SERVE_THREADS: usize = 4;
+
+// How many names `create_private_file` tries before giving up.
+const PRIVATE_FILE_ATTEMPTS: usize = 16;
+
+const COVERALLS_URL: &str = "https://coveralls.io/api/v1/jobs";
+const CODECOV_URL: &str = "https://codecov.io";
+
+/// The exit codes of the CLI. Scripts may rely on these, so they must not
+/// change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exit {
+    Ok = 0,
+    /// Any other error, e.g. a file that could not be read.
+    Error = 1,
+    /// The coverage is below a threshold, or its rates are inconsistent.
+    Threshold = 2,
+    /// An input file could not be parsed.
+    Parse = 3,
+    /// The command line arguments are invalid.
+    Usage = 4,
+}
+
+/// An error with its own exit code, wrapped in an [`std::io::Error`].
+#[derive(Debug)]
+struct Failure {
+    exit: Exit,
+    message: String,
+    /// The error of the parser, if an input could not be parsed.
+    parser_error: Option<ParserError>,
+}
 
-    let mut classes_by_file = HashMap::new();
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
 
-    for class in coverage.packages.iter().flat_map(|v| &v.classes) {
-        let entry = classes_by_file
-            .entry(class.file_name.as_os_str())
-            .or_insert(Vec::new());
-        entry.push(class);
+impl std::error::Error for Failure {}
+
+impl Failure {
+    fn parse(message: String) -> std::io::Error {
+        std::io::Error::other(Self {
+            exit: Exit::Parse,
+            message,
+            parser_error: None,
+        })
     }
 
-    let mut total_source_lines = 0;
-    for (_, classes) in classes_by_file {
-        total_source_lines += classes
-            .iter()
-            .flat_map(|c| c.lines.iter())
-            .map(|l| l.number + 1)
-            .max_by(usize::cmp)
-            .unwrap_or(0);
+    /// The failure to parse the coverage file `file`.
+    fn parser(file: &Path, error: ParserError) -> std::io::Error {
+        std::io::Error::other(Self {
+            exit: Exit::Parse,
+            message: format!("Failed to parse coverage file {}: {error}", file.display()),
+            parser_error: Some(error),
+        })
     }
 
-    let all_lines = coverage.lines();
+    fn threshold(message: String) -> std::io::Error {
+        std::io::Error::other(Self {
+            exit: Exit::Threshold,
+            message,
+            parser_error: None,
+        })
+    }
 
-    let (tracked_lines, hit_lines) = all_lines
-        .fold((0usize, 0usize), |(total_lines, hit_lines), line| {
-            (total_lines + 1, hit_lines + (line.hits > 0) as usize)
-        });
+    /// The exit code for `error`.
+    fn exit(error: &std::io::Error) -> Exit {
+        error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<Self>())
+            .map_or(Exit::Error, |failure| failure.exit)
+    }
+}
+
+#[derive(clap::Parser)]
+#[command(
+    version,
+    about = "Read, merge, check and report on code coverage files.",
+    after_help = "Exit codes: 0 on success, 1 on errors, 2 if coverage is below a threshold, 3 if an input could not be parsed, and 4 for invalid arguments."
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Log more details. Can be repeated.
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+    /// Log less. Once only logs errors, twice disables logging.
+    #[arg(short, long, action = ArgAction::Count, global = true, conflicts_with = "verbose")]
+    quiet: u8,
+    #[arg(long, value_enum, default_value_t = LogFormat::Text, global = true)]
+    log_format: LogFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum LogFormat {
+    Text,
+    /// One JSON object per line, for CI logs.
+    Json,
+}
+
+impl Cli {
+    fn log_level(&self) -> LevelFilter {
+        match (self.quiet, self.verbose) {
+            (0, 0) => LevelFilter::Warn,
+            (0, 1) => LevelFilter::Info,
+            (0, 2) => LevelFilter::Debug,
+            (0, _) => LevelFilter::Trace,
+            (1, _) => LevelFilter::Error,
+            _ => LevelFilter::Off,
+        }
+    }
+
+    fn init_logger(&self) {
+        let mut builder = env_logger::Builder::new();
+        builder.filter_level(self.log_level());
+
+        if self.log_format == LogFormat::Json {
+            builder.format(|buf, record| {
+                let entry = serde_json::json!({
+                    "level": record.level().as_str(),
+                    "target": record.target(),
+                    "message": record.args().to_string(),
+                });
+                writeln!(buf, "{entry}")
+            });
+        } else {
+            builder.format(|buf, record| {
+                writeln!(
+                    buf,
+                    "{}: {}",
+                    record.level().as_str().to_lowercase(),
+                    record.args()
+                )
+            });
+        }
+
+        builder.init();
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate an HTML report.
+    Report(Box<ReportArgs>),
+    /// Merge coverage files into a single file.
+    Merge(MergeArgs),
+    /// Split coverage files into several smaller Cobertura files.
+    Split(SplitArgs),
+    /// Compare two coverage files.
+    Diff(DiffArgs),
+    /// Convert a coverage file to another format.
+    Convert(ConvertArgs),
+    /// Check that the declared rates match the line data, and that a
+    /// coverage policy is met.
+    Check(CheckArgs),
+    /// Print size statistics.
+    Stats(StatsArgs),
+    /// Print the worst covered files or classes.
+    WorstCovered(WorstCoveredArgs),
+    /// Print the coverage of each owner in a `CODEOWNERS` file.
+    Owners(OwnersArgs),
+    /// Quickly print the declared totals of Cobertura files, without reading
+    /// their contents.
+    Summary(SummaryArgs),
+    /// Serve the HTML report and a JSON API on a local port, regenerating
+    /// them whenever an input changes.
+    Serve(ServeArgs),
+    /// Upload the coverage to Coveralls or Codecov. This runs `curl`, which
+    /// must be on the `PATH`.
+    Upload(UploadArgs),
+    /// Post a summary of the coverage to a Slack or Microsoft Teams webhook.
+    /// This runs `curl`, which must be on the `PATH`.
+    Notify(NotifyArgs),
+    /// Check the files of an HTML report against its manifest, written with
+    /// `report --report-manifest`.
+    VerifyReport(VerifyReportArgs),
+    /// Manage the history recorded with `report --history`.
+    History(HistoryArgs),
+}
+
+/// A coverage file, optionally grouped under a project.
+#[derive(Debug, Clone)]
+struct Input {
+    project: Option<String>,
+    path: PathBuf,
+}
+
+fn parse_input(arg: &str) -> Result<Input, String> {
+    Ok(match arg.split_once('=') {
+        Some((project, path)) => Input {
+            project: Some(project.to_string()),
+            path: PathBuf::from(path),
+        },
+        None => Input {
+            project: None,
+            path: PathBuf::from(arg),
+        },
+    })
+}
+
+fn parse_profile(name: &str) -> Result<Profile, String> {
+    Profile::from_name(name).ok_or_else(|| format!("unknown profile `{name}`"))
+}
+
+fn parse_badge_scope(scope: &str) -> Result<BadgeScope, String> {
+    match scope {
+        "total" => Ok(BadgeScope::Total),
+        "packages" => Ok(BadgeScope::Packages),
+        other => match other.strip_prefix("prefixes:") {
+            Some(prefixes) => Ok(BadgeScope::PathPrefixes(
+                prefixes.split(',').map(String::from).collect(),
+            )),
+            None => Err(format!("unknown badge scope `{other}`")),
+        },
+    }
+}
+
+fn parse_worst_order(order: &str) -> Result<WorstOrder, String> {
+    match order {
+        "uncovered" => Ok(WorstOrder::Uncovered),
+        "rate" => Ok(WorstOrder::Rate),
+        other => Err(format!("unknown order `{other}`")),
+    }
+}
+
+fn parse_retention_interval(interval: &str) -> Result<RetentionInterval, String> {
+    match interval {
+        "day" => Ok(RetentionInterval::Day),
+        "week" => Ok(RetentionInterval::Week),
+        other => Err(format!("unknown interval `{other}`")),
+    }
+}
+
+// A period such as `30d`, in seconds (`s`), minutes (`m`), hours (`h`), days
+// (`d`) or weeks (`w`).
+fn parse_period(period: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid period `{period}`, expected e.g. `30d` or `12w`");
+
+    let split = period.len() - period.chars().last().map_or(0, char::len_utf8);
+    let (count, unit) = period.split_at(split);
+    let count: u64 = count.parse().map_err(|_| invalid())?;
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+
+    count
+        .checked_mul(unit)
+        .map(Duration::from_secs)
+        .ok_or_else(invalid)
+}
+
+fn parse_hits_strategy(strategy: &str) -> Result<HitsStrategy, String> {
+    match strategy {
+        "sum" => Ok(HitsStrategy::Sum),
+        "max" => Ok(HitsStrategy::Max),
+        "or" => Ok(HitsStrategy::Or),
+        other => Err(format!("unknown hits strategy `{other}`")),
+    }
+}
+
+fn parse_line_precedence(precedence: &str) -> Result<LinePrecedence, String> {
+    match precedence {
+        "class" => Ok(LinePrecedence::Class),
+        "method" => Ok(LinePrecedence::Method),
+        "max" => Ok(LinePrecedence::Max),
+        other => Err(format!("unknown line precedence `{other}`")),
+    }
+}
+
+fn parse_branch_strategy(strategy: &str) -> Result<BranchStrategy, String> {
+    match strategy {
+        "best" => Ok(BranchStrategy::Best),
+        "sum" => Ok(BranchStrategy::Sum),
+        other => Err(format!("unknown branch strategy `{other}`")),
+    }
+}
+
+/// How to read a single coverage file.
+#[derive(Args)]
+struct LoadArgs {
+    /// The tool that produced the Cobertura files, to work around its quirks.
+    #[arg(long, value_parser = parse_profile, default_value = "generic")]
+    profile: Profile,
+    /// Skip unknown elements in Cobertura files instead of failing.
+    #[arg(long)]
+    skip_unknown: bool,
+    /// Validate Cobertura files against the DTD first.
+    #[arg(long)]
+    validate: bool,
+    /// Leave out the files listed in this file, with the syntax of
+    /// `.gitignore`, matched against the file names in the reports. Defaults
+    /// to `.coverageignore` in the working directory, if it exists.
+    #[arg(long, value_name = "FILE")]
+    ignore_file: Option<PathBuf>,
+    /// Don't read `.coverageignore` from the working directory.
+    #[arg(long, conflicts_with = "ignore_file")]
+    no_ignore_file: bool,
+}
+
+impl LoadArgs {
+    /// The ignore file to apply to every coverage file, if any.
+    fn filter(&self) -> std::io::Result<Option<CoverageFilter>> {
+        if self.no_ignore_file {
+            return Ok(None);
+        }
+
+        match &self.ignore_file {
+            Some(path) => CoverageFilter::from_ignore_file(path).map(Some),
+            None => match CoverageFilter::from_ignore_file(CoverageFilter::IGNORE_FILE) {
+                Ok(filter) => Ok(Some(filter)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e),
+            },
+        }
+    }
+}
+
+/// How to read and combine the input files.
+#[derive(Args)]
+struct InputArgs {
+    /// The coverage files, as `path` or `project=path`. The latter groups all
+    /// packages of that file under `project`.
+    #[arg(required = true, value_parser = parse_input)]
+    inputs: Vec<Input>,
+    #[command(flatten)]
+    load: LoadArgs,
+    /// Merge classes that are split over several entries.
+    #[arg(long)]
+    consolidate: bool,
+    /// Exclude lines marked with exclusion comments in the sources.
+    #[arg(long)]
+    exclusions: bool,
+    /// More markers that exclude the line they are on, besides
+    /// `LCOV_EXCL_LINE`.
+    #[arg(
+        long = "exclusion-marker",
+        value_name = "MARKER",
+        requires = "exclusions"
+    )]
+    exclusion_markers: Vec<String>,
+    /// More markers that start a block of excluded lines, besides
+    /// `LCOV_EXCL_START` and `coverage: off`.
+    #[arg(
+        long = "exclusion-start",
+        value_name = "MARKER",
+        requires = "exclusions"
+    )]
+    exclusion_starts: Vec<String>,
+    /// More markers that end a block of excluded lines, besides
+    /// `LCOV_EXCL_STOP` and `coverage: on`.
+    #[arg(
+        long = "exclusion-stop",
+        value_name = "MARKER",
+        requires = "exclusions"
+    )]
+    exclusion_stops: Vec<String>,
+    /// Estimate missing complexities from the sources.
+    #[arg(long)]
+    complexity: bool,
+    /// Only count lines hit at least this many times as covered.
+    #[arg(long, default_value_t = 1)]
+    min_hits: u64,
+    /// Limit the hits of every line to this count.
+    #[arg(long)]
+    max_hits: Option<u64>,
+    /// Warn about source files that changed since their checksums were
+    /// saved to this file with `report --save-checksums`.
+    #[arg(long)]
+    verify_checksums: Option<PathBuf>,
+    /// How to combine the hits of the same line in several inputs: `sum`,
+    /// `max` or `or`.
+    #[arg(long, default_value = "sum", value_parser = parse_hits_strategy)]
+    merge_hits: HitsStrategy,
+    /// How to combine the branch coverage of the same line in several inputs:
+    /// `best` or `sum`.
+    #[arg(long, default_value = "best", value_parser = parse_branch_strategy)]
+    merge_branches: BranchStrategy,
+    /// Make method lines agree with class lines, preferring the `class` or
+    /// `method` line, or the one with the `max` hits, when they differ.
+    #[arg(long, value_parser = parse_line_precedence)]
+    reconcile: Option<LinePrecedence>,
+    /// Glob patterns of more files with generated code, besides the usual
+    /// ones such as `**/*.pb.go` and `**/generated/**`.
+    #[arg(long = "generated", value_name = "PATTERN")]
+    generated_patterns: Vec<String>,
+    /// Also take files that have a marker such as `@generated` or
+    /// `DO NOT EDIT` near the top of their sources to be generated.
+    #[arg(long)]
+    generated_markers: bool,
+    /// Count generated code towards the coverage of summaries, checks and
+    /// reports, instead of only listing it separately. Files written by
+    /// `merge`, `split` and `upload` always keep generated code.
+    #[arg(long)]
+    include_generated: bool,
+}
+
+impl InputArgs {
+    fn merge_options(&self) -> MergeOptions {
+        MergeOptions {
+            hits: self.merge_hits,
+            branches: self.merge_branches,
+        }
+    }
+
+    fn generated_code(&self) -> GeneratedCode {
+        let mut classifier = GeneratedCode {
+            detect_markers: self.generated_markers,
+            ..Default::default()
+        };
+        classifier
+            .patterns
+            .extend(self.generated_patterns.iter().cloned());
+        classifier
+    }
 
-    let calculated_line_rate = hit_lines as f64 / tracked_lines as f64;
+    fn exclusions(&self) -> Exclusions {
+        let mut exclusions = Exclusions::default();
+        exclusions
+            .line
+            .extend(self.exclusion_markers.iter().cloned());
+        exclusions
+            .start
+            .extend(self.exclusion_starts.iter().cloned());
+        exclusions.stop.extend(self.exclusion_stops.iter().cloned());
+        exclusions
+    }
+}
+
+#[derive(Args)]
+struct RankingArgs {
+    /// Leave files with fewer tracked lines than this out of rankings, such
+    /// as the least covered files.
+    #[arg(long, default_value_t = 0)]
+    min_file_lines: usize,
+}
 
-    println!(
-        "{}, {}, {}, {}, {}",
-        tracked_lines, hit_lines, calculated_line_rate, coverage.line_rate, total_source_lines
+impl RankingArgs {
+    fn filter(&self) -> RankingFilter {
+        RankingFilter::new(self.min_file_lines)
+    }
+}
+
+#[derive(Args)]
+struct RoundingArgs {
+    /// The number of decimals of percentages.
+    #[arg(long, default_value_t = 1)]
+    decimals: u8,
+    #[arg(long, default_value_t = '.')]
+    decimal_separator: char,
+    /// Round percentages down instead of to the nearest value.
+    #[arg(long)]
+    round_down: bool,
+}
+
+impl RoundingArgs {
+    fn policy(&self) -> RoundingPolicy {
+        RoundingPolicy {
+            decimals: self.decimals,
+            mode: if self.round_down {
+                RoundingMode::Floor
+            } else {
+                RoundingMode::Nearest
+            },
+            decimal_separator: self.decimal_separator,
+        }
+    }
+}
+
+/// The language of the HTML report: `en`, `de`, `fr` or a translation JSON
+/// file.
+#[derive(Args)]
+struct LanguageArgs {
+    #[arg(long, default_value = "en")]
+    language: String,
+}
+
+impl LanguageArgs {
+    fn translation(&self) -> std::io::Result<Translation> {
+        if let Some(language) = Language::from_code(&self.language) {
+            return Ok(Translation::builtin(language));
+        }
+
+        let json = std::fs::read_to_string(&self.language)?;
+        Translation::from_json(&json).map_err(|e| {
+            std::io::Error::other(format!("Invalid translation {}: {e}", self.language))
+        })
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PrintFormat {
+    Text,
+    Json,
+}
+
+#[derive(Args)]
+struct ReportArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[command(flatten)]
+    rounding: RoundingArgs,
+    #[command(flatten)]
+    language: LanguageArgs,
+    #[arg(long, default_value = "output-rs")]
+    output_dir: PathBuf,
+    /// Write the report to this zip file instead of the output directory.
+    #[arg(long)]
+    zip: Option<PathBuf>,
+    /// The reports to write, out of `Html`, `MarkdownSummary`, `Csv` and
+    /// `Badges`.
+    #[arg(
+        long = "reporttypes",
+        value_name = "TYPES",
+        value_delimiter = ',',
+        default_value = "Html"
+    )]
+    report_types: Vec<String>,
+    /// Write a single self-contained `index.html`.
+    #[arg(long)]
+    single_file: bool,
+    /// Only rewrite pages that changed since the previous run.
+    #[arg(long)]
+    incremental: bool,
+    /// Show classes in the index as a tree of namespaces, split on this
+    /// separator (e.g. `.`, `::` or `/`).
+    #[arg(long)]
+    namespace_separator: Option<String>,
+    /// A manifest of per-test coverage files, with `path = test name` lines.
+    #[arg(long)]
+    tests: Option<PathBuf>,
+    /// Show which inputs contributed the hits of each line in the HTML
+    /// report.
+    #[arg(long)]
+    provenance: bool,
+    /// A previous coverage file to compare against in `comparison.html`.
+    #[arg(long)]
+    compare: Option<PathBuf>,
+    /// Write SVG badges for `total`, `packages` or `prefixes:a,b,...`, as
+    /// the `Badges` report (by default for `total`).
+    #[arg(long, value_parser = parse_badge_scope)]
+    badges: Option<BadgeScope>,
+    /// Print the uncovered lines of each file.
+    #[arg(long)]
+    uncovered: Option<PrintFormat>,
+    /// Print TeamCity service messages.
+    #[arg(long)]
+    teamcity: bool,
+    /// Save the processed report as a binary snapshot.
+    #[arg(long)]
+    save_snapshot: Option<PathBuf>,
+    /// Save the checksums of all source files, to detect changed sources
+    /// with `--verify-checksums` later.
+    #[arg(long)]
+    save_checksums: Option<PathBuf>,
+    /// Regenerate the report whenever an input changes.
+    #[arg(long)]
+    watch: bool,
+    /// Add the coverage of each owner in this `CODEOWNERS` file to the index.
+    #[arg(long)]
+    codeowners: Option<PathBuf>,
+    /// Add the coverage of each component to the summaries and the index,
+    /// with the components of files given by `REGEX -> COMPONENT` lines in
+    /// this file.
+    #[arg(long)]
+    components: Option<PathBuf>,
+    /// Show the change since the previous run recorded in this directory,
+    /// and record this run.
+    #[arg(long)]
+    history: Option<PathBuf>,
+    /// Prune the history after recording this run.
+    #[command(flatten)]
+    retention: RetentionArgs,
+    /// Show a sparkline of the line coverage of each class over this many
+    /// previous runs of the history in the index, or none if 0.
+    #[arg(long, value_name = "RUNS", default_value_t = 10)]
+    trend_runs: usize,
+    /// Write a Markdown summary to this file, e.g. `$GITHUB_STEP_SUMMARY`.
+    #[arg(long)]
+    markdown_summary: Option<PathBuf>,
+    /// Write a JSON summary to this file.
+    #[arg(long)]
+    json_summary: Option<PathBuf>,
+    /// Write a compact HTML digest to this file, with the overall coverage
+    /// and the least covered files, e.g. to send by e-mail.
+    #[arg(long)]
+    digest: Option<PathBuf>,
+    #[command(flatten)]
+    ranking: RankingArgs,
+    /// Add a section with the worst covered files or classes to the index of
+    /// the HTML report.
+    #[arg(long)]
+    worst_covered: bool,
+    #[command(flatten)]
+    worst: WorstArgs,
+    /// Always use the high contrast color scheme in the HTML report.
+    #[arg(long)]
+    high_contrast: bool,
+    /// Add a chart of the lines by their hits to the index of the HTML
+    /// report.
+    #[arg(long)]
+    hit_histogram: bool,
+    /// Minify the stylesheet and scripts of the HTML report.
+    #[arg(long)]
+    minify: bool,
+    /// Add a content hash to the file names of the stylesheet and scripts.
+    #[arg(long)]
+    hash_assets: bool,
+    /// Inline stylesheets and scripts of at most this many bytes into the
+    /// pages.
+    #[arg(long, value_name = "BYTES")]
+    inline_assets_below: Option<usize>,
+    /// Write `report-manifest.json` with the SHA-256 hashes of all files of
+    /// the HTML report.
+    #[arg(long)]
+    report_manifest: bool,
+    /// Sign the report manifest with the secret key in this file. Implies
+    /// `--report-manifest`.
+    #[arg(long)]
+    signing_key: Option<PathBuf>,
+    #[command(flatten)]
+    metadata: MetadataArgs,
+    /// Also print the coverage without trivial lines (e.g. closing braces),
+    /// read from the sources.
+    #[arg(long)]
+    effective_coverage: bool,
+    /// A line to consider trivial for `--effective-coverage`, in addition to
+    /// the defaults. Can be repeated.
+    #[arg(long, value_name = "LINE")]
+    trivial_line: Vec<String>,
+}
+
+/// Information about the build, shown in the reports and the history.
+#[derive(Args)]
+struct MetadataArgs {
+    /// The title of the report.
+    #[arg(long)]
+    title: Option<String>,
+    /// A label for the report, e.g. a version or a test suite.
+    #[arg(long)]
+    tag: Option<String>,
+    /// The commit the report was generated for.
+    #[arg(long)]
+    commit: Option<String>,
+    /// The branch the report was generated for.
+    #[arg(long)]
+    branch: Option<String>,
+    /// A link to the build that generated the report.
+    #[arg(long)]
+    build_url: Option<String>,
+}
+
+impl MetadataArgs {
+    fn metadata(&self) -> ReportMetadata {
+        ReportMetadata {
+            title: self.title.clone(),
+            tag: self.tag.clone(),
+            commit: self.commit.clone(),
+            branch: self.branch.clone(),
+            build_url: self.build_url.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Cobertura,
+    Snapshot,
+    Csv,
+    Lcov,
+}
+
+#[derive(Args)]
+struct MergeArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[arg(short, long)]
+    output: PathBuf,
+    #[arg(long, value_enum, default_value_t = OutputFormat::Cobertura)]
+    format: OutputFormat,
+    /// Keep attributes that aren't part of the Cobertura format, e.g. those
+    /// added by coverage.py.
+    #[arg(long)]
+    round_trip: bool,
+}
+
+#[derive(Args)]
+struct SplitArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// The directory to write `coverage-1.xml`, `coverage-2.xml`, ... to.
+    /// Parts without any classes are left out.
+    #[arg(long, default_value = ".")]
+    output_dir: PathBuf,
+    /// Put the classes whose file name or package matches this glob into
+    /// their own file, followed by a file with the remaining classes. Can be
+    /// repeated.
+    #[arg(long, required_unless_present = "parts")]
+    pattern: Vec<String>,
+    /// Split into at most this many files of about the same size, keeping
+    /// packages whole.
+    #[arg(long, conflicts_with = "pattern")]
+    parts: Option<std::num::NonZeroUsize>,
+    /// Keep attributes that aren't part of the Cobertura format.
+    #[arg(long)]
+    round_trip: bool,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    before: PathBuf,
+    after: PathBuf,
+    #[command(flatten)]
+    load: LoadArgs,
+    #[command(flatten)]
+    rounding: RoundingArgs,
+    #[command(flatten)]
+    language: LanguageArgs,
+    /// Also write `comparison.html` to this directory.
+    #[arg(long)]
+    html: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct ConvertArgs {
+    input: PathBuf,
+    #[arg(short, long)]
+    output: PathBuf,
+    #[arg(long, value_enum)]
+    to: OutputFormat,
+    #[command(flatten)]
+    load: LoadArgs,
+    /// Keep attributes that aren't part of the Cobertura format, so that the
+    /// output parses to the same report.
+    #[arg(long)]
+    round_trip: bool,
+    /// Sort the report and normalize its representation first.
+    #[arg(long)]
+    canonicalize: bool,
+}
+
+#[derive(Args)]
+struct CheckArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// A coverage policy (TOML) to check against.
+    #[arg(long)]
+    policy: Option<PathBuf>,
+    /// How far declared rates may be off. Rates are usually written with
+    /// limited precision.
+    #[arg(long, default_value_t = 1e-4)]
+    tolerance: f64,
+    /// The git revision that files are new relative to, for the `[new-files]`
+    /// thresholds of the policy. Overrides the policy's `base`.
+    #[arg(long)]
+    base: Option<String>,
+    /// The git repository to look for new files in.
+    #[arg(long, default_value = ".")]
+    repo: PathBuf,
+    /// Write the results of the check as JSON to this file, also if the
+    /// check fails.
+    #[arg(long)]
+    check_output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct WorstArgs {
+    /// The number of worst covered files or classes to list.
+    #[arg(long = "worst-count", default_value_t = 10)]
+    count: usize,
+    /// List the worst covered classes instead of files.
+    #[arg(long = "worst-classes")]
+    classes: bool,
+    /// Rank by the number of `uncovered` lines, or by the line coverage
+    /// `rate`.
+    #[arg(long = "worst-by", default_value = "uncovered", value_parser = parse_worst_order)]
+    order: WorstOrder,
+}
+
+impl WorstArgs {
+    fn options(&self, ranking: &RankingArgs) -> WorstCoveredOptions {
+        WorstCoveredOptions {
+            count: self.count,
+            classes: self.classes,
+            order: self.order,
+            ranking: ranking.filter(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum WorstFormat {
+    Text,
+    Markdown,
+}
+
+#[derive(Args)]
+struct WorstCoveredArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[command(flatten)]
+    worst: WorstArgs,
+    #[command(flatten)]
+    ranking: RankingArgs,
+    #[command(flatten)]
+    rounding: RoundingArgs,
+    #[arg(long, default_value = "text")]
+    format: WorstFormat,
+}
+
+#[derive(Args)]
+struct StatsArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[command(flatten)]
+    ranking: RankingArgs,
+}
+
+#[derive(Args)]
+struct SummaryArgs {
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// Also print the declared rates of each package.
+    #[arg(long)]
+    packages: bool,
+    #[command(flatten)]
+    rounding: RoundingArgs,
+    /// The tool that produced the files, to work around its quirks.
+    #[arg(long, value_parser = parse_profile, default_value = "generic")]
+    profile: Profile,
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[command(flatten)]
+    rounding: RoundingArgs,
+    #[command(flatten)]
+    language: LanguageArgs,
+    #[command(flatten)]
+    metadata: MetadataArgs,
+    /// The port to listen on, on `127.0.0.1`. `0` picks a free port.
+    #[arg(long, default_value_t = 8000)]
+    port: u16,
+}
+
+#[derive(Args)]
+struct OwnersArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// The `CODEOWNERS` file.
+    #[arg(long, default_value = ".github/CODEOWNERS")]
+    codeowners: PathBuf,
+    #[arg(long, value_enum, default_value_t = PrintFormat::Text)]
+    format: PrintFormat,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum UploadService {
+    Coveralls,
+    Codecov,
+}
+
+#[derive(Args)]
+struct UploadArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[arg(long, value_enum)]
+    service: UploadService,
+    /// The token of the repository. Defaults to `$COVERALLS_REPO_TOKEN` or
+    /// `$CODECOV_TOKEN`.
+    #[arg(long)]
+    token: Option<String>,
+    /// The git repository the coverage was collected in.
+    #[arg(long, default_value = ".")]
+    repo: PathBuf,
+    /// The branch, if none is checked out. Detected on GitHub Actions.
+    #[arg(long)]
+    branch: Option<String>,
+    /// The CI service, e.g. `github`. Detected on GitHub Actions.
+    #[arg(long)]
+    service_name: Option<String>,
+    /// The id of the CI job. Detected on GitHub Actions.
+    #[arg(long)]
+    job_id: Option<String>,
+    /// Upload to this URL instead of to the service.
+    #[arg(long)]
+    url: Option<String>,
+    /// Write the payload to this file instead of uploading it.
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum NotifyService {
+    Slack,
+    Teams,
+}
+
+#[derive(Args)]
+struct NotifyArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    #[arg(long, value_enum)]
+    service: NotifyService,
+    /// The URL of the incoming webhook.
+    #[arg(long, required_unless_present = "output")]
+    webhook: Option<String>,
+    /// Show the change since the latest run recorded in this history
+    /// directory.
+    #[arg(long)]
+    history: Option<PathBuf>,
+    /// Show whether this coverage policy (TOML) is met.
+    #[arg(long)]
+    policy: Option<PathBuf>,
+    #[command(flatten)]
+    rounding: RoundingArgs,
+    #[command(flatten)]
+    metadata: MetadataArgs,
+    /// Write the payload to this file instead of posting it.
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+/// Which history entries to keep, see [`RetentionPolicy`].
+#[derive(Args)]
+struct RetentionArgs {
+    /// Keep this many of the most recent history entries.
+    #[arg(long, value_name = "N")]
+    keep_last: Option<usize>,
+    /// Keep all history entries of this period before now, e.g. `30d`, `12w`
+    /// or `48h`.
+    #[arg(long, value_name = "PERIOD", value_parser = parse_period)]
+    keep_within: Option<Duration>,
+    /// Keep the most recent history entry of every `day` or `week`.
+    #[arg(long, value_name = "INTERVAL", value_parser = parse_retention_interval)]
+    keep_one_per: Option<RetentionInterval>,
+}
+
+impl RetentionArgs {
+    /// The policy, or `None` if no option was given.
+    fn policy(&self) -> Option<RetentionPolicy> {
+        if self.keep_last.is_none() && self.keep_within.is_none() && self.keep_one_per.is_none() {
+            return None;
+        }
+
+        Some(RetentionPolicy {
+            keep_last: self.keep_last.unwrap_or_default(),
+            keep_within: self.keep_within,
+            keep_one_per: self.keep_one_per,
+        })
+    }
+}
+
+#[derive(Args)]
+struct HistoryArgs {
+    #[command(subcommand)]
+    command: HistoryCommand,
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// Remove the entries of a history directory that the retention options
+    /// don't keep.
+    Prune(PruneArgs),
+}
+
+#[derive(Args)]
+struct PruneArgs {
+    /// The history directory.
+    dir: PathBuf,
+    #[command(flatten)]
+    retention: RetentionArgs,
+    /// Only print the entries that would be removed.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args)]
+struct VerifyReportArgs {
+    /// The output directory of the report.
+    #[arg(default_value = "output-rs")]
+    dir: PathBuf,
+    /// Also check that the manifest is signed with the secret key in this
+    /// file.
+    #[arg(long)]
+    signing_key: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return if e.use_stderr() {
+                ExitCode::from(Exit::Usage as u8)
+            } else {
+                ExitCode::SUCCESS
+            };
+        }
+    };
+    cli.init_logger();
+
+    let result = match &cli.command {
+        Command::Report(args) if args.watch => watch(args),
+        Command::Report(args) => report(args),
+        Command::Merge(args) => merge(args),
+        Command::Split(args) => split(args),
+        Command::Diff(args) => diff(args),
+        Command::Convert(args) => convert(args),
+        Command::Check(args) => check(args),
+        Command::Stats(args) => stats(args),
+        Command::WorstCovered(args) => worst_covered(args),
+        Command::Owners(args) => owners(args),
+        Command::Summary(args) => summary(args),
+        Command::Serve(args) => serve(args),
+        Command::Upload(args) => upload(args),
+        Command::Notify(args) => notify(args),
+        Command::VerifyReport(args) => verify_report(args),
+        Command::History(args) => match &args.command {
+            HistoryCommand::Prune(args) => prune_history(args),
+        },
+    };
+
+    match result {
+        Ok(()) => ExitCode::from(Exit::Ok as u8),
+        Err(e) => {
+            log::error!("{e}");
+            ExitCode::from(Failure::exit(&e) as u8)
+        }
+    }
+}
+
+fn watch(args: &ReportArgs) -> std::io::Result<()> {
+    if let Err(e) = report(args) {
+        log::error!("{e}");
+    }
+
+    on_input_change(&args.input, || {
+        if let Err(e) = report(args) {
+            log::error!("{e}");
+        }
+    })
+}
+
+/// Call `on_change` whenever one of the inputs changes, forever.
+fn on_input_change(input: &InputArgs, mut on_change: impl FnMut()) -> std::io::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(std::io::Error::other)?;
+
+    // Watch the containing directories rather than the files themselves, as
+    // coverage tools usually replace the file instead of writing to it.
+    let mut watched = Vec::new();
+    for input in &input.inputs {
+        let file = std::path::absolute(&input.path)?;
+        let dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        if !watched.contains(&dir) {
+            watcher
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .map_err(std::io::Error::other)?;
+            watched.push(dir);
+        }
+    }
+
+    let inputs: Vec<_> = input
+        .inputs
+        .iter()
+        .map(|input| std::path::absolute(&input.path))
+        .collect::<Result<_, _>>()?;
+
+    let is_relevant = |event: &notify::Result<notify::Event>| match event {
+        Ok(event) => !event.kind.is_access() && event.paths.iter().any(|p| inputs.contains(p)),
+        Err(_) => false,
+    };
+
+    loop {
+        log::info!("Watching for changes...");
+
+        loop {
+            let event = rx.recv().map_err(std::io::Error::other)?;
+            if is_relevant(&event) {
+                break;
+            }
+        }
+
+        // Wait until the writer is done, and coalesce the events it causes.
+        while rx.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+
+        on_change();
+    }
+}
+
+fn worst_covered(args: &WorstCoveredArgs) -> std::io::Result<()> {
+    let coverage = load_inputs_without_generated(&args.input)?;
+    let options = args.worst.options(&args.ranking);
+    let rounding = args.rounding.policy();
+    let out = std::io::stdout().lock();
+
+    match args.format {
+        WorstFormat::Text => WorstCoveredGenerator::write_text(&coverage, &options, &rounding, out),
+        WorstFormat::Markdown => {
+            WorstCoveredGenerator::write_markdown(&coverage, &options, &rounding, out)
+        }
+    }
+}
+
+fn stats(args: &StatsArgs) -> std::io::Result<()> {
+    let start = Instant::now();
+    let coverage = load_inputs_without_generated(&args.input)?;
+    let parse_time = start.elapsed();
+
+    print!(
+        "{}",
+        coverage.statistics_with(STATS_TOP_FILES, &args.ranking.filter())
     );
+    print!("{}", coverage.hit_statistics());
+    println!("Parse time:         {parse_time:.2?}");
+
+    Ok(())
+}
+
+fn owners(args: &OwnersArgs) -> std::io::Result<()> {
+    let coverage = load_inputs_without_generated(&args.input)?;
+    let owners = CodeOwners::load(&args.codeowners)?;
+    let summaries = coverage.by_owner(&owners);
+
+    match args.format {
+        PrintFormat::Text => OwnershipGenerator::write_text(&summaries, std::io::stdout().lock()),
+        PrintFormat::Json => OwnershipGenerator::write_json(&summaries, std::io::stdout().lock()),
+    }
+}
+
+fn upload(args: &UploadArgs) -> std::io::Result<()> {
+    let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+    let github = env("GITHUB_ACTIONS").is_some();
+
+    let coverage = load_inputs(&args.input)?;
+    let mut git = GitInfo::read(&args.repo)?;
+    git.branch = args
+        .branch
+        .clone()
+        .or(git.branch)
+        .or_else(|| env("GITHUB_HEAD_REF"))
+        .or_else(|| env("GITHUB_REF_NAME"));
+    let service_name = args
+        .service_name
+        .clone()
+        .or_else(|| github.then(|| "github".to_string()));
+    let job_id = args.job_id.clone().or_else(|| env("GITHUB_RUN_ID"));
+
+    let mut payload = Vec::new();
+    let token = match args.service {
+        UploadService::Coveralls => {
+            let options = CoverallsOptions {
+                repo_token: args.token.clone().or_else(|| env("COVERALLS_REPO_TOKEN")),
+                service_name: service_name.clone(),
+                service_job_id: job_id.clone(),
+                git: Some(git.clone()),
+            };
+            let resolver = coverage.source_resolver();
+            CoverallsGenerator::write(
+                &coverage,
+                &options,
+                |path| resolver.read(path),
+                &mut payload,
+            )?;
+            options.repo_token
+        }
+        UploadService::Codecov => {
+            CodecovGenerator::write(&coverage, &mut payload)?;
+            args.token.clone().or_else(|| env("CODECOV_TOKEN"))
+        }
+    };
 
-    if calculated_line_rate != coverage.line_rate {
-        let err = std::io::Error::other(
-            "Reported coverage line rate does not match calculated line rate.",
+    if let Some(path) = &args.output {
+        return std::fs::write(path, payload);
+    }
+
+    match args.service {
+        UploadService::Coveralls => {
+            let url = args.url.as_deref().unwrap_or(COVERALLS_URL);
+            let response = curl(
+                &["-F", "json_file=@-;filename=coveralls.json", url],
+                &payload,
+            )?;
+            log::info!("Uploaded the coverage to Coveralls: {}", response.trim());
+        }
+        UploadService::Codecov => {
+            let query: Vec<_> = [
+                ("commit", Some(git.commit)),
+                ("branch", git.branch),
+                ("service", service_name),
+                ("build", job_id),
+            ]
+            .into_iter()
+            .filter_map(|(key, value)| Some(format!("{key}={}", percent_encode(&value?))))
+            .collect();
+            let url = format!(
+                "{}/upload/v4?{}",
+                args.url.as_deref().unwrap_or(CODECOV_URL),
+                query.join("&")
+            );
+
+            // The token only goes into the config file, so that it doesn't
+            // show up in the process list or in the URL.
+            let mut config = curl_config("url", &url);
+            if let Some(token) = &token {
+                config += &curl_config("header", &format!("Authorization: token {token}"));
+            }
+
+            // Codecov answers with the URL of the report, and where to put it.
+            let response =
+                curl_with_config(&config, &["-X", "POST", "-H", "Accept: text/plain"], &[])?;
+            let mut lines = response.lines();
+            let (Some(report_url), Some(upload_url)) = (lines.next(), lines.next()) else {
+                return Err(std::io::Error::other(format!(
+                    "Unexpected response from Codecov: {response}"
+                )));
+            };
+
+            let mut body = b"<<<<<< network\n# path=coverage.json\n".to_vec();
+            body.extend_from_slice(&payload);
+            body.extend_from_slice(b"<<<<<< EOF\n");
+
+            // The upload URL is signed, so it goes into the config as well.
+            curl_with_config(
+                &curl_config("url", upload_url),
+                &[
+                    "-X",
+                    "PUT",
+                    "-H",
+                    "Content-Type: text/plain",
+                    "--data-binary",
+                    "@-",
+                ],
+                &body,
+            )?;
+            log::info!("Uploaded the coverage to Codecov: {report_url}");
+        }
+    }
+
+    Ok(())
+}
+
+fn notify(args: &NotifyArgs) -> std::io::Result<()> {
+    let coverage = load_inputs_without_generated(&args.input)?;
+
+    let threshold_failures = match &args.policy {
+        Some(policy) => Some(
+            PolicyChecker::new(Policy::load(policy)?)
+                .check(&coverage)
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        ),
+        None => None,
+    };
+
+    let options = NotificationOptions {
+        previous: match &args.history {
+            Some(history) => History::new(history).latest()?,
+            None => None,
+        },
+        metadata: args.metadata.metadata(),
+        threshold_failures,
+        rounding: args.rounding.policy(),
+    };
+
+    let mut payload = Vec::new();
+    match args.service {
+        NotifyService::Slack => SlackGenerator::write(&coverage, &options, &mut payload)?,
+        NotifyService::Teams => TeamsGenerator::write(&coverage, &options, &mut payload)?,
+    }
+
+    if let Some(path) = &args.output {
+        return std::fs::write(path, payload);
+    }
+    let Some(url) = &args.webhook else {
+        return Err(std::io::Error::other("Pass --webhook or --output."));
+    };
+
+    // Webhook URLs are secrets, so the URL goes into the config file.
+    curl_with_config(
+        &curl_config("url", url),
+        &[
+            "-X",
+            "POST",
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+        ],
+        &payload,
+    )?;
+    log::info!("Posted the coverage summary to the webhook");
+
+    Ok(())
+}
+
+/// Run `curl` with `args`, passing `body` on stdin, and return its output.
+fn curl(args: &[&str], body: &[u8]) -> std::io::Result<String> {
+    let mut child = std::process::Command::new("curl")
+        .args(["-sS", "--fail-with-body"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(body)?;
+    }
+
+    let output = child.wait_with_output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "Upload failed: {} {}",
+            String::from_utf8_lossy(&output.stderr).trim(),
+            stdout.trim()
+        )));
+    }
+
+    Ok(stdout)
+}
+
+/// Like `curl`, also reading `config` (see `curl_config`) from a private
+/// file, to keep secrets out of the arguments.
+fn curl_with_config(config: &str, args: &[&str], body: &[u8]) -> std::io::Result<String> {
+    let config_file = create_private_file(config.as_bytes())?;
+    let config_path = config_file.to_string_lossy();
+    let args: Vec<_> = ["-K", &*config_path].iter().chain(args).copied().collect();
+
+    let result = curl(&args, body);
+    std::fs::remove_file(&config_file)?;
+    result
+}
+
+/// Create a temporary file with `contents` that only the current user can
+/// read, under a name that can't be guessed, and return its path.
+fn create_private_file(contents: &[u8]) -> std::io::Result<PathBuf> {
+    let mut options = std::fs::OpenOptions::new();
+    // Fails instead of following a symlink that someone else put there.
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    for _ in 0..PRIVATE_FILE_ATTEMPTS {
+        let name = RandomState::new().hash_one((std::process::id(), Instant::now()));
+        let path = std::env::temp_dir().join(format!("cobertura-rs-{name:016x}"));
+
+        let mut file = match options.open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        if let Err(e) = file.write_all(contents) {
+            let _ = std::fs::remove_file(&path);
+            return Err(e);
+        }
+        return Ok(path);
+    }
+
+    Err(std::io::Error::new(
+        std::io::ErrorKind::AlreadyExists,
+        "Could not create a temporary file.",
+    ))
+}
+
+// A line of a curl config file, setting the option `name` to `value`.
+fn curl_config(name: &str, value: &str) -> String {
+    let value = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    format!("{name} = \"{value}\"\n")
+}
+
+fn percent_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            byte => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+fn summary(args: &SummaryArgs) -> std::io::Result<()> {
+    let rounding = args.rounding.policy();
+    let mut parser = Parser::with_profile(args.profile);
+
+    for file in &args.files {
+        let reader = BufReader::new(File::open(file)?);
+        let summary = if args.packages {
+            parser.parse_summary_with_packages(reader)
+        } else {
+            parser.parse_summary(reader)
+        };
+        let summary = summary.map_err(|e| Failure::parser(file, e))?;
+
+        for warning in &parser.report().warnings {
+            log::warn!("{}: {warning}", file.display());
+        }
+
+        println!(
+            "{}: line coverage {}, branch coverage {}",
+            file.display(),
+            rounding.format_rate(summary.line_rate),
+            rounding.format_rate(summary.branch_rate)
         );
-        return Err(err);
+
+        for package in &summary.packages {
+            println!(
+                "  {}: line coverage {}, branch coverage {}",
+                package.name,
+                rounding.format_rate(package.line_rate),
+                rounding.format_rate(package.branch_rate)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The report served by `serve`.
+struct Site {
+    coverage: Coverage,
+    metadata: ReportMetadata,
+    pages: MemorySink,
+}
+
+impl Site {
+    fn generate(args: &ServeArgs) -> std::io::Result<Self> {
+        let coverage = load_inputs_without_generated(&args.input)?;
+        let metadata = args.metadata.metadata();
+        let options = HtmlOptions {
+            rounding: args.rounding.policy(),
+            translation: args.language.translation()?,
+            metadata: metadata.clone(),
+            ..Default::default()
+        };
+
+        let mut pages = MemorySink::new();
+        HtmlGenerator::generate_to(&coverage, &options, &mut pages)?;
+
+        Ok(Self {
+            coverage,
+            metadata,
+            pages,
+        })
+    }
+
+    /// The status, content type and body of the response to a `GET` of
+    /// `path`.
+    fn get(&self, path: &str) -> std::io::Result<(&'static str, &'static str, Vec<u8>)> {
+        const JSON: &str = "application/json";
+
+        let mut body = Vec::new();
+        if path == "/api/summary" {
+            SummaryGenerator::write_json(&self.coverage, None, &self.metadata, &[], &mut body)?;
+            return Ok(("200 OK", JSON, body));
+        }
+
+        if path == "/api/files" {
+            let files: Vec<_> = self
+                .coverage
+                .files()
+                .map(|file| {
+                    serde_json::json!({
+                        "path": file.path,
+                        "line_rate": file.line_rate,
+                        "branch_rate": file.branch_rate,
+                    })
+                })
+                .collect();
+            serde_json::to_writer_pretty(&mut body, &files).map_err(std::io::Error::other)?;
+            return Ok(("200 OK", JSON, body));
+        }
+
+        if let Some(file) = path.strip_prefix("/api/files/") {
+            let Some(file) = self.coverage.files().find(|f| f.path == Path::new(file)) else {
+                return Ok((
+                    "404 Not Found",
+                    "text/plain; charset=utf-8",
+                    b"Not found".to_vec(),
+                ));
+            };
+
+            let lines: Vec<_> = file
+                .unique_lines()
+                .iter()
+                .map(|line| {
+                    serde_json::json!({
+                        "number": line.number,
+                        "hits": line.hits,
+                        "branches": line.branch_counts().map(|(covered, valid)| {
+                            serde_json::json!({ "covered": covered, "valid": valid })
+                        }),
+                    })
+                })
+                .collect();
+            let classes: Vec<_> = file.classes.iter().map(|c| &*c.name).collect();
+
+            let json = serde_json::json!({
+                "path": file.path,
+                "line_rate": file.line_rate,
+                "branch_rate": file.branch_rate,
+                "classes": classes,
+                "lines": lines,
+            });
+            serde_json::to_writer_pretty(&mut body, &json).map_err(std::io::Error::other)?;
+            return Ok(("200 OK", JSON, body));
+        }
+
+        let page = match path.strip_prefix('/') {
+            Some("") | None => "index.html",
+            Some(page) => page,
+        };
+        Ok(match self.pages.files.get(page) {
+            Some(contents) => ("200 OK", content_type(page), contents.clone()),
+            None => (
+                "404 Not Found",
+                "text/plain; charset=utf-8",
+                b"Not found".to_vec(),
+            ),
+        })
+    }
+}
+
+fn serve(args: &ServeArgs) -> std::io::Result<()> {
+    // Swapped for a new one when the inputs change. The lock is only held to
+    // take or replace the site, never while answering a request.
+    let site = Mutex::new(Arc::new(Site::generate(args)?));
+    let current = || Arc::clone(&site.lock().unwrap_or_else(PoisonError::into_inner));
+    let listener = TcpListener::bind(("127.0.0.1", args.port))?;
+    log::info!("Serving the report at http://{}/", listener.local_addr()?);
+
+    let (streams, incoming) = mpsc::sync_channel::<TcpStream>(SERVE_THREADS);
+    let incoming = Mutex::new(incoming);
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            let result = on_input_change(&args.input, || match Site::generate(args) {
+                Ok(generated) => {
+                    *site.lock().unwrap_or_else(PoisonError::into_inner) = Arc::new(generated);
+                    log::info!("Regenerated the report.");
+                }
+                Err(e) => log::error!("{e}"),
+            });
+
+            if let Err(e) = result {
+                log::error!("Stopped watching the inputs: {e}");
+            }
+        });
+
+        for _ in 0..SERVE_THREADS {
+            scope.spawn(|| loop {
+                let stream = incoming
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .recv();
+                let Ok(stream) = stream else {
+                    break;
+                };
+
+                let result = read_request(&stream)
+                    .and_then(|request| respond(&stream, &request, &current()));
+                if let Err(e) = result {
+                    log::debug!("Failed to answer a request: {e}");
+                }
+            });
+        }
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if streams.send(stream).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => log::debug!("Failed to accept a connection: {e}"),
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// The method and decoded path of an HTTP request.
+struct Request {
+    method: String,
+    path: String,
+}
+
+/// Read the HTTP request on `stream`, up to the end of its headers.
+fn read_request(stream: &TcpStream) -> std::io::Result<Request> {
+    stream.set_read_timeout(Some(SERVE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // The headers are not needed, but must be read before answering.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default();
+    let path = percent_decode(target.split(['?', '#']).next().unwrap_or_default());
+    log::debug!("{method} {path}");
+
+    Ok(Request { method, path })
+}
+
+/// Answer `request` on `stream`, closing the connection afterwards.
+fn respond(stream: &TcpStream, request: &Request, site: &Site) -> std::io::Result<()> {
+    let (status, content_type, body) = match request.method.as_str() {
+        "GET" | "HEAD" => site.get(&request.path)?,
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            b"Method not allowed".to_vec(),
+        ),
+    };
+
+    let mut out = BufWriter::new(stream);
+    write!(
+        out,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    if request.method != "HEAD" {
+        out.write_all(&body)?;
+    }
+    out.flush()
+}
+
+// Decode `%XX` escapes, e.g. the spaces in the names of package pages.
+fn percent_decode(input: &str) -> String {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut rest = input.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = (byte == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn load_inputs(args: &InputArgs) -> std::io::Result<Coverage> {
+    load_inputs_with(args, None)
+}
+
+/// Like `load_inputs`, recording which inputs contributed hits to which lines
+/// in `provenance`.
+fn load_inputs_with(
+    args: &InputArgs,
+    mut provenance: Option<&mut LineProvenance>,
+) -> std::io::Result<Coverage> {
+    let mut coverage: Option<Coverage> = None;
+
+    for input in &args.inputs {
+        let mut parsed = load_file(&input.path, &args.load)?;
+        // Each input only counts as covering the lines it hit often enough.
+        // Snapshots may already have a higher threshold.
+        parsed.min_hits = parsed.min_hits.max(args.min_hits);
+        if let Some(provenance) = provenance.as_deref_mut() {
+            provenance.add_input(input.path.display().to_string(), &parsed);
+        }
+
+        match (&mut coverage, &input.project) {
+            (Some(coverage), Some(project)) => {
+                coverage.merge_project_with(project, parsed, &args.merge_options())
+            }
+            (Some(coverage), None) => coverage.merge_with(parsed, &args.merge_options()),
+            (None, project) => {
+                for package in &mut parsed.packages {
+                    package.project = project.clone();
+                }
+                coverage = Some(parsed);
+            }
+        }
+    }
+
+    let mut coverage = coverage.unwrap();
+
+    if args.consolidate {
+        coverage.consolidate();
+    }
+
+    if let Some(precedence) = args.reconcile {
+        let report = coverage.reconcile_lines(precedence);
+        for conflict in &report.conflicts {
+            log::warn!(
+                "Line {} of {} has {} hit(s), but {} in method {}.",
+                conflict.line,
+                conflict.class,
+                conflict.class_hits,
+                conflict.method_hits,
+                conflict.method
+            );
+        }
+        log::info!(
+            "Reconciled {} conflicting line(s), added {} method line(s) to their classes.",
+            report.conflicts.len(),
+            report.added_lines
+        );
+    }
+
+    if args.min_hits > 1 {
+        let uncovered = coverage.apply_min_hits(args.min_hits);
+        log::info!(
+            "Counted {uncovered} line(s) hit fewer than {} times as uncovered.",
+            args.min_hits
+        );
+    }
+
+    if let Some(max) = args.max_hits {
+        let capped = coverage.cap_hits(max);
+        log::info!("Capped the hits of {capped} line(s) to {max}.");
+    }
+
+    if let Some(path) = &args.verify_checksums {
+        let checksums = SourceChecksums::load(BufReader::new(File::open(path)?))?;
+        let resolver = coverage.source_resolver();
+
+        for mismatch in checksums.verify(|path| resolver.read(path)) {
+            log::warn!("{mismatch}");
+        }
+    }
+
+    if args.exclusions {
+        let resolver = coverage.source_resolver();
+        let outcome =
+            coverage.apply_exclusions(&args.exclusions(), |path| resolver.read_to_string(path));
+
+        for file in &outcome.unreadable {
+            log::warn!(
+                "Could not read {} to look for exclusion markers.",
+                file.display()
+            );
+        }
+
+        log::info!("Excluded {} line(s).", outcome.excluded_lines);
+    }
+
+    if args.complexity {
+        let resolver = coverage.source_resolver();
+        let outcome = coverage.compute_complexity(false, |path| resolver.read_to_string(path));
+
+        for file in &outcome.unreadable {
+            log::warn!("Could not read {} to compute complexity.", file.display());
+        }
+
+        log::info!(
+            "Computed complexity of {} method(s) and {} class(es).",
+            outcome.methods,
+            outcome.classes
+        );
+    }
+
+    Ok(coverage)
+}
+
+/// Leave the generated code out of `coverage`, so that it doesn't count
+/// towards the numbers of summaries, checks and reports (unless
+/// `--include-generated`), and return it to be listed separately. Commands
+/// that write coverage files don't call this, and keep generated code.
+fn split_generated(coverage: &mut Coverage, args: &InputArgs) -> Coverage {
+    let classifier = args.generated_code();
+    let resolver = coverage.source_resolver();
+    let load_source = |path: &Path| resolver.read_to_string(path);
+    let outcome = if args.include_generated {
+        coverage.find_generated(&classifier, load_source)
     } else {
-        println!("Validation OK :)");
+        coverage.split_generated(&classifier, load_source)
+    };
+
+    for file in &outcome.unreadable {
+        log::warn!(
+            "Could not read {} to look for generated code markers.",
+            file.display()
+        );
+    }
+
+    let generated = outcome.generated.classes().count();
+    if generated > 0 && !args.include_generated {
+        log::info!("Left out {generated} class(es) of generated code.");
+    }
+
+    outcome.generated
+}
+
+/// Load the inputs for a command that only shows or checks their coverage,
+/// without generated code (see `split_generated`).
+fn load_inputs_without_generated(args: &InputArgs) -> std::io::Result<Coverage> {
+    let mut coverage = load_inputs(args)?;
+    split_generated(&mut coverage, args);
+    Ok(coverage)
+}
+
+fn report(args: &ReportArgs) -> std::io::Result<()> {
+    let mut provenance = args.provenance.then(LineProvenance::new);
+    let mut coverage = load_inputs_with(&args.input, provenance.as_mut())?;
+    let rounding = args.rounding.policy();
+
+    if let Some(path) = &args.save_snapshot {
+        coverage.save_snapshot(BufWriter::new(File::create(path)?))?;
+    }
+
+    if let Some(path) = &args.save_checksums {
+        let resolver = coverage.source_resolver();
+        let checksums = SourceChecksums::compute(&coverage, |path| resolver.read(path));
+        log::info!("Saved the checksums of {} source file(s).", checksums.len());
+        checksums.save(BufWriter::new(File::create(path)?))?;
+    }
+
+    let generated = split_generated(&mut coverage, &args.input);
+
+    let components = match &args.components {
+        Some(path) => coverage.by_component(&ComponentMap::load(path)?),
+        None => Vec::new(),
+    };
+
+    let metadata = args.metadata.metadata();
+    let history = args.history.as_ref().map(History::new);
+    let mut trend = match &history {
+        Some(history) => history.entries()?,
+        None => Vec::new(),
+    };
+    let previous = trend.last().cloned();
+    trend.drain(..trend.len().saturating_sub(args.trend_runs));
+
+    SummaryGenerator::write_text(
+        &coverage,
+        previous.as_ref(),
+        &rounding,
+        std::io::stdout().lock(),
+    )?;
+
+    ComponentGenerator::write_text(&components, std::io::stdout().lock())?;
+
+    if args.effective_coverage {
+        let mut trivial = TrivialLines::default();
+        trivial.tokens.extend(args.trivial_line.iter().cloned());
+
+        let resolver = coverage.source_resolver();
+        let outcome = coverage.effective_coverage(&trivial, |path| resolver.read_to_string(path));
+        for file in &outcome.unreadable {
+            log::warn!(
+                "Could not read {} to look for trivial lines.",
+                file.display()
+            );
+        }
+
+        let format =
+            |rate: Option<f64>| rate.map_or_else(|| "-".to_string(), |r| rounding.format_rate(r));
+        println!(
+            "Effective line coverage: {} (raw {}, {} trivial line(s) left out)",
+            format(outcome.effective.line_rate),
+            format(outcome.raw.line_rate),
+            outcome.trivial_lines
+        );
+    }
+
+    if let Some(path) = &args.markdown_summary {
+        let out = File::options().create(true).append(true).open(path)?;
+        SummaryGenerator::write_markdown(
+            &coverage,
+            previous.as_ref(),
+            &metadata,
+            &components,
+            &rounding,
+            out,
+        )?;
     }
 
-    HtmlGenerator::generate_pages(&coverage)?;
+    if let Some(path) = &args.json_summary {
+        let out = BufWriter::new(File::create(path)?);
+        SummaryGenerator::write_json(&coverage, previous.as_ref(), &metadata, &components, out)?;
+    }
+
+    let markdown_summary = MarkdownSummaryReport {
+        previous: previous.clone(),
+        metadata: metadata.clone(),
+        components: components.clone(),
+        rounding,
+    };
+
+    let html_options = HtmlOptions {
+        rounding,
+        incremental: args.incremental,
+        single_file: args.single_file,
+        tests: args
+            .tests
+            .as_deref()
+            .map(|manifest| load_tests(manifest, &args.input))
+            .transpose()?,
+        provenance,
+        namespace_separator: args.namespace_separator.clone(),
+        translation: args.language.translation()?,
+        owners: args
+            .codeowners
+            .as_deref()
+            .map(CodeOwners::load)
+            .transpose()?,
+        components,
+        previous,
+        trend,
+        high_contrast: args.high_contrast,
+        hit_histogram: args.hit_histogram,
+        ranking: args.ranking.filter(),
+        worst_covered: args
+            .worst_covered
+            .then(|| args.worst.options(&args.ranking)),
+        metadata: metadata.clone(),
+        assets: AssetOptions {
+            minify: args.minify,
+            content_hash: args.hash_assets,
+            inline_limit: args.inline_assets_below,
+        },
+        generated: Some(generated),
+        ..Default::default()
+    };
+
+    if let Some(path) = &args.digest {
+        let out = BufWriter::new(File::create(path)?);
+        HtmlGenerator::generate_digest(&coverage, &html_options, out)?;
+    }
+
+    if let Some(baseline) = &args.compare {
+        let baseline = load_file(baseline, &args.input.load)?;
+        let diff = CoverageDiff::new(&baseline, &coverage);
+        let mut sink = DirectorySink::new(&args.output_dir);
+        HtmlGenerator::generate_comparison(&diff, &html_options, &mut sink)?;
+    }
+
+    let mut registry = GeneratorRegistry::new();
+    registry.register(HtmlReport {
+        options: html_options,
+    });
+    registry.register(markdown_summary);
+    registry.register(CsvReport);
+    registry.register(BadgesReport {
+        scope: args.badges.clone().unwrap_or(BadgeScope::Total),
+        rounding,
+    });
+    registry.register(WorstCoveredReport {
+        options: args.worst.options(&args.ranking),
+        rounding,
+    });
+
+    let mut report_types = args.report_types.clone();
+    if args.badges.is_some()
+        && !report_types
+            .iter()
+            .any(|t| t.eq_ignore_ascii_case("Badges"))
+    {
+        report_types.push("Badges".to_string());
+    }
+
+    match &args.zip {
+        Some(path) => {
+            let mut sink = ZipSink::new(BufWriter::new(File::create(path)?));
+            generate_reports(&coverage, &registry, &report_types, &mut sink, args)?;
+            sink.finish()?;
+        }
+        None => {
+            let mut sink = DirectorySink::new(&args.output_dir);
+            generate_reports(&coverage, &registry, &report_types, &mut sink, args)?;
+        }
+    }
+
+    match args.uncovered {
+        Some(PrintFormat::Text) => {
+            UncoveredLinesGenerator::write_text(&coverage, std::io::stdout().lock())?
+        }
+        Some(PrintFormat::Json) => {
+            UncoveredLinesGenerator::write_json(&coverage, std::io::stdout().lock())?
+        }
+        None => {}
+    }
+
+    if args.teamcity {
+        TeamCityGenerator::write(&coverage, std::io::stdout().lock())?;
+    }
+
+    if let Some(history) = &history {
+        history.record(&HistoryEntry::new(&coverage, &metadata))?;
+
+        if let Some(policy) = args.retention.policy() {
+            let pruned = history.prune(&policy, unix_now())?;
+            log::info!("Pruned {} history entries", pruned.len());
+        }
+    }
+
+    Ok(())
+}
+
+/// Generate the reports of `report_types` into `sink`, with a (signed)
+/// manifest of their files if asked for.
+fn generate_reports(
+    coverage: &Coverage,
+    registry: &GeneratorRegistry,
+    report_types: &[String],
+    sink: &mut dyn ReportSink,
+    args: &ReportArgs,
+) -> std::io::Result<()> {
+    let key = args
+        .signing_key
+        .as_deref()
+        .map(read_signing_key)
+        .transpose()?;
+
+    if !args.report_manifest && key.is_none() {
+        return registry.generate(report_types, coverage, sink);
+    }
+
+    let mut sink = ManifestSink::new(sink);
+    registry.generate(report_types, coverage, &mut sink)?;
+    let manifest = sink.finish(key.as_deref())?;
+    log::info!(
+        "Wrote {} with {} file(s)",
+        ReportManifest::FILE_NAME,
+        manifest.files().count()
+    );
+
+    Ok(())
+}
+
+// Trailing whitespace (e.g. a newline) is not part of the key.
+fn read_signing_key(path: &Path) -> std::io::Result<Vec<u8>> {
+    let key = std::fs::read(path)?;
+    Ok(key.trim_ascii_end().to_vec())
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+fn prune_history(args: &PruneArgs) -> std::io::Result<()> {
+    let Some(policy) = args.retention.policy() else {
+        return Err(std::io::Error::other(
+            "Pass at least one of --keep-last, --keep-within and --keep-one-per.",
+        ));
+    };
+
+    let history = History::new(&args.dir);
+    let pruned = if args.dry_run {
+        history.prunable(&policy, unix_now())?
+    } else {
+        history.prune(&policy, unix_now())?
+    };
+
+    for path in &pruned {
+        println!("{}", path.display());
+    }
+    log::info!("Pruned {} history entries", pruned.len());
+
+    Ok(())
+}
+
+fn verify_report(args: &VerifyReportArgs) -> std::io::Result<()> {
+    let sink = DirectorySink::new(&args.dir);
+    let manifest = sink.read(ReportManifest::FILE_NAME)?.ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "{} has no {}",
+                args.dir.display(),
+                ReportManifest::FILE_NAME
+            ),
+        )
+    })?;
+    let manifest = ReportManifest::from_json(&manifest)?;
+
+    if let Some(path) = &args.signing_key {
+        if !manifest.verify_signature(&read_signing_key(path)?) {
+            return Err(std::io::Error::other(
+                "The report manifest is not signed with this key.",
+            ));
+        }
+    }
+
+    let mismatches = manifest.verify(|path| sink.read(path));
+    for mismatch in &mismatches {
+        println!("{mismatch}");
+    }
+
+    if !mismatches.is_empty() {
+        return Err(std::io::Error::other(format!(
+            "{} file(s) don't match the report manifest.",
+            mismatches.len()
+        )));
+    }
+
+    println!("Report OK ({} file(s))", manifest.files().count());
+    Ok(())
+}
+
+fn write_output(
+    coverage: &Coverage,
+    path: &Path,
+    format: OutputFormat,
+    round_trip: bool,
+) -> std::io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+
+    match format {
+        OutputFormat::Cobertura if round_trip => {
+            CoberturaGenerator::write_round_trip(coverage, &mut out)?
+        }
+        OutputFormat::Cobertura => CoberturaGenerator::write(coverage, &mut out)?,
+        OutputFormat::Snapshot => coverage.save_snapshot(&mut out)?,
+        OutputFormat::Csv => CsvGenerator::write_classes(coverage, &mut out)?,
+        OutputFormat::Lcov => LcovGenerator::write(coverage, &mut out)?,
+    }
+
+    out.flush()
+}
+
+fn merge(args: &MergeArgs) -> std::io::Result<()> {
+    let coverage = load_inputs(&args.input)?;
+    write_output(&coverage, &args.output, args.format, args.round_trip)
+}
+
+fn split(args: &SplitArgs) -> std::io::Result<()> {
+    let coverage = load_inputs(&args.input)?;
+    let parts = match args.parts {
+        Some(parts) => coverage.split_evenly(parts.get()),
+        None => coverage.split_by_patterns(&args.pattern),
+    };
+
+    std::fs::create_dir_all(&args.output_dir)?;
+    let mut written = 0;
+    for part in parts.iter().filter(|p| !p.packages.is_empty()) {
+        written += 1;
+        let path = args.output_dir.join(format!("coverage-{written}.xml"));
+        write_output(part, &path, OutputFormat::Cobertura, args.round_trip)?;
+        log::info!(
+            "Wrote {} with {} package(s)",
+            path.display(),
+            part.packages.len()
+        );
+    }
+
+    Ok(())
+}
+
+fn convert(args: &ConvertArgs) -> std::io::Result<()> {
+    let mut coverage = load_file(&args.input, &args.load)?;
+    if args.canonicalize {
+        coverage.canonicalize();
+    }
+    write_output(&coverage, &args.output, args.to, args.round_trip)
+}
+
+fn diff(args: &DiffArgs) -> std::io::Result<()> {
+    let before = load_file(&args.before, &args.load)?;
+    let after = load_file(&args.after, &args.load)?;
+    let diff = CoverageDiff::new(&before, &after);
+    let rounding = args.rounding.policy();
+
+    let format_change = |change: &RateChange| {
+        let format = |rate: Option<f64>| {
+            rate.map(|r| rounding.format_rate(r))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let delta = change
+            .delta()
+            .map(|d| {
+                format!(
+                    " ({}{})",
+                    if d > 0.0 { "+" } else { "" },
+                    rounding.format_rate(d)
+                )
+            })
+            .unwrap_or_default();
+
+        format!(
+            "{} -> {}{delta}",
+            format(change.before),
+            format(change.after)
+        )
+    };
+
+    println!("Line coverage:   {}", format_change(&diff.line_rate));
+    println!("Branch coverage: {}", format_change(&diff.branch_rate));
+
+    for package in &diff.packages {
+        if package.line_rate.delta() != Some(0.0) {
+            println!("{}: {}", package.name, format_change(&package.line_rate));
+        }
+
+        for class in &package.classes {
+            if let Some(from) = &class.moved_from {
+                let change = if class.is_moved_unchanged() {
+                    "coverage unchanged".to_string()
+                } else {
+                    format_change(&class.line_rate)
+                };
+                println!(
+                    "  {}: moved from {} ({}), {change}",
+                    class.name,
+                    from.name,
+                    from.file_name.display()
+                );
+            } else if class.line_rate.delta() != Some(0.0) {
+                println!("  {}: {}", class.name, format_change(&class.line_rate));
+            }
+        }
+    }
+
+    if !diff.newly_uncovered.is_empty() {
+        println!("Newly uncovered lines:");
+        for file in &diff.newly_uncovered {
+            println!("  {file}");
+        }
+    }
+
+    if let Some(output_dir) = &args.html {
+        let options = HtmlOptions {
+            rounding,
+            translation: args.language.translation()?,
+            ..Default::default()
+        };
+        HtmlGenerator::generate_comparison(&diff, &options, &mut DirectorySink::new(output_dir))?;
+    }
+
+    Ok(())
+}
+
+fn check(args: &CheckArgs) -> std::io::Result<()> {
+    let mut results = serde_json::Map::new();
+    let outcome = run_check(args, &mut results);
+
+    if let Some(path) = &args.check_output {
+        let exit = outcome.as_ref().map_or_else(Failure::exit, |()| Exit::Ok);
+        results.insert("ok".into(), (exit == Exit::Ok).into());
+        results.insert("exit_code".into(), (exit as u8).into());
+        if let Err(e) = &outcome {
+            results.insert("error".into(), error_json(e));
+        }
+
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut out, &results)?;
+        writeln!(out)?;
+        out.flush()?;
+    }
+
+    outcome
+}
+
+// The error of a failed check, with the details of a parse error if it is
+// one.
+fn error_json(error: &std::io::Error) -> serde_json::Value {
+    let mut json = serde_json::json!({ "message": error.to_string() });
+    let parser_error = error
+        .get_ref()
+        .and_then(|e| e.downcast_ref::<Failure>())
+        .and_then(|failure| failure.parser_error.as_ref());
+    if let Some(e) = parser_error {
+        json["parse_error"] = parser_error_json(e);
+    }
+    json
+}
+
+fn parser_error_json(error: &ParserError) -> serde_json::Value {
+    let starts = |names: &[String]| names.iter().map(|n| format!("<{n}>")).collect::<Vec<_>>();
+    let ends = |names: &[String]| names.iter().map(|n| format!("</{n}>")).collect::<Vec<_>>();
+
+    match error {
+        ParserError::ExpectedStart { got, expected } => serde_json::json!({
+            "kind": "expected_start",
+            "expected": starts(expected),
+            "got": got.to_string(),
+        }),
+        ParserError::ExpectedEnd { got, expected } => serde_json::json!({
+            "kind": "expected_end",
+            "expected": ends(expected),
+            "got": got.to_string(),
+        }),
+        ParserError::ExpectedStartOrEnd {
+            got,
+            expected_starts,
+            expected_ends,
+        } => serde_json::json!({
+            "kind": "expected_start_or_end",
+            "expected": ([starts(expected_starts), ends(expected_ends)].concat()),
+            "got": got.to_string(),
+        }),
+        ParserError::UnexpectedValue(value) => serde_json::json!({
+            "kind": "unexpected_value",
+            "value": value,
+        }),
+        ParserError::FailedToParseAttribute => serde_json::json!({ "kind": "invalid_attribute" }),
+        ParserError::InvalidValueForAttribute { name, value } => serde_json::json!({
+            "kind": "invalid_attribute_value",
+            "attribute": name,
+            "value": value,
+        }),
+        ParserError::MissingRequiredAttribute(name) => serde_json::json!({
+            "kind": "missing_attribute",
+            "attribute": name,
+        }),
+        ParserError::UnexpectedEof => serde_json::json!({ "kind": "unexpected_eof" }),
+        ParserError::EventAfterEnd => serde_json::json!({ "kind": "content_after_end" }),
+        ParserError::Xml(e) => serde_json::json!({
+            "kind": "xml",
+            "message": e.to_string(),
+        }),
+        ParserError::Cancelled => serde_json::json!({ "kind": "cancelled" }),
+    }
+}
+
+fn rate_name(rate: Rate) -> &'static str {
+    match rate {
+        Rate::Line => "line",
+        Rate::Branch => "branch",
+    }
+}
+
+fn violations_json(violations: &[PolicyViolation]) -> serde_json::Value {
+    violations
+        .iter()
+        .map(|v| {
+            serde_json::json!({
+                "target": v.target,
+                "rate": rate_name(v.rate),
+                "required": v.required,
+                "actual": v.actual,
+                "message": v.to_string(),
+            })
+        })
+        .collect()
+}
+
+// Run the check, adding its findings to `results` as they come in.
+fn run_check(
+    args: &CheckArgs,
+    results: &mut serde_json::Map<String, serde_json::Value>,
+) -> std::io::Result<()> {
+    let coverage = load_inputs_without_generated(&args.input)?;
+    let mut failures = Vec::new();
+
+    let report = coverage.verify(args.tolerance);
+    for discrepancy in &report.discrepancies {
+        eprintln!("{discrepancy}");
+    }
+    results.insert(
+        "discrepancies".into(),
+        report
+            .discrepancies
+            .iter()
+            .map(|d| {
+                serde_json::json!({
+                    "rate": rate_name(d.rate),
+                    "declared": d.declared,
+                    "computed": d.computed,
+                    "message": d.to_string(),
+                })
+            })
+            .collect(),
+    );
+    if !report.is_ok() {
+        failures.push("Reported coverage rates do not match calculated rates.".to_string());
+    }
+
+    if let Some(policy) = &args.policy {
+        let checker = PolicyChecker::new(Policy::load(policy)?);
+        let violations = checker.check(&coverage);
+
+        for violation in &violations {
+            eprintln!("{violation}");
+        }
+        results.insert("violations".into(), violations_json(&violations));
+
+        if !violations.is_empty() {
+            failures.push(format!(
+                "Coverage policy {} has {} violation(s).",
+                policy.display(),
+                violations.len()
+            ));
+        }
+
+        if let Some(rule) = &checker.policy().new_files {
+            let base = args
+                .base
+                .as_deref()
+                .or(rule.base.as_deref())
+                .ok_or_else(|| {
+                    std::io::Error::other(
+                        "The policy has `[new-files]` thresholds, but no base revision.",
+                    )
+                })?;
+
+            let new_files = added_files(&args.repo, base)?;
+            let violations = checker.check_new_files(&coverage, &new_files);
+
+            println!("{} file(s) added since {base}.", new_files.len());
+            for violation in &violations {
+                eprintln!("{violation}");
+            }
+            results.insert("new_files".into(), new_files.len().into());
+            results.insert("new_file_violations".into(), violations_json(&violations));
+
+            if !violations.is_empty() {
+                failures.push(format!(
+                    "{} new file(s) are below the required coverage.",
+                    violations.len()
+                ));
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        return Err(Failure::threshold(failures.join(" ")));
+    }
+
+    println!("Check OK");
+    Ok(())
+}
+
+fn load_file(file: &Path, args: &LoadArgs) -> std::io::Result<Coverage> {
+    let mut coverage = parse_file(file, args)?;
+
+    if let Some(filter) = args.filter()? {
+        let removed = coverage.apply_filter(&filter);
+        log::debug!("Ignored {removed} class(es) of {}", file.display());
+    }
+
+    Ok(coverage)
+}
+
+fn parse_file(file: &Path, args: &LoadArgs) -> std::io::Result<Coverage> {
+    let mut reader = BufReader::new(File::open(file)?);
+    let format = InputFormat::detect(&mut reader)?;
+    log::info!("Reading {}", file.display());
+
+    let json_error = |kind: &str, e: serde_json::Error| {
+        Failure::parse(format!(
+            "Failed to parse {kind} coverage file {}: {e}",
+            file.display()
+        ))
+    };
+
+    match format {
+        Some(InputFormat::Istanbul) => {
+            return IstanbulParser::parse(reader).map_err(|e| json_error("Istanbul", e))
+        }
+        Some(InputFormat::LlvmCov) => {
+            return LlvmCovParser::parse(reader).map_err(|e| json_error("llvm-cov", e))
+        }
+        Some(InputFormat::Snapshot) => {
+            return Coverage::load_snapshot(reader).map_err(|e| {
+                Failure::parse(format!(
+                    "Failed to load coverage snapshot {}: {e}",
+                    file.display()
+                ))
+            })
+        }
+        Some(InputFormat::Cobertura) | None => {}
+    }
+
+    if args.validate {
+        validate_file(file)?;
+    }
+
+    let mut state = Parser::with_profile(args.profile);
+    state.set_skip_unknown(args.skip_unknown);
+
+    let coverage = state
+        .parse_path(file)
+        .map_err(|e| Failure::parser(file, e))?;
+
+    for warning in &state.report().warnings {
+        log::warn!("{}: {warning}", file.display());
+    }
+
+    Ok(coverage)
+}
+
+fn load_tests(manifest: &Path, args: &InputArgs) -> std::io::Result<TestAttribution> {
+    let contents = std::fs::read_to_string(manifest)?;
+    let entries = TestAttribution::parse_manifest(&contents).map_err(|line| {
+        std::io::Error::other(format!(
+            "Malformed test manifest {} at line {line}.",
+            manifest.display()
+        ))
+    })?;
+
+    // Paths in the manifest are relative to the manifest itself.
+    let base = manifest.parent().unwrap_or(Path::new(""));
+
+    let mut tests = TestAttribution::new();
+    for (file, test) in entries {
+        let mut coverage = load_file(&base.join(file), &args.load)?;
+        coverage.min_hits = coverage.min_hits.max(args.min_hits);
+        tests.add_test(test, &coverage);
+    }
+
+    Ok(tests)
+}
+
+fn validate_file(file: &Path) -> std::io::Result<()> {
+    let mut reader = Reader::from_file(file).map_err(std::io::Error::other)?;
+    let violations = DtdValidator::validate(&mut reader);
+
+    for violation in &violations {
+        log::warn!("{}: {violation}", file.display());
+    }
+
+    if !violations.is_empty() {
+        let err = Failure::parse(format!(
+            "Coverage file {} has {} DTD violation(s).",
+            file.display(),
+            violations.len()
+        ));
+        return Err(err);
+    }
 
     Ok(())
 }
@@ -0,0 +1,397 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use crate::{Class, Condition, Coverage, Line, Method, Package};
+
+/// How to combine the hits of matching lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HitsStrategy {
+    /// Add up the hits, e.g. for reports of different test shards.
+    #[default]
+    Sum,
+    /// Take the highest hits, e.g. for reports of retried test runs.
+    Max,
+    /// Only record whether the line was hit at all, as 1 or 0 hits.
+    Or,
+}
+
+/// How to combine the branch coverage of matching lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BranchStrategy {
+    /// Take the better covered of the two.
+    #[default]
+    Best,
+    /// Add up the covered branches, up to the number of branches. As reports
+    /// don't say which branches were covered, this assumes that different
+    /// runs covered different branches.
+    Sum,
+}
+
+/// How to combine matching lines when merging reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MergeOptions {
+    pub hits: HitsStrategy,
+    pub branches: BranchStrategy,
+}
+
+impl Coverage {
+    /// Merge `other` into this coverage report.
+    ///
+    /// Packages are matched by project and name, classes by name and file
+    /// name, methods by name and signature and lines by line number. Hits of
+    /// matching lines are summed. Unknown attributes of both reports are
+    /// kept, preferring the values of this report. All rates are recomputed
+    /// afterwards.
+    pub fn merge(&mut self, other: Coverage) {
+        self.merge_with(other, &MergeOptions::default());
+    }
+
+    /// Merge `other` into this coverage report like [`Coverage::merge`],
+    /// combining matching lines according to `options`.
+    pub fn merge_with(&mut self, other: Coverage, options: &MergeOptions) {
+        for source in other.sources {
+            if !self.sources.contains(&source) {
+                self.sources.push(source);
+            }
+        }
+
+        merge_by_key(
+            &mut self.packages,
+            other.packages,
+            |p| (p.project.clone(), p.name.clone()),
+            |into, from| merge_package(into, from, options),
+        );
+
+        merge_attributes(&mut self.extra_attributes, other.extra_attributes);
+        self.timestamp = self.timestamp.max(other.timestamp);
+        self.sort();
+        if options.hits == HitsStrategy::Or {
+            // Lines that are only in one of the reports were not combined
+            // above, and still have their own hits.
+            self.combine_lines(|_, line| line.hits = line.hits.min(1));
+        }
+        self.recompute_rates();
+    }
+
+    /// Merge `other` into this coverage report, tagging all of its packages
+    /// as belonging to `project`.
+    pub fn merge_project(&mut self, project: impl Into<String>, other: Coverage) {
+        self.merge_project_with(project, other, &MergeOptions::default());
+    }
+
+    /// Merge `other` into this coverage report like
+    /// [`Coverage::merge_project`], combining matching lines according to
+    /// `options`.
+    pub fn merge_project_with(
+        &mut self,
+        project: impl Into<String>,
+        mut other: Coverage,
+        options: &MergeOptions,
+    ) {
+        let project = project.into();
+        for package in &mut other.packages {
+            package.project = Some(project.clone());
+        }
+
+        self.merge_with(other, options);
+    }
+
+    /// The distinct projects of all packages, in order of first appearance.
+    /// Packages without a project are represented by `None`.
+    pub fn projects(&self) -> Vec<Option<&str>> {
+        let mut projects = Vec::new();
+        for package in &self.packages {
+            let project = package.project.as_deref();
+            if !projects.contains(&project) {
+                projects.push(project);
+            }
+        }
+        projects
+    }
+
+    /// Merge classes that share a file name, e.g. partial classes or generic
+    /// specializations that some tools emit as separate `<class>` entries.
+    ///
+    /// The merged class is kept in the package of the first entry for that
+    /// file. Lines are matched by line number, keeping
+    /// the maximum hit count, and methods are unioned. All rates are
+    /// recomputed afterwards.
+    pub fn consolidate(&mut self) {
+        let mut first_by_file: HashMap<PathBuf, (usize, usize)> = HashMap::new();
+        let mut emptied = vec![false; self.packages.len()];
+
+        for (package_idx, emptied) in emptied.iter_mut().enumerate() {
+            let classes = std::mem::take(&mut self.packages[package_idx].classes);
+            let had_classes = !classes.is_empty();
+
+            for class in classes {
+                if let Some((p, c)) = first_by_file.get(&*class.file_name) {
+                    merge_class(&mut self.packages[*p].classes[*c], class, &CONSOLIDATE);
+                } else {
+                    let package = &mut self.packages[package_idx];
+                    first_by_file.insert(
+                        class.file_name.to_path_buf(),
+                        (package_idx, package.classes.len()),
+                    );
+                    package.classes.push(class);
+                }
+            }
+
+            *emptied = had_classes && self.packages[package_idx].classes.is_empty();
+        }
+
+        // Packages whose classes were all merged into other packages are
+        // removed only now, so that the indices above stay valid.
+        let mut emptied = emptied.into_iter();
+        self.packages.retain(|_| !emptied.next().unwrap_or(false));
+
+        self.sort();
+        self.recompute_rates();
+    }
+
+    /// Keep only the coverage that is also in `other`: the hits of each line
+    /// become the minimum of both reports, and lines that are not in `other`
+    /// become uncovered. Lines are matched by file name and line number, so
+    /// reports with different class names can be compared.
+    ///
+    /// Branch counts can't be matched up per branch, so the covered branch
+    /// count becomes the minimum of both. All rates are recomputed
+    /// afterwards.
+    pub fn intersect(&mut self, other: &Coverage) {
+        let other = line_hits_by_file(other);
+
+        self.combine_lines(|file, line| {
+            let (hits, branches) = other.get(&(file, line.number)).copied().unwrap_or((0, 0));
+            line.hits = line.hits.min(hits);
+            if let Some((covered, total)) = line.branch_counts() {
+                line.set_branch_counts(covered.min(branches), total);
+            }
+        });
+    }
+
+    /// Keep only the coverage that is not in `other`: lines that are covered
+    /// in `other`, by its [`Coverage::min_hits`], become uncovered. Lines are
+    /// matched by file name and line number.
+    ///
+    /// The covered branch count of a line is reduced by the covered branch
+    /// count in `other`, which is exact only if the same branches were
+    /// taken. All rates are recomputed afterwards.
+    pub fn subtract(&mut self, other: &Coverage) {
+        let min_hits = other.min_hits;
+        let other = line_hits_by_file(other);
+
+        self.combine_lines(|file, line| {
+            let (hits, branches) = other.get(&(file, line.number)).copied().unwrap_or((0, 0));
+            if Line::new(line.number, hits).is_covered(min_hits) {
+                line.hits = 0;
+            }
+            if let Some((covered, total)) = line.branch_counts() {
+                line.set_branch_counts(covered.saturating_sub(branches), total);
+            }
+        });
+    }
+
+    /// Limit the hits of every line to `max`, for profilers that report
+    /// absurd counts. Returns the number of lines that were capped.
+    pub fn cap_hits(&mut self, max: u64) -> usize {
+        let mut capped = 0;
+
+        self.combine_lines(|_, line| {
+            if line.hits > max {
+                line.hits = max;
+                capped += 1;
+            }
+        });
+
+        capped
+    }
+
+    /// Only count lines as covered if they were hit at least `min_hits`
+    /// times, by setting [`Coverage::min_hits`] and recomputing all rates.
+    /// The hits of the lines are kept, so that the report can still be
+    /// merged with others. Returns the number of lines hit fewer times.
+    pub fn apply_min_hits(&mut self, min_hits: u64) -> usize {
+        self.min_hits = min_hits;
+        self.recompute_rates();
+
+        self.classes()
+            .flat_map(|c| {
+                c.lines
+                    .iter()
+                    .chain(c.methods.iter().flat_map(|m| &m.lines))
+            })
+            .filter(|l| l.hits > 0 && !l.is_covered(min_hits))
+            .count()
+    }
+
+    fn combine_lines(&mut self, mut combine: impl FnMut(&Path, &mut Line)) {
+        for class in self.packages.iter_mut().flat_map(|p| &mut p.classes) {
+            let file = &*class.file_name;
+
+            for line in &mut class.lines {
+                combine(file, line);
+            }
+
+            for line in class.methods.iter_mut().flat_map(|m| &mut m.lines) {
+                combine(file, line);
+            }
+        }
+
+        self.recompute_rates();
+    }
+
+    /// All packages belonging to `project`.
+    pub fn packages_in_project<'a>(
+        &'a self,
+        project: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a Package> + 'a {
+        self.packages
+            .iter()
+            .filter(move |p| p.project.as_deref() == project)
+    }
+}
+
+// The highest hits and covered branch count of every line, by file name and
+// line number.
+fn line_hits_by_file(coverage: &Coverage) -> HashMap<(&Path, usize), (u64, usize)> {
+    let mut lines: HashMap<(&Path, usize), (u64, usize)> = HashMap::new();
+
+    for class in coverage.classes() {
+        for line in &class.lines {
+            let branches = line.branch_counts().map(|(c, _)| c).unwrap_or(0);
+            let entry = lines.entry((&*class.file_name, line.number)).or_default();
+            entry.0 = entry.0.max(line.hits);
+            entry.1 = entry.1.max(branches);
+        }
+    }
+
+    lines
+}
+
+fn merge_by_key<T, K>(
+    into: &mut Vec<T>,
+    from: Vec<T>,
+    key: impl Fn(&T) -> K,
+    merge: impl Fn(&mut T, T),
+) where
+    K: std::hash::Hash + Eq,
+{
+    let mut positions: HashMap<K, usize> = into
+        .iter()
+        .enumerate()
+        .map(|(idx, v)| (key(v), idx))
+        .collect();
+
+    for value in from {
+        let k = key(&value);
+        if let Some(idx) = positions.get(&k) {
+            merge(&mut into[*idx], value);
+        } else {
+            positions.insert(k, into.len());
+            into.push(value);
+        }
+    }
+}
+
+// Entries of the same class in one report describe the same run.
+const CONSOLIDATE: MergeOptions = MergeOptions {
+    hits: HitsStrategy::Max,
+    branches: BranchStrategy::Best,
+};
+
+fn merge_package(into: &mut Package, from: Package, options: &MergeOptions) {
+    merge_by_key(
+        &mut into.classes,
+        from.classes,
+        |c| (c.name.clone(), c.file_name.clone()),
+        |into, from| merge_class(into, from, options),
+    );
+    into.complexity = into.complexity.max(from.complexity);
+    merge_attributes(&mut into.extra_attributes, from.extra_attributes);
+}
+
+fn merge_class(into: &mut Class, from: Class, options: &MergeOptions) {
+    merge_by_key(
+        &mut into.methods,
+        from.methods,
+        |m| (m.name.clone(), m.signature.clone()),
+        |into, from| merge_method(into, from, options),
+    );
+    merge_lines(&mut into.lines, from.lines, options);
+    into.complexity = into.complexity.max(from.complexity);
+    merge_attributes(&mut into.extra_attributes, from.extra_attributes);
+}
+
+fn merge_method(into: &mut Method, from: Method, options: &MergeOptions) {
+    merge_lines(&mut into.lines, from.lines, options);
+    into.complexity = into.complexity.max(from.complexity);
+    merge_attributes(&mut into.extra_attributes, from.extra_attributes);
+}
+
+fn merge_lines(into: &mut Vec<Line>, from: Vec<Line>, options: &MergeOptions) {
+    merge_by_key(
+        into,
+        from,
+        |l| l.number,
+        |into, from| merge_line(into, from, options),
+    );
+}
+
+/// Combine two reports of the same line, like [`Coverage::consolidate`].
+pub(crate) fn consolidate_line(into: &mut Line, from: Line) {
+    merge_line(into, from, &CONSOLIDATE);
+}
+
+fn merge_line(into: &mut Line, from: Line, options: &MergeOptions) {
+    into.hits = match options.hits {
+        HitsStrategy::Sum => into.hits.saturating_add(from.hits),
+        HitsStrategy::Max => into.hits.max(from.hits),
+        HitsStrategy::Or => u64::from(into.hits > 0 || from.hits > 0),
+    };
+    into.branch |= from.branch;
+
+    match (into.branch_counts(), from.branch_counts(), options.branches) {
+        (Some((a, total)), Some((b, _)), BranchStrategy::Sum) => {
+            into.set_branch_counts((a + b).min(total), total)
+        }
+        // We can't tell which branch sides were taken in which report, so the
+        // best we can do is take the better of the two.
+        (Some((a, _)), Some((b, _)), _) if b > a => {
+            into.condition_coverage = from.condition_coverage
+        }
+        (None, Some(_), _) => into.condition_coverage = from.condition_coverage,
+        _ => {}
+    }
+
+    merge_by_key(
+        &mut into.conditions,
+        from.conditions,
+        |c| c.number,
+        merge_condition,
+    );
+    merge_attributes(&mut into.extra_attributes, from.extra_attributes);
+}
+
+fn merge_condition(into: &mut Condition, from: Condition) {
+    let percentage = |c: &Condition| {
+        c.coverage
+            .trim()
+            .trim_end_matches('%')
+            .parse::<f64>()
+            .unwrap_or(0.0)
+    };
+
+    if percentage(&from) > percentage(into) {
+        into.coverage = from.coverage;
+    }
+    merge_attributes(&mut into.extra_attributes, from.extra_attributes);
+}
+
+// Add the attributes of `from` that `into` doesn't have.
+fn merge_attributes(into: &mut BTreeMap<String, String>, from: BTreeMap<String, String>) {
+    for (name, value) in from {
+        into.entry(name).or_insert(value);
+    }
+}
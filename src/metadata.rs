@@ -0,0 +1,35 @@
+/// Information about the build a report belongs to, to tell reports apart.
+/// Shown in the HTML report and the summaries, and kept in the history.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "json",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ReportMetadata {
+    pub title: Option<String>,
+    /// A free-form label, e.g. a version or the name of a test suite.
+    pub tag: Option<String>,
+    pub commit: Option<String>,
+    pub branch: Option<String>,
+    pub build_url: Option<String>,
+}
+
+impl ReportMetadata {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The fields other than the title that are set, as `(key, value)` pairs,
+    /// with keys `tag`, `commit`, `branch` and `build_url`.
+    pub fn details(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("tag", &self.tag),
+            ("commit", &self.commit),
+            ("branch", &self.branch),
+            ("build_url", &self.build_url),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value.as_deref()?)))
+    }
+}
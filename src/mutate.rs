@@ -0,0 +1,128 @@
+use std::collections::BTreeSet;
+
+use crate::{Class, Coverage, Line, Method};
+
+impl Class {
+    /// Remove the lines for which `predicate` returns `true`, from the class
+    /// and from its methods, and recompute the rates of the class with the
+    /// `min_hits` of its report (see [`Coverage::min_hits`]). Returns the
+    /// number of lines removed from the class.
+    ///
+    /// Prefer [`Coverage::remove_lines`], which also updates the rates of the
+    /// package and the report.
+    pub fn remove_lines(
+        &mut self,
+        min_hits: u64,
+        mut predicate: impl FnMut(&Line) -> bool,
+    ) -> usize {
+        let before = self.lines.len();
+        self.lines.retain(|l| !predicate(l));
+        for method in &mut self.methods {
+            method.lines.retain(|l| !predicate(l));
+        }
+
+        self.recompute_rates(min_hits);
+        before - self.lines.len()
+    }
+
+    /// Remove the methods for which `predicate` returns `true`, together with
+    /// their lines (unless a remaining method also has them), and recompute
+    /// the rates of the class with the `min_hits` of its report (see
+    /// [`Coverage::min_hits`]). Returns the number of methods removed.
+    ///
+    /// Prefer [`Coverage::remove_methods`], which also updates the rates of
+    /// the package and the report.
+    pub fn remove_methods(
+        &mut self,
+        min_hits: u64,
+        mut predicate: impl FnMut(&Method) -> bool,
+    ) -> usize {
+        let (removed, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.methods)
+            .into_iter()
+            .partition(|m| predicate(m));
+        self.methods = kept;
+
+        let kept_lines: BTreeSet<_> = self
+            .methods
+            .iter()
+            .flat_map(|m| m.lines.iter().map(|l| l.number))
+            .collect();
+        let removed_lines: BTreeSet<_> = removed
+            .iter()
+            .flat_map(|m| m.lines.iter().map(|l| l.number))
+            .filter(|number| !kept_lines.contains(number))
+            .collect();
+
+        self.lines.retain(|l| !removed_lines.contains(&l.number));
+        self.recompute_rates(min_hits);
+
+        removed.len()
+    }
+}
+
+impl Coverage {
+    /// Keep only the classes for which `predicate` returns `true`, and
+    /// recompute all rates. Packages that no longer have any classes are
+    /// removed. Returns the number of classes removed.
+    pub fn retain_classes(&mut self, mut predicate: impl FnMut(&Class) -> bool) -> usize {
+        let mut removed = 0;
+
+        self.packages.retain_mut(|package| {
+            let before = package.classes.len();
+            package.classes.retain(|c| predicate(c));
+            removed += before - package.classes.len();
+
+            before == 0 || !package.classes.is_empty()
+        });
+
+        self.recompute_rates();
+        removed
+    }
+
+    /// Remove the lines for which `predicate` returns `true` (see
+    /// [`Class::remove_lines`]), and recompute all rates. `predicate` is
+    /// called with the class of each line. Returns the number of lines
+    /// removed from classes.
+    pub fn remove_lines(&mut self, mut predicate: impl FnMut(&Class, &Line) -> bool) -> usize {
+        let mut removed = 0;
+
+        for class in self.packages.iter_mut().flat_map(|p| &mut p.classes) {
+            // The predicate can't borrow the class while its lines change.
+            let excluded: BTreeSet<_> = class
+                .lines
+                .iter()
+                .chain(class.methods.iter().flat_map(|m| &m.lines))
+                .filter(|l| predicate(class, l))
+                .map(|l| l.number)
+                .collect();
+
+            if !excluded.is_empty() {
+                removed += class.remove_lines(self.min_hits, |l| excluded.contains(&l.number));
+            }
+        }
+
+        self.recompute_rates();
+        removed
+    }
+
+    /// Remove the methods for which `predicate` returns `true` (see
+    /// [`Class::remove_methods`]), and recompute all rates. `predicate` is
+    /// called with the class of each method. Returns the number of methods
+    /// removed.
+    pub fn remove_methods(&mut self, mut predicate: impl FnMut(&Class, &Method) -> bool) -> usize {
+        let mut removed = 0;
+
+        for class in self.packages.iter_mut().flat_map(|p| &mut p.classes) {
+            let excluded: Vec<_> = class.methods.iter().map(|m| predicate(class, m)).collect();
+
+            if excluded.contains(&true) {
+                let mut excluded = excluded.into_iter();
+                removed +=
+                    class.remove_methods(self.min_hits, |_| excluded.next().unwrap_or(false));
+            }
+        }
+
+        self.recompute_rates();
+        removed
+    }
+}
@@ -0,0 +1,226 @@
+use std::io::{self, Write};
+
+use serde_json::{json, Value};
+
+use crate::{Coverage, HistoryEntry, ReportMetadata, RoundingPolicy, SummaryGenerator};
+
+/// What a chat notification about a report says, besides the coverage.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NotificationOptions {
+    /// The previous run, to show the change in coverage since then.
+    pub previous: Option<HistoryEntry>,
+    /// The title and build information of the report.
+    pub metadata: ReportMetadata,
+    /// Why the coverage doesn't meet its thresholds, or `None` if they
+    /// weren't checked. An empty list means that all thresholds are met.
+    pub threshold_failures: Option<Vec<String>>,
+    pub rounding: RoundingPolicy,
+}
+
+impl NotificationOptions {
+    fn title(&self) -> &str {
+        self.metadata.title.as_deref().unwrap_or("Coverage report")
+    }
+
+    // The formatted line and branch coverage, with their changes.
+    fn rates(&self, coverage: &Coverage) -> [(&'static str, String); 2] {
+        let previous = self.previous.as_ref();
+        [
+            (
+                "Line coverage",
+                SummaryGenerator::format_rate(
+                    coverage.line_rate,
+                    previous.map(|p| p.line_rate),
+                    &self.rounding,
+                ),
+            ),
+            (
+                "Branch coverage",
+                SummaryGenerator::format_rate(
+                    coverage.branch_rate,
+                    previous.map(|p| p.branch_rate),
+                    &self.rounding,
+                ),
+            ),
+        ]
+    }
+
+    // The label of each detail of the metadata.
+    fn details(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.metadata.details().map(|(key, value)| {
+            let label = match key {
+                "tag" => "Tag",
+                "commit" => "Commit",
+                "branch" => "Branch",
+                _ => "Build",
+            };
+            (label, value)
+        })
+    }
+}
+
+fn write_json(payload: &Value, mut out: impl Write) -> io::Result<()> {
+    serde_json::to_writer(&mut out, payload).map_err(io::Error::other)?;
+    writeln!(out)
+}
+
+/// Writes Slack messages, as posted to an incoming webhook.
+pub struct SlackGenerator;
+
+impl SlackGenerator {
+    // Escape the characters that Slack's `mrkdwn` gives a meaning.
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+
+    /// Write a message with the overall coverage of `coverage` as Block Kit
+    /// blocks, with a plain text fallback for notifications.
+    pub fn write(
+        coverage: &Coverage,
+        options: &NotificationOptions,
+        out: impl Write,
+    ) -> io::Result<()> {
+        let rates = options.rates(coverage);
+
+        let mut blocks = vec![
+            json!({
+                "type": "header",
+                "text": { "type": "plain_text", "text": options.title() },
+            }),
+            json!({
+                "type": "section",
+                "fields": rates
+                    .iter()
+                    .map(|(label, rate)| json!({
+                        "type": "mrkdwn",
+                        "text": format!("*{label}*\n{rate}"),
+                    }))
+                    .collect::<Vec<_>>(),
+            }),
+        ];
+
+        if let Some(failures) = &options.threshold_failures {
+            let text = if failures.is_empty() {
+                ":white_check_mark: All coverage thresholds are met.".to_string()
+            } else {
+                let reasons: Vec<_> = failures
+                    .iter()
+                    .map(|f| format!("• {}", Self::escape(f)))
+                    .collect();
+                format!(
+                    ":x: Coverage thresholds are not met:\n{}",
+                    reasons.join("\n")
+                )
+            };
+            blocks.push(json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": text },
+            }));
+        }
+
+        let details: Vec<_> = options
+            .details()
+            .map(|(label, value)| match label {
+                "Build" => format!("<{}|{label}>", Self::escape(value)),
+                _ => format!("{label}: `{}`", Self::escape(value)),
+            })
+            .collect();
+        if !details.is_empty() {
+            blocks.push(json!({
+                "type": "context",
+                "elements": [{ "type": "mrkdwn", "text": details.join(" · ") }],
+            }));
+        }
+
+        let text = format!(
+            "{}: line coverage {}, branch coverage {}",
+            options.title(),
+            rates[0].1,
+            rates[1].1
+        );
+        write_json(&json!({ "text": text, "blocks": blocks }), out)
+    }
+}
+
+/// Writes Microsoft Teams messages with an Adaptive Card, as posted to a
+/// workflow webhook.
+pub struct TeamsGenerator;
+
+impl TeamsGenerator {
+    /// Write a message with the overall coverage of `coverage`.
+    pub fn write(
+        coverage: &Coverage,
+        options: &NotificationOptions,
+        out: impl Write,
+    ) -> io::Result<()> {
+        let facts: Vec<_> = options
+            .rates(coverage)
+            .into_iter()
+            .map(|(title, value)| json!({ "title": title, "value": value }))
+            .collect();
+
+        let mut body = vec![
+            json!({
+                "type": "TextBlock",
+                "size": "Large",
+                "weight": "Bolder",
+                "text": options.title(),
+                "wrap": true,
+            }),
+            json!({ "type": "FactSet", "facts": facts }),
+        ];
+
+        if let Some(failures) = &options.threshold_failures {
+            let (text, color) = if failures.is_empty() {
+                ("All coverage thresholds are met.", "Good")
+            } else {
+                ("Coverage thresholds are not met:", "Attention")
+            };
+            body.push(json!({
+                "type": "TextBlock",
+                "text": text,
+                "color": color,
+                "weight": "Bolder",
+                "wrap": true,
+            }));
+            for failure in failures {
+                body.push(
+                    json!({ "type": "TextBlock", "text": format!("- {failure}"), "wrap": true }),
+                );
+            }
+        }
+
+        let details: Vec<_> = options
+            .details()
+            .filter(|(label, _)| *label != "Build")
+            .map(|(title, value)| json!({ "title": title, "value": value }))
+            .collect();
+        if !details.is_empty() {
+            body.push(json!({ "type": "FactSet", "facts": details }));
+        }
+
+        let actions: Vec<_> = options
+            .metadata
+            .build_url
+            .iter()
+            .map(|url| json!({ "type": "Action.OpenUrl", "title": "Build", "url": url }))
+            .collect();
+
+        let payload = json!({
+            "type": "message",
+            "attachments": [{
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "content": {
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "type": "AdaptiveCard",
+                    "version": "1.4",
+                    "body": body,
+                    "actions": actions,
+                },
+            }],
+        });
+        write_json(&payload, out)
+    }
+}
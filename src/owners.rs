@@ -0,0 +1,212 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{glob::glob_match, ComputedRates, Coverage, Line};
+
+#[derive(Debug, Clone, PartialEq)]
+struct OwnerRule {
+    pattern: String,
+    // Whether the pattern only matches directories (and so their contents).
+    directory: bool,
+    owners: Vec<String>,
+}
+
+impl OwnerRule {
+    fn matches(&self, path: &str) -> bool {
+        let contents = format!("{}/**", self.pattern);
+
+        // Paths in coverage reports are often absolute, or relative to some
+        // other directory than the repository root, so also match trailing
+        // parts of them.
+        std::iter::once(path)
+            .chain(path.match_indices('/').map(|(idx, _)| &path[idx + 1..]))
+            .any(|candidate| {
+                (!self.directory && glob_match(&self.pattern, candidate, '/'))
+                    || glob_match(&contents, candidate, '/')
+            })
+    }
+}
+
+/// The owners of files, as listed in a `CODEOWNERS` file:
+///
+/// ```text
+/// # Comments and empty lines are ignored.
+/// *               @org/everyone
+/// /src/parser/    @org/parsing
+/// *.js            @org/frontend alice@example.com
+/// ```
+///
+/// As in git, the last matching pattern determines the owners of a file. A
+/// pattern without owners leaves matching files without owners. Patterns
+/// also match any trailing part of a path, so that they work with absolute
+/// file names.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CodeOwners {
+    rules: Vec<OwnerRule>,
+}
+
+impl CodeOwners {
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .map(str::trim)
+            // GitLab section headers, e.g. `[Documentation]`.
+            .filter(|line| !line.is_empty() && !line.starts_with(['#', '[']))
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let pattern = parts.next()?;
+                let owners = parts
+                    .take_while(|p| !p.starts_with('#'))
+                    .map(String::from)
+                    .collect();
+
+                let directory = pattern.ends_with('/');
+                let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+
+                Some(OwnerRule {
+                    pattern: pattern.to_string(),
+                    directory,
+                    owners,
+                })
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// The owners of `path`, empty if it has none.
+    pub fn owners_of(&self, path: &Path) -> &[String] {
+        let path = path.to_string_lossy().replace('\\', "/");
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(&path))
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// The coverage of all files of one owner.
+#[derive(Debug, Clone)]
+pub struct OwnerSummary {
+    /// `None` for the files without owners.
+    pub owner: Option<String>,
+    pub files: usize,
+    pub rates: ComputedRates,
+}
+
+impl Coverage {
+    /// The coverage per owner, sorted by owner, followed by the files without
+    /// owners (if any). Files with several owners count for each of them.
+    pub fn by_owner(&self, owners: &CodeOwners) -> Vec<OwnerSummary> {
+        let mut by_owner: BTreeMap<Option<&str>, (usize, Vec<&Line>)> = BTreeMap::new();
+
+        let files = self.file_coverage();
+        for file in &files {
+            let file_owners = owners.owners_of(file.path);
+            let keys: Vec<_> = if file_owners.is_empty() {
+                vec![None]
+            } else {
+                file_owners.iter().map(|o| Some(o.as_str())).collect()
+            };
+
+            for key in keys {
+                let (files, lines) = by_owner.entry(key).or_default();
+                *files += 1;
+                lines.extend(file.lines.iter().map(AsRef::as_ref));
+            }
+        }
+
+        let mut summaries: Vec<_> = by_owner
+            .into_iter()
+            .map(|(owner, (files, lines))| OwnerSummary {
+                owner: owner.map(String::from),
+                files,
+                rates: ComputedRates::from_lines(lines, self.min_hits),
+            })
+            .collect();
+
+        // `None` sorts first, but is the least interesting.
+        if summaries.first().is_some_and(|s| s.owner.is_none()) {
+            summaries.rotate_left(1);
+        }
+
+        summaries
+    }
+}
+
+/// Lists the coverage of each owner.
+pub struct OwnershipGenerator;
+
+impl OwnershipGenerator {
+    /// Write one line per owner, e.g.
+    /// `@org/parsing: 12 file(s), line coverage 85.71%, branch coverage 50.00%`.
+    pub fn write_text(summaries: &[OwnerSummary], mut out: impl Write) -> io::Result<()> {
+        let format = |rate: Option<f64>| {
+            rate.map_or_else(|| "-".to_string(), |rate| format!("{:.2}%", rate * 100.0))
+        };
+
+        for summary in summaries {
+            writeln!(
+                out,
+                "{}: {} file(s), line coverage {}, branch coverage {}",
+                summary.owner.as_deref().unwrap_or("(no owner)"),
+                summary.files,
+                format(summary.rates.line_rate),
+                format(summary.rates.branch_rate)
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a JSON document of the form `{"owners": [{"owner": "@org/parsing",
+    /// "files": 12, "lines_valid": 80, "lines_covered": 70, "line_rate": 0.875,
+    /// ...}]}`, with a `null` owner for files without owners.
+    #[cfg(feature = "json")]
+    pub fn write_json(summaries: &[OwnerSummary], mut out: impl Write) -> io::Result<()> {
+        #[derive(serde::Serialize)]
+        struct Owner<'a> {
+            owner: Option<&'a str>,
+            files: usize,
+            lines_valid: usize,
+            lines_covered: usize,
+            branches_valid: usize,
+            branches_covered: usize,
+            line_rate: Option<f64>,
+            branch_rate: Option<f64>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Report<'a> {
+            owners: Vec<Owner<'a>>,
+        }
+
+        let report = Report {
+            owners: summaries
+                .iter()
+                .map(|s| Owner {
+                    owner: s.owner.as_deref(),
+                    files: s.files,
+                    lines_valid: s.rates.lines_valid,
+                    lines_covered: s.rates.lines_covered,
+                    branches_valid: s.rates.branches_valid,
+                    branches_covered: s.rates.branches_covered,
+                    line_rate: s.rates.line_rate,
+                    branch_rate: s.rates.branch_rate,
+                })
+                .collect(),
+        };
+
+        serde_json::to_writer_pretty(&mut out, &report).map_err(io::Error::other)?;
+        writeln!(out)
+    }
+}
@@ -1,11 +1,24 @@
-use std::{io::BufRead, path::PathBuf, task::Poll};
+use std::{
+    collections::{BTreeSet, HashSet},
+    io::BufRead,
+    num::IntErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+    task::Poll,
+};
 
 use quick_xml::{
+    encoding::Decoder,
     events::{BytesEnd, BytesStart, BytesText, Event},
     Reader,
 };
 
-use crate::{Class, Condition, Coverage, Line, Method, Package, ParserError, Source};
+use crate::sort::sort_lines;
+use crate::{
+    error::BasicEvent, Class, Condition, ConditionType, Coverage, CoverageVisitor, Interner, Line,
+    Method, Package, ParseOutcome, ParseReport, ParserError, Profile, Progress, ProgressUpdate,
+    Source, Warning,
+};
 
 #[derive(Debug)]
 pub enum FilteredEvent<'a> {
@@ -20,7 +33,7 @@ impl<'a> FilteredEvent<'a> {
         match event {
             Event::Eof => None,
             Event::Decl(_) => None,
-            Event::Text(text) if text.as_ref().trim_ascii().is_empty() => None,
+            Event::Text(text) if text.trim_ascii().is_empty() => None,
             Event::Text(text) => Some(FilteredEvent::Text(text)),
             Event::Start(start) => Some(FilteredEvent::Start(start)),
             Event::End(end) => Some(FilteredEvent::End(end)),
@@ -34,67 +47,411 @@ fn utf8_attr(input: impl AsRef<[u8]>) -> String {
     String::from_utf8_lossy(input.as_ref()).to_string()
 }
 
+// Counts that don't even fit in a u64 are clamped rather than rejected, as
+// some profilers emit absurd hit counts. Timestamps are clamped the same way,
+// and then ignored.
+fn parse_hits(value: &str) -> Option<u64> {
+    match value.parse::<u64>() {
+        Ok(hits) => Some(hits),
+        Err(e) if *e.kind() == IntErrorKind::PosOverflow => Some(u64::MAX),
+        Err(_) => None,
+    }
+}
+
+// Coverlet writes `True` and `False`.
+fn parse_bool(value: &str) -> Option<bool> {
+    if value.eq_ignore_ascii_case("true") {
+        Some(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+trait FromAttribute: Sized {
+    fn from_attribute(value: &str, interner: &mut Interner) -> Option<Self>;
+}
+
+macro_rules! from_str_attribute {
+    ($($ty:ty),*) => {
+        $(
+            impl FromAttribute for $ty {
+                fn from_attribute(value: &str, _: &mut Interner) -> Option<Self> {
+                    value.parse().ok()
+                }
+            }
+        )*
+    };
+}
+
+from_str_attribute!(f64, usize, bool, String, PathBuf);
+
+impl FromAttribute for ConditionType {
+    fn from_attribute(value: &str, _: &mut Interner) -> Option<Self> {
+        Some(ConditionType::parse(value))
+    }
+}
+
+impl FromAttribute for u64 {
+    fn from_attribute(value: &str, _: &mut Interner) -> Option<Self> {
+        parse_hits(value)
+    }
+}
+
+impl FromAttribute for Arc<str> {
+    fn from_attribute(value: &str, interner: &mut Interner) -> Option<Self> {
+        Some(interner.intern(value))
+    }
+}
+
+impl FromAttribute for Arc<Path> {
+    fn from_attribute(value: &str, interner: &mut Interner) -> Option<Self> {
+        Some(interner.intern_path(Path::new(value)))
+    }
+}
+
+impl<T: FromAttribute> FromAttribute for Option<T> {
+    fn from_attribute(value: &str, interner: &mut Interner) -> Option<Self> {
+        T::from_attribute(value, interner).map(Some)
+    }
+}
+
 macro_rules! set_attributes {
-    (field: $set_on:expr, $field:ident, $str_name:literal) => {
-        set_attributes!(field: $set_on, $field, $str_name, false)
+    (field: $ctx:expr, $element:literal, $set_on:expr, $field:ident, $str_name:literal) => {
+        set_attributes!(field: $ctx, $element, $set_on, $field, $str_name, false)
     };
 
-    (field: $set_on:expr, $field:ident, $str_name:literal, false) => {
+    (field: $ctx:expr, $element:literal, $set_on:expr, $field:ident, $str_name:literal, false) => {
         if let Some(value) = $field {
             $set_on.$field = value;
+        } else if $ctx.profile.is_optional($element, $str_name) {
+            $set_on.$field = Default::default();
         } else {
             return Err(ParserError::MissingRequiredAttribute(utf8_attr($str_name)));
         }
     };
 
-    (field: $set_on:expr, $field:ident, $str_name:literal, true) => {
+    // For fields without a default, that no profile can make optional.
+    (field: $ctx:expr, $element:literal, $set_on:expr, $field:ident, $str_name:literal, never) => {
+        match $field {
+            Some(value) => $set_on.$field = value,
+            None => return Err(ParserError::MissingRequiredAttribute(utf8_attr($str_name))),
+        }
+    };
+
+    (field: $ctx:expr, $element:literal, $set_on:expr, $field:ident, $str_name:literal, true) => {
         $set_on.$field = $field.unwrap_or_default();
     };
 
-    ($set_on:expr, $attributes:expr, $([$str_name:literal, $ty:ty, $field:ident$(, optional = $optional_value:tt)?],)*) => {{
+    ($set_on:expr, $element:literal, $attributes:expr, $ctx:expr, $([$str_name:literal, $ty:ty, $field:ident$(, optional = $optional_value:tt)?],)*) => {{
+        let ctx: &mut ParseContext = $ctx;
+
         $(
             let mut $field: Option<$ty> = None;
         )*
 
         for attribute in $attributes {
             let attribute = attribute.map_err(|_| ParserError::FailedToParseAttribute)?;
-            let name = attribute.key.as_ref();
-            let value = attribute.unescape_value().unwrap();
+            let name = attribute.key.local_name();
+            let name = name.as_ref();
+            let value = attribute.decode_and_unescape_value(ctx.decoder)?;
+            let mut known = false;
 
             $(
                 if name == $str_name {
-                    $field = Some(value.parse().map_err(|_| ParserError::InvalidValueForAttribute{ name: utf8_attr($str_name), value: value.to_string() })?);
+                    known = true;
+                    $field = Some(<$ty as FromAttribute>::from_attribute(&value, &mut ctx.interner).ok_or_else(|| ParserError::InvalidValueForAttribute{ name: utf8_attr($str_name), value: value.to_string() })?);
                 }
             )*
+
+            if !known {
+                ctx.unknown_attribute($element, attribute.key.as_ref());
+                $set_on.extra_attributes.insert(utf8_attr(attribute.key), value.to_string());
+            }
         }
 
         $(
-            set_attributes!(field: $set_on, $field, $str_name$(, $optional_value)?);
+            set_attributes!(field: ctx, $element, $set_on, $field, $str_name$(, $optional_value)?);
         )*
     }};
 }
 
+/// State shared by all parts of the parser, that lives across documents.
+#[derive(Debug)]
+pub(crate) struct ParseContext {
+    // Kept across documents, so that names shared between parsed reports
+    // (e.g. when merging) are also only stored once.
+    interner: Interner,
+    profile: Profile,
+    // Decodes attribute values in the encoding declared by the document.
+    decoder: Decoder,
+    // Moved to the report of the parser after every event.
+    warnings: Vec<Warning>,
+}
+
+impl Default for ParseContext {
+    fn default() -> Self {
+        Self {
+            interner: Interner::new(),
+            profile: Profile::default(),
+            decoder: utf8_decoder(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+// quick-xml has no public constructor for its decoders, but readers start out
+// decoding UTF-8.
+fn utf8_decoder() -> Decoder {
+    Reader::from_str("").decoder()
+}
+
+impl ParseContext {
+    fn warn(&mut self, warning: Warning) {
+        log::debug!("{warning}");
+        self.warnings.push(warning);
+    }
+
+    fn unknown_attribute(&mut self, element: &[u8], attribute: &[u8]) {
+        if self.profile.is_extension(element, attribute) {
+            log::debug!(
+                "Keeping attribute `{}` on <{}>",
+                utf8_attr(attribute),
+                utf8_attr(element)
+            );
+        } else {
+            self.warn(Warning::UnknownAttribute {
+                element: utf8_attr(element),
+                attribute: utf8_attr(attribute),
+            });
+        }
+    }
+
+    fn normalize_rates(&mut self, element: &str, line_rate: &mut f64, branch_rate: &mut f64) {
+        for (attribute, rate) in [("line-rate", line_rate), ("branch-rate", branch_rate)] {
+            if let Some(read) = self.profile.normalize_rate(rate) {
+                self.warn(Warning::RateNormalized {
+                    element: element.to_string(),
+                    attribute: attribute.to_string(),
+                    read,
+                    value: *rate,
+                });
+            }
+
+            if !(0.0..=1.0).contains(rate) {
+                self.warn(Warning::RateOutOfRange {
+                    element: element.to_string(),
+                    attribute: attribute.to_string(),
+                    value: *rate,
+                });
+            }
+        }
+    }
+}
+
 pub struct Parser {
     inner: Option<ParserInner>,
+    context: ParseContext,
+    skip_unknown: bool,
+    // The depth within an element that is being skipped, if any.
+    skip_depth: usize,
+    report: ParseReport,
+    progress: Progress,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Parser {
     pub fn new() -> Self {
-        Self { inner: None }
+        Self::with_profile(Profile::Generic)
+    }
+
+    pub fn with_profile(profile: Profile) -> Self {
+        Self {
+            inner: None,
+            context: ParseContext {
+                profile,
+                ..Default::default()
+            },
+            skip_unknown: false,
+            skip_depth: 0,
+            report: ParseReport::default(),
+            progress: Progress::default(),
+        }
+    }
+
+    pub fn profile(&self) -> Profile {
+        self.context.profile
+    }
+
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.context.profile = profile;
+    }
+
+    pub fn skip_unknown(&self) -> bool {
+        self.skip_unknown
+    }
+
+    /// Skip elements (and everything inside them) and text that are not part
+    /// of the Cobertura format instead of failing, recording a [`Warning`]
+    /// for each in the [`report`](Self::report).
+    pub fn set_skip_unknown(&mut self, skip_unknown: bool) {
+        self.skip_unknown = skip_unknown;
+    }
+
+    /// Decode attribute values with `decoder`. Only needed for documents in
+    /// another encoding than UTF-8 that are fed to
+    /// [`consume_event`](Self::consume_event), pass the
+    /// [`decoder`](Reader::decoder) of their reader after its declaration
+    /// was read. The other parse functions do this themselves.
+    pub fn set_decoder(&mut self, decoder: Decoder) {
+        self.context.decoder = decoder;
+    }
+
+    /// Report the classes parsed and the bytes read while parsing, and stop
+    /// with [`ParserError::Cancelled`] once cancelled. Not used by
+    /// [`consume_event`](Self::consume_event).
+    pub fn set_progress(&mut self, progress: Progress) {
+        self.progress = progress;
+    }
+
+    /// Like [`parse_reader`](Self::parse_reader), also returning the
+    /// warnings of the document (which are taken out of the
+    /// [`report`](Self::report)).
+    pub fn parse_outcome(&mut self, reader: impl BufRead) -> Result<ParseOutcome, ParserError> {
+        let coverage = self.parse_reader(reader)?;
+        Ok(ParseOutcome {
+            coverage,
+            warnings: self.take_report().warnings,
+        })
+    }
+
+    /// The report of the document that was parsed last, or that is being
+    /// parsed.
+    pub fn report(&self) -> &ParseReport {
+        &self.report
+    }
+
+    pub fn take_report(&mut self) -> ParseReport {
+        std::mem::take(&mut self.report)
+    }
+
+    // Drop the state of a document that ended with an error, so that the
+    // next one starts from scratch. Unlike `reset`, the report is kept.
+    fn abort(&mut self) {
+        self.inner.take();
+        self.skip_depth = 0;
     }
 
     pub fn reset(&mut self) {
         self.inner.take();
+        self.skip_depth = 0;
+        self.report = ParseReport::default();
+        self.context.warnings.clear();
+        self.context.interner.clear();
+    }
+
+    pub fn parse_str(&mut self, input: &str) -> Result<Coverage, ParserError> {
+        let total_bytes = input.len() as u64;
+        self.parse_events(&mut Reader::from_str(input), None, Some(total_bytes))
+    }
+
+    pub fn parse_bytes(&mut self, input: &[u8]) -> Result<Coverage, ParserError> {
+        let total_bytes = input.len() as u64;
+        self.parse_events(&mut Reader::from_reader(input), None, Some(total_bytes))
+    }
+
+    /// Like [`parse_bytes`](Self::parse_bytes), replacing invalid UTF-8 in
+    /// `input` with U+FFFD instead of failing on it. Only for input that
+    /// should be UTF-8, as any other encoding declared by the document is
+    /// ignored.
+    pub fn parse_bytes_lossy(&mut self, input: &[u8]) -> Result<Coverage, ParserError> {
+        self.parse_str(&String::from_utf8_lossy(input))
+    }
+
+    pub fn parse_reader(&mut self, reader: impl BufRead) -> Result<Coverage, ParserError> {
+        self.parse(&mut Reader::from_reader(reader))
+    }
+
+    pub fn parse_path(&mut self, path: impl AsRef<Path>) -> Result<Coverage, ParserError> {
+        log::debug!("Parsing {}", path.as_ref().display());
+        let total_bytes = std::fs::metadata(&path).ok().map(|m| m.len());
+        self.parse_events(&mut Reader::from_file(path)?, None, total_bytes)
     }
 
     pub fn parse<R>(&mut self, reader: &mut Reader<R>) -> Result<Coverage, ParserError>
     where
         R: BufRead,
     {
+        self.parse_events(reader, None, None)
+    }
+
+    /// Parse a document, handing every class to `visitor` instead of keeping
+    /// it in the model.
+    ///
+    /// The returned [`Coverage`] only holds the attributes and sources of the
+    /// report, its `packages` are empty.
+    pub fn parse_with_visitor<R>(
+        &mut self,
+        reader: &mut Reader<R>,
+        visitor: &mut dyn CoverageVisitor,
+    ) -> Result<Coverage, ParserError>
+    where
+        R: BufRead,
+    {
+        self.parse_events(reader, Some(visitor), None)
+    }
+
+    /// Like [`parse_with_visitor`](Self::parse_with_visitor), reading from
+    /// any buffered reader.
+    pub fn parse_reader_with_visitor(
+        &mut self,
+        reader: impl BufRead,
+        visitor: &mut dyn CoverageVisitor,
+    ) -> Result<Coverage, ParserError> {
+        self.parse_events(&mut Reader::from_reader(reader), Some(visitor), None)
+    }
+
+    fn parse_events<R>(
+        &mut self,
+        reader: &mut Reader<R>,
+        mut visitor: Option<&mut dyn CoverageVisitor>,
+        total_bytes: Option<u64>,
+    ) -> Result<Coverage, ParserError>
+    where
+        R: BufRead,
+    {
+        let mut progress = ProgressUpdate {
+            total_bytes,
+            ..Default::default()
+        };
+
         let mut buf = Vec::new();
         loop {
-            let event = reader.read_event_into(&mut buf).unwrap();
+            if self.progress.is_cancelled() {
+                self.abort();
+                return Err(ParserError::Cancelled);
+            }
+
+            buf.clear();
+            let event = match reader.read_event_into(&mut buf) {
+                Ok(event) => event,
+                Err(e) => {
+                    self.abort();
+                    return Err(e.into());
+                }
+            };
+
+            // The decoder changes when the declaration is read.
+            self.context.decoder = reader.decoder();
+
             if event == Event::Eof {
+                self.abort();
                 return Err(ParserError::UnexpectedEof);
             }
 
@@ -104,24 +461,193 @@ impl Parser {
                 continue;
             };
 
-            if let Poll::Ready(result) = self.consume_event(&filtered) {
+            let class_ended = matches!(&filtered, FilteredEvent::End(end) if end.local_name().as_ref() == b"class");
+
+            let visitor = visitor
+                .as_mut()
+                .map(|v| &mut **v as &mut dyn CoverageVisitor);
+            if let Poll::Ready(mut result) = self.consume(&filtered, visitor) {
+                if let Ok(coverage) = &mut result {
+                    coverage.sort();
+                    log::debug!(
+                        "Parsed {} package(s) with {} class(es)",
+                        coverage.packages.len(),
+                        coverage
+                            .packages
+                            .iter()
+                            .map(|p| p.classes.len())
+                            .sum::<usize>()
+                    );
+                }
+                progress.bytes = reader.buffer_position();
+                self.progress.report(&progress);
                 break result;
             }
+
+            if class_ended {
+                progress.items += 1;
+                progress.bytes = reader.buffer_position();
+                self.progress.report(&progress);
+            }
+        }
+    }
+
+    /// Parse a single `<package>` element, e.g. of a report sharded into one
+    /// file per package, or to splice it into another report.
+    ///
+    /// `reader` must be positioned before the start of the element, and is
+    /// left right after its end. Unlike for whole documents, unknown
+    /// elements are never skipped.
+    pub fn parse_package<R>(&mut self, reader: &mut Reader<R>) -> Result<Package, ParserError>
+    where
+        R: BufRead,
+    {
+        let inner = self.parse_fragment(reader, "package", State::ParsingPackages)?;
+        let mut package = inner
+            .coverage
+            .packages
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        package.sort();
+        Ok(package)
+    }
+
+    /// Parse a single `<class>` element, like
+    /// [`parse_package`](Self::parse_package).
+    pub fn parse_class<R>(&mut self, reader: &mut Reader<R>) -> Result<Class, ParserError>
+    where
+        R: BufRead,
+    {
+        let inner = self.parse_fragment(reader, "class", State::ParsingClasses)?;
+        let mut class = inner.package.classes.into_iter().next().unwrap_or_default();
+        class.sort();
+        Ok(class)
+    }
+
+    /// Parse a single `<line>` element, like
+    /// [`parse_package`](Self::parse_package).
+    pub fn parse_line<R>(&mut self, reader: &mut Reader<R>) -> Result<Line, ParserError>
+    where
+        R: BufRead,
+    {
+        let inner = self.parse_fragment(reader, "line", State::ParsingClassLines)?;
+        let mut lines = inner.class.lines;
+        sort_lines(&mut lines);
+        Ok(lines.into_iter().next().unwrap_or_default())
+    }
+
+    // Parse one `element`, starting in `outer`, the state in which it can
+    // start, until the element ended and the parser is back in that state.
+    fn parse_fragment<R>(
+        &mut self,
+        reader: &mut Reader<R>,
+        element: &'static str,
+        outer: State,
+    ) -> Result<ParserInner, ParserError>
+    where
+        R: BufRead,
+    {
+        let mut inner = ParserInner {
+            coverage: Default::default(),
+            state: outer,
+            package: Default::default(),
+            class: Default::default(),
+            method: Default::default(),
+            line: Default::default(),
+        };
+
+        let mut buf = Vec::new();
+        let mut started = false;
+        loop {
+            if self.progress.is_cancelled() {
+                return Err(ParserError::Cancelled);
+            }
+
+            buf.clear();
+            let event = reader.read_event_into(&mut buf)?;
+            self.context.decoder = reader.decoder();
+
+            if event == Event::Eof {
+                return Err(ParserError::UnexpectedEof);
+            }
+
+            let Some(event) = FilteredEvent::try_from(event) else {
+                continue;
+            };
+
+            if !started {
+                match &event {
+                    FilteredEvent::Start(start) | FilteredEvent::AttributesOnly(start)
+                        if start.local_name().as_ref() == element.as_bytes() => {}
+                    evt => return Err(ParserError::start(evt, [element])),
+                }
+                started = true;
+            }
+
+            let result = inner.consume_event(&event, &mut self.context, None);
+            self.report.warnings.append(&mut self.context.warnings);
+
+            if let Poll::Ready(Err(e)) = result {
+                return Err(e);
+            }
+
+            if inner.state == outer {
+                return Ok(inner);
+            }
         }
     }
 
     pub fn consume_event(&mut self, event: &FilteredEvent) -> Poll<Result<Coverage, ParserError>> {
+        self.consume(event, None)
+    }
+
+    fn consume(
+        &mut self,
+        event: &FilteredEvent,
+        visitor: Option<&mut dyn CoverageVisitor>,
+    ) -> Poll<Result<Coverage, ParserError>> {
+        if self.skip_depth > 0 {
+            match event {
+                FilteredEvent::Start(_) => self.skip_depth += 1,
+                FilteredEvent::End(_) => self.skip_depth -= 1,
+                _ => {}
+            }
+            return Poll::Pending;
+        }
+
         let result = if let Some(inner) = &mut self.inner {
             inner
-                .consume_event(event)
+                .consume_event(event, &mut self.context, visitor)
                 .map(|v| v.map(|_| std::mem::take(&mut inner.coverage)))
         } else {
             self.parse_coverage(event)?;
             Poll::Pending
         };
+        self.report.warnings.append(&mut self.context.warnings);
 
         match result {
             Poll::Pending => Poll::Pending,
+            Poll::Ready(Err(e)) if self.skip_unknown && Self::is_unknown(event, &e) => {
+                match event {
+                    FilteredEvent::Start(start) => {
+                        self.skip_depth = 1;
+                        self.report.warnings.push(Warning::SkippedElement {
+                            name: utf8_attr(start.name()),
+                        });
+                    }
+                    FilteredEvent::AttributesOnly(start) => {
+                        self.report.warnings.push(Warning::SkippedElement {
+                            name: utf8_attr(start.name()),
+                        });
+                    }
+                    _ => self.report.warnings.push(Warning::SkippedText),
+                }
+                if let Some(warning) = self.report.warnings.last() {
+                    log::debug!("{warning}");
+                }
+                Poll::Pending
+            }
             Poll::Ready(value) => {
                 self.inner.take();
                 Poll::Ready(value)
@@ -129,22 +655,58 @@ impl Parser {
         }
     }
 
+    // Whether `error` was caused by `event` being an element or text that
+    // doesn't belong where it is. Ends are never skipped, as a mismatched end
+    // means the structure is broken, and handling it may already have moved
+    // data out of the current element.
+    fn is_unknown(event: &FilteredEvent, error: &ParserError) -> bool {
+        let got = match error {
+            ParserError::ExpectedStart { got, .. }
+            | ParserError::ExpectedStartOrEnd { got, .. } => got,
+            _ => return false,
+        };
+
+        !matches!(event, FilteredEvent::End(_)) && !matches!(got, BasicEvent::End(_))
+    }
+
     fn parse_coverage(&mut self, event: &FilteredEvent) -> Result<(), ParserError> {
         let start = match event {
             FilteredEvent::Start(start) => start,
             evt => return Err(ParserError::start(evt, ["coverage"])),
         };
 
-        if start.name().as_ref() != b"coverage" {
+        if start.local_name().as_ref() != b"coverage" {
             return Err(ParserError::start(event, ["coverage"]));
         }
 
+        self.report = ParseReport::default();
+        self.context.warnings.clear();
+
+        let coverage = Self::coverage_attributes(&mut self.context, start)?;
+
+        self.inner = Some(ParserInner {
+            coverage,
+            state: State::ParsingCoverage,
+            package: Default::default(),
+            class: Default::default(),
+            method: Default::default(),
+            line: Default::default(),
+        });
+
+        Ok(())
+    }
+
+    fn coverage_attributes(
+        ctx: &mut ParseContext,
+        start: &BytesStart,
+    ) -> Result<Coverage, ParserError> {
         let mut coverage = Coverage::default();
-        let attributes = start.attributes();
 
         set_attributes!(
             coverage,
-            attributes,
+            b"coverage",
+            start.attributes(),
+            ctx,
             [b"line-rate", f64, line_rate],
             [b"branch-rate", f64, branch_rate],
             [b"lines-covered", usize, lines_covered],
@@ -152,23 +714,198 @@ impl Parser {
             [b"branches-covered", usize, branches_covered],
             [b"branches-valid", usize, branches_valid],
             [b"complexity", f64, complexity],
-            [b"version", String, version],
+            [b"version", String, version, optional = true],
             [b"timestamp", u64, timestamp, optional = true],
         );
 
-        self.inner = Some(ParserInner {
-            coverage,
-            state: State::ParsingCoverage,
-            package: Default::default(),
-            class: Default::default(),
-            method: Default::default(),
-            line: Default::default(),
-        });
+        ctx.normalize_rates(
+            "coverage",
+            &mut coverage.line_rate,
+            &mut coverage.branch_rate,
+        );
+        ctx.profile.normalize_complexity(&mut coverage.complexity);
+
+        if coverage.timestamp == u64::MAX {
+            ctx.warn(Warning::TimestampOverflow);
+            coverage.timestamp = 0;
+        }
+
+        Ok(coverage)
+    }
+
+    /// Read only the attributes of the `<coverage>` root element, without
+    /// the rest of the document. This is much faster than parsing the whole
+    /// report if only the totals are needed. Warnings about the attributes
+    /// are kept in the [`report`](Self::report).
+    pub fn parse_summary(&mut self, reader: impl BufRead) -> Result<CoverageSummary, ParserError> {
+        self.read_summary(&mut Reader::from_reader(reader), false)
+    }
+
+    /// Like [`parse_summary`](Self::parse_summary), also reading the
+    /// attributes of every `<package>`, but none of their contents.
+    pub fn parse_summary_with_packages(
+        &mut self,
+        reader: impl BufRead,
+    ) -> Result<CoverageSummary, ParserError> {
+        self.read_summary(&mut Reader::from_reader(reader), true)
+    }
+
+    fn read_summary<R>(
+        &mut self,
+        reader: &mut Reader<R>,
+        packages: bool,
+    ) -> Result<CoverageSummary, ParserError>
+    where
+        R: BufRead,
+    {
+        self.reset();
+        let summary = self.read_summary_events(reader, packages);
+        self.report.warnings.append(&mut self.context.warnings);
+        summary
+    }
+
+    fn read_summary_events<R>(
+        &mut self,
+        reader: &mut Reader<R>,
+        packages: bool,
+    ) -> Result<CoverageSummary, ParserError>
+    where
+        R: BufRead,
+    {
+        let mut buf = Vec::new();
+        let mut summary: Option<CoverageSummary> = None;
+
+        loop {
+            buf.clear();
+            let event = reader.read_event_into(&mut buf)?;
+            self.context.decoder = reader.decoder();
+
+            if event == Event::Eof {
+                return Err(ParserError::UnexpectedEof);
+            }
+
+            let Some(event) = FilteredEvent::try_from(event) else {
+                continue;
+            };
+
+            let Some(summary) = &mut summary else {
+                let start = match &event {
+                    FilteredEvent::Start(start) if start.local_name().as_ref() == b"coverage" => {
+                        start
+                    }
+                    evt => return Err(ParserError::start(evt, ["coverage"])),
+                };
+
+                let coverage = Self::coverage_attributes(&mut self.context, start)?;
+                if !packages {
+                    return Ok(CoverageSummary::new(coverage));
+                }
+
+                summary = Some(CoverageSummary::new(coverage));
+                continue;
+            };
+
+            match &event {
+                FilteredEvent::Start(start) | FilteredEvent::AttributesOnly(start)
+                    if start.local_name().as_ref() == b"package" =>
+                {
+                    let mut package = Package::default();
+                    Self::package_attributes(&mut package, &mut self.context, start)?;
+                    summary.packages.push(PackageSummary::new(package));
+
+                    if let FilteredEvent::Start(start) = &event {
+                        // Skip the contents without looking at them.
+                        let end = start.to_end().into_owned();
+                        reader.read_to_end_into(end.name(), &mut Vec::new())?;
+                    }
+                }
+                FilteredEvent::End(end)
+                    if matches!(end.local_name().as_ref(), b"packages" | b"coverage") =>
+                {
+                    return Ok(std::mem::take(summary));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn package_attributes(
+        package: &mut Package,
+        ctx: &mut ParseContext,
+        start: &BytesStart,
+    ) -> Result<(), ParserError> {
+        set_attributes!(
+            package,
+            b"package",
+            start.attributes(),
+            ctx,
+            [b"name", Arc<str>, name],
+            [b"line-rate", f64, line_rate],
+            [b"branch-rate", f64, branch_rate],
+            [b"complexity", f64, complexity],
+        );
+
+        ctx.normalize_rates("package", &mut package.line_rate, &mut package.branch_rate);
+        ctx.profile.normalize_complexity(&mut package.complexity);
 
         Ok(())
     }
 }
 
+/// The totals of a report, read by [`Parser::parse_summary`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoverageSummary {
+    pub line_rate: f64,
+    pub branch_rate: f64,
+    pub lines_covered: usize,
+    pub lines_valid: usize,
+    pub branches_covered: usize,
+    pub branches_valid: usize,
+    pub complexity: f64,
+    pub version: String,
+    pub timestamp: u64,
+    /// Only read by [`Parser::parse_summary_with_packages`].
+    pub packages: Vec<PackageSummary>,
+}
+
+impl CoverageSummary {
+    fn new(coverage: Coverage) -> Self {
+        Self {
+            line_rate: coverage.line_rate,
+            branch_rate: coverage.branch_rate,
+            lines_covered: coverage.lines_covered,
+            lines_valid: coverage.lines_valid,
+            branches_covered: coverage.branches_covered,
+            branches_valid: coverage.branches_valid,
+            complexity: coverage.complexity,
+            version: coverage.version,
+            timestamp: coverage.timestamp,
+            packages: Vec::new(),
+        }
+    }
+}
+
+/// The declared rates of a package, read by
+/// [`Parser::parse_summary_with_packages`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackageSummary {
+    pub name: Arc<str>,
+    pub line_rate: f64,
+    pub branch_rate: f64,
+    pub complexity: f64,
+}
+
+impl PackageSummary {
+    fn new(package: Package) -> Self {
+        Self {
+            name: package.name,
+            line_rate: package.line_rate,
+            branch_rate: package.branch_rate,
+            complexity: package.complexity,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ParserInner {
     coverage: Coverage,
@@ -202,7 +939,7 @@ pub enum State {
 macro_rules ! transition {
     (basic($value:expr), $unexpected:ident, $($name:literal => $to:ident$( with $op:expr)?),*$(,)?) => {{
         $(
-            if $value.name().as_ref() == $name.as_bytes() {
+            if $value.local_name().as_ref() == $name.as_bytes() {
                 $($op;)?
                 return Ok(State::$to);
             }
@@ -225,7 +962,13 @@ macro_rules ! transition {
 }
 
 impl ParserInner {
-    fn consume_event(&mut self, event: &FilteredEvent) -> Poll<Result<(), ParserError>> {
+    fn consume_event(
+        &mut self,
+        event: &FilteredEvent,
+        ctx: &mut ParseContext,
+        visitor: Option<&mut dyn CoverageVisitor>,
+    ) -> Poll<Result<(), ParserError>> {
+        let previous_state = self.state;
         let Self {
             coverage,
             state,
@@ -239,21 +982,43 @@ impl ParserInner {
             State::ParsingCoverage => Self::in_coverage(event),
             State::ParsingSources => Self::in_sources(event),
             State::ParsingSource => Self::in_source(coverage, event),
-            State::ParsingPackages => Self::in_packages(package, event),
+            State::ParsingPackages => Self::in_packages(package, ctx, event),
             State::ParsingPackage => Self::in_package(coverage, package, event),
-            State::ParsingClasses => Self::in_classes(class, event),
-            State::ParsingClass => Self::in_class(package, class, event),
-            State::ParsingMethods => Self::in_methods(method, event),
+            State::ParsingClasses => Self::in_classes(class, ctx, event),
+            State::ParsingClass => Self::in_class(package, class, ctx, event),
+            State::ParsingMethods => Self::in_methods(method, ctx, event),
             State::ParsingMethod => Self::in_method(class, method, event),
-            State::ParsingMethodLines => Self::in_method_lines(method, line, event),
+            State::ParsingMethodLines => Self::in_method_lines(method, line, ctx, event),
             State::ParsingMethodLine => Self::in_method_line(method, line, event),
-            State::ParsingMethodLineConditions => Self::in_method_line_conditions(line, event),
-            State::ParsingClassLines => Self::in_class_lines(class, line, event),
+            State::ParsingMethodLineConditions => Self::in_method_line_conditions(line, ctx, event),
+            State::ParsingClassLines => Self::in_class_lines(class, line, ctx, event),
             State::ParsingClassLine => Self::in_class_line(class, line, event),
-            State::ParsingClassLineConditions => Self::in_class_line_conditions(line, event),
-            State::End => panic!("Consuming more after end event."),
+            State::ParsingClassLineConditions => Self::in_class_line_conditions(line, ctx, event),
+            State::End => Err(ParserError::EventAfterEnd),
         }?;
 
+        if let Some(visitor) = visitor {
+            match (previous_state, next_state) {
+                (State::ParsingClass, State::ParsingClasses) => {
+                    if let Some(class) = package.classes.pop() {
+                        visitor.on_class(package, &class);
+                        for method in &class.methods {
+                            visitor.on_method(&class, method);
+                        }
+                        for line in &class.lines {
+                            visitor.on_line(&class, line);
+                        }
+                    }
+                }
+                (State::ParsingPackage, State::ParsingPackages) => {
+                    if let Some(package) = coverage.packages.pop() {
+                        visitor.on_package(&package);
+                    }
+                }
+                _ => {}
+            }
+        }
+
         self.state = next_state;
 
         if self.state == State::End {
@@ -305,10 +1070,9 @@ impl ParserInner {
     fn in_source(coverage: &mut Coverage, event: &FilteredEvent) -> Result<State, ParserError> {
         match event {
             FilteredEvent::Text(text) => {
+                let path = text.unescape()?;
                 coverage.sources.push(Source {
-                    _data: std::str::from_utf8(text.as_ref())
-                        .map(String::from)
-                        .unwrap_or(String::new()),
+                    path: PathBuf::from(path.trim()),
                 });
 
                 Ok(State::ParsingSource)
@@ -320,19 +1084,15 @@ impl ParserInner {
         }
     }
 
-    fn in_packages(package: &mut Package, event: &FilteredEvent) -> Result<State, ParserError> {
+    fn in_packages(
+        package: &mut Package,
+        ctx: &mut ParseContext,
+        event: &FilteredEvent,
+    ) -> Result<State, ParserError> {
         match event {
             FilteredEvent::Start(start) => {
-                if start.name().as_ref() == b"package" {
-                    set_attributes!(
-                        package,
-                        start.attributes(),
-                        [b"name", String, name],
-                        [b"line-rate", f64, line_rate],
-                        [b"branch-rate", f64, branch_rate],
-                        [b"complexity", f64, complexity],
-                    );
-
+                if start.local_name().as_ref() == b"package" {
+                    Parser::package_attributes(package, ctx, start)?;
                     Ok(State::ParsingPackage)
                 } else {
                     Err(ParserError::start(event, ["package"]))
@@ -365,20 +1125,29 @@ impl ParserInner {
         }
     }
 
-    fn in_classes(class: &mut Class, event: &FilteredEvent) -> Result<State, ParserError> {
+    fn in_classes(
+        class: &mut Class,
+        ctx: &mut ParseContext,
+        event: &FilteredEvent,
+    ) -> Result<State, ParserError> {
         match event {
             FilteredEvent::Start(start) => {
-                if start.name().as_ref() == b"class" {
+                if start.local_name().as_ref() == b"class" {
                     set_attributes!(
                         class,
+                        b"class",
                         start.attributes(),
-                        [b"name", String, name],
-                        [b"filename", PathBuf, file_name],
+                        ctx,
+                        [b"name", Arc<str>, name],
+                        [b"filename", Arc<Path>, file_name, optional = never],
                         [b"line-rate", f64, line_rate],
                         [b"branch-rate", f64, branch_rate],
                         [b"complexity", f64, complexity],
                     );
 
+                    ctx.normalize_rates("class", &mut class.line_rate, &mut class.branch_rate);
+                    ctx.profile.normalize_complexity(&mut class.complexity);
+
                     Ok(State::ParsingClass)
                 } else {
                     Err(ParserError::start(event, ["class"]))
@@ -394,6 +1163,7 @@ impl ParserInner {
     fn in_class(
         package: &mut Package,
         class: &mut Class,
+        ctx: &mut ParseContext,
         event: &FilteredEvent,
     ) -> Result<State, ParserError> {
         match event {
@@ -406,6 +1176,7 @@ impl ParserInner {
             }
             FilteredEvent::End(end) => {
                 let class = std::mem::take(class);
+                Self::check_duplicate_lines(&class, ctx);
                 transition!(basic_end(end), "class" => ParsingClasses with package.classes.push(class));
             }
             FilteredEvent::AttributesOnly(start) => {
@@ -419,19 +1190,46 @@ impl ParserInner {
         }
     }
 
-    fn in_methods(method: &mut Method, event: &FilteredEvent) -> Result<State, ParserError> {
+    fn check_duplicate_lines(class: &Class, ctx: &mut ParseContext) {
+        let mut seen = HashSet::new();
+        let duplicates: BTreeSet<_> = class
+            .lines
+            .iter()
+            .map(|l| l.number)
+            .filter(|number| !seen.insert(*number))
+            .collect();
+
+        for line in duplicates {
+            ctx.warn(Warning::DuplicateLine {
+                class: class.name.to_string(),
+                line,
+            });
+        }
+    }
+
+    fn in_methods(
+        method: &mut Method,
+        ctx: &mut ParseContext,
+        event: &FilteredEvent,
+    ) -> Result<State, ParserError> {
         match event {
             FilteredEvent::Start(start) => {
-                if start.name().as_ref() == b"method" {
+                if start.local_name().as_ref() == b"method" {
                     set_attributes!(
                         method,
+                        b"method",
                         start.attributes(),
-                        [b"name", String, name],
-                        [b"signature", String, signature],
+                        ctx,
+                        [b"name", Arc<str>, name],
+                        [b"signature", Option<Arc<str>>, signature, optional = true],
                         [b"line-rate", f64, line_rate],
                         [b"branch-rate", f64, branch_rate],
+                        [b"complexity", f64, complexity, optional = true],
                     );
 
+                    ctx.normalize_rates("method", &mut method.line_rate, &mut method.branch_rate);
+                    ctx.profile.normalize_complexity(&mut method.complexity);
+
                     Ok(State::ParsingMethod)
                 } else {
                     Err(ParserError::start(event, ["method"]))
@@ -464,45 +1262,42 @@ impl ParserInner {
     fn lines(
         line: &mut Line,
         lines: &mut Vec<Line>,
+        ctx: &mut ParseContext,
         event: &FilteredEvent,
         on_attr_only: State,
         on_list: State,
         on_end: State,
     ) -> Result<State, ParserError> {
         let mut load_lines = |start: &BytesStart| {
-            if start.name().as_ref() != b"line" {
+            if start.local_name().as_ref() != b"line" {
                 return Err(ParserError::start(event, ["line"]));
             }
 
             let attributes = start.attributes();
             let mut number: Option<usize> = None;
-            let mut hits: Option<usize> = None;
+            let mut hits: Option<u64> = None;
 
             let mut condition_coverage: Option<String> = None;
 
             for attribute in attributes {
                 let attribute = attribute.map_err(|_| ParserError::FailedToParseAttribute)?;
-                let value = attribute.unescape_value().unwrap();
+                let value = attribute.decode_and_unescape_value(ctx.decoder)?;
 
                 let err = || ParserError::InvalidValueForAttribute {
                     name: utf8_attr(attribute.key),
                     value: value.to_string(),
                 };
 
-                if attribute.key.as_ref() == b"number" {
-                    number = Some(value.parse().map_err(|_| err())?);
-                }
-
-                if attribute.key.as_ref() == b"hits" {
-                    hits = Some(value.parse().map_err(|_| err())?);
-                }
-
-                if attribute.key.as_ref() == b"branch" {
-                    line.branch = value.parse().map_err(|_| err())?;
-                }
-
-                if attribute.key.as_ref() == b"condition-coverage" {
-                    condition_coverage = Some(value.to_string());
+                match attribute.key.local_name().as_ref() {
+                    b"number" => number = Some(value.parse().map_err(|_| err())?),
+                    b"hits" => hits = Some(parse_hits(&value).ok_or_else(err)?),
+                    b"branch" => line.branch = parse_bool(&value).ok_or_else(err)?,
+                    b"condition-coverage" => condition_coverage = Some(value.to_string()),
+                    _ => {
+                        ctx.unknown_attribute(b"line", attribute.key.as_ref());
+                        line.extra_attributes
+                            .insert(utf8_attr(attribute.key), value.to_string());
+                    }
                 }
             }
 
@@ -525,16 +1320,16 @@ impl ParserInner {
         match event {
             FilteredEvent::Start(start) => {
                 load_lines(start)?;
-                return Ok(on_list);
+                Ok(on_list)
             }
             FilteredEvent::AttributesOnly(start) => {
                 load_lines(start)?;
                 let line = std::mem::take(line);
                 lines.push(line);
-                return Ok(on_attr_only);
+                Ok(on_attr_only)
             }
             FilteredEvent::End(end) => {
-                if end.name().as_ref() == b"lines" {
+                if end.local_name().as_ref() == b"lines" {
                     Ok(on_end)
                 } else {
                     Err(ParserError::end(event, ["lines"]))
@@ -547,11 +1342,13 @@ impl ParserInner {
     fn in_method_lines(
         method: &mut Method,
         line: &mut Line,
+        ctx: &mut ParseContext,
         event: &FilteredEvent,
     ) -> Result<State, ParserError> {
         Self::lines(
             line,
             &mut method.lines,
+            ctx,
             event,
             State::ParsingMethodLines,
             State::ParsingMethodLine,
@@ -562,11 +1359,13 @@ impl ParserInner {
     fn in_class_lines(
         class: &mut Class,
         line: &mut Line,
+        ctx: &mut ParseContext,
         event: &FilteredEvent,
     ) -> Result<State, ParserError> {
         Self::lines(
             line,
             &mut class.lines,
+            ctx,
             event,
             State::ParsingClassLines,
             State::ParsingClassLine,
@@ -615,24 +1414,28 @@ impl ParserInner {
     }
 
     fn in_line_conditions(
-        conditions: &mut Vec<Condition>,
+        line: &mut Line,
+        ctx: &mut ParseContext,
         event: &FilteredEvent,
         on_attr_only: State,
         on_end: State,
     ) -> Result<State, ParserError> {
         match event {
             FilteredEvent::AttributesOnly(start) => {
-                if start.name().as_ref() == b"condition" {
+                if start.local_name().as_ref() == b"condition" {
                     let mut condition = Condition::default();
 
                     set_attributes!(
                         condition,
+                        b"condition",
                         start.attributes(),
-                        [b"type", String, r#type],
+                        ctx,
+                        [b"number", usize, number],
+                        [b"type", ConditionType, r#type],
                         [b"coverage", String, coverage],
                     );
 
-                    conditions.push(condition);
+                    line.conditions.push(condition);
 
                     Ok(on_attr_only)
                 } else {
@@ -640,7 +1443,15 @@ impl ParserInner {
                 }
             }
             FilteredEvent::End(end) => {
-                if end.name().as_ref() == b"conditions" {
+                if end.local_name().as_ref() == b"conditions" {
+                    // The branches of a line with a single switch are its
+                    // cases.
+                    let total = line.branch_counts().map(|(_, total)| total);
+                    if let ([condition], Some(total)) = (&mut line.conditions[..], total) {
+                        if let ConditionType::Switch { cases } = &mut condition.r#type {
+                            *cases = total as u32;
+                        }
+                    }
                     Ok(on_end)
                 } else {
                     Err(ParserError::end(event, ["conditions"]))
@@ -652,10 +1463,12 @@ impl ParserInner {
 
     fn in_method_line_conditions(
         line: &mut Line,
+        ctx: &mut ParseContext,
         event: &FilteredEvent,
     ) -> Result<State, ParserError> {
         Self::in_line_conditions(
-            &mut line.conditions,
+            line,
+            ctx,
             event,
             State::ParsingMethodLineConditions,
             State::ParsingMethodLine,
@@ -664,10 +1477,12 @@ impl ParserInner {
 
     fn in_class_line_conditions(
         line: &mut Line,
+        ctx: &mut ParseContext,
         event: &FilteredEvent,
     ) -> Result<State, ParserError> {
         Self::in_line_conditions(
-            &mut line.conditions,
+            line,
+            ctx,
             event,
             State::ParsingClassLineConditions,
             State::ParsingClassLine,
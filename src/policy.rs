@@ -0,0 +1,270 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    glob::{glob_match, path_matches},
+    unique::unique_rates,
+    ComputedRates, Coverage, Rate,
+};
+
+/// Coverage thresholds, usually loaded from a `coverage.toml` file:
+///
+/// ```toml
+/// # Thresholds for the whole report, in percent.
+/// line = 80
+/// branch = 60
+///
+/// [[rule]]
+/// path = "src/parser/**"
+/// line = 90
+///
+/// [[rule]]
+/// package = "com.example.*"
+/// branch = 75
+///
+/// # Thresholds for each file added since `base`.
+/// [new-files]
+/// base = "origin/main"
+/// line = 90
+/// ```
+///
+/// Path patterns are matched against the file names of classes, and also
+/// match if they match a trailing part of the file name (so relative patterns
+/// work with absolute file names). Package patterns are matched against
+/// package names, with `.` as the separator.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    pub line: Option<f64>,
+    pub branch: Option<f64>,
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<PolicyRule>,
+    #[serde(rename = "new-files")]
+    pub new_files: Option<NewFilesRule>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyRule {
+    pub path: Option<String>,
+    pub package: Option<String>,
+    pub line: Option<f64>,
+    pub branch: Option<f64>,
+}
+
+/// Thresholds that every newly added file must meet on its own, regardless
+/// of the coverage of the rest of the report.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NewFilesRule {
+    /// The git revision that files are new relative to.
+    pub base: Option<String>,
+    pub line: Option<f64>,
+    pub branch: Option<f64>,
+}
+
+impl PolicyRule {
+    fn describe(&self) -> String {
+        match (&self.path, &self.package) {
+            (Some(path), Some(package)) => format!("`{path}` in package `{package}`"),
+            (Some(path), None) => format!("`{path}`"),
+            (None, Some(package)) => format!("package `{package}`"),
+            (None, None) => "all files".to_string(),
+        }
+    }
+}
+
+impl FromStr for Policy {
+    type Err = toml::de::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s)
+    }
+}
+
+impl Policy {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        contents.parse().map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid policy file {}: {e}", path.display()),
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyViolation {
+    /// A description of what the threshold applies to.
+    pub target: String,
+    pub rate: Rate,
+    /// The required coverage, in percent.
+    pub required: f64,
+    /// The actual coverage, in percent.
+    pub actual: f64,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rate = match self.rate {
+            Rate::Line => "line coverage",
+            Rate::Branch => "branch coverage",
+        };
+
+        write!(
+            f,
+            "{}: {rate} {:.2}% is below the required {:.2}%",
+            self.target, self.actual, self.required
+        )
+    }
+}
+
+/// Checks a [`Coverage`] report against a [`Policy`].
+pub struct PolicyChecker {
+    policy: Policy,
+}
+
+impl PolicyChecker {
+    pub fn new(policy: Policy) -> Self {
+        Self { policy }
+    }
+
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    /// Check all thresholds. Rules that match no lines, or rates that cannot
+    /// be computed (e.g. a branch threshold for code without branches), are
+    /// not violations.
+    pub fn check(&self, coverage: &Coverage) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+
+        let rates = coverage.computed_rates();
+        Self::check_rates(
+            "coverage".to_string(),
+            self.policy.line,
+            self.policy.branch,
+            &rates,
+            &mut violations,
+        );
+
+        for rule in &self.policy.rules {
+            let classes = coverage
+                .packages
+                .iter()
+                .filter(|p| {
+                    rule.package
+                        .as_deref()
+                        .is_none_or(|pattern| glob_match(pattern, &p.name, '.'))
+                })
+                .flat_map(|p| &p.classes)
+                .filter(|c| {
+                    let matches = rule
+                        .path
+                        .as_deref()
+                        .is_none_or(|pattern| path_matches(pattern, &c.file_name));
+                    if !matches {
+                        log::trace!("{}: skipping class {}", rule.describe(), c.name);
+                    }
+                    matches
+                });
+
+            let rates = unique_rates(classes, coverage.min_hits);
+            Self::check_rates(
+                rule.describe(),
+                rule.line,
+                rule.branch,
+                &rates,
+                &mut violations,
+            );
+        }
+
+        violations
+    }
+
+    /// Check each of `new_files` against the `[new-files]` thresholds, if the
+    /// policy has them. Files that have no lines in the report (e.g. because
+    /// they aren't code) are skipped.
+    ///
+    /// These violations are not part of [`check`](Self::check), so that they
+    /// can be reported separately. Use [`added_files`](crate::added_files) to
+    /// find the files added in a git repository.
+    pub fn check_new_files(
+        &self,
+        coverage: &Coverage,
+        new_files: &[PathBuf],
+    ) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+
+        let Some(rule) = &self.policy.new_files else {
+            return violations;
+        };
+
+        for file in new_files {
+            let classes: Vec<_> = coverage
+                .packages
+                .iter()
+                .flat_map(|p| &p.classes)
+                .filter(|c| is_same_file(file, &c.file_name))
+                .collect();
+
+            if classes.is_empty() {
+                log::debug!("New file {} has no coverage data", file.display());
+                continue;
+            }
+
+            let rates = unique_rates(classes.iter().copied(), coverage.min_hits);
+            Self::check_rates(
+                format!("new file `{}`", file.display()),
+                rule.line,
+                rule.branch,
+                &rates,
+                &mut violations,
+            );
+        }
+
+        violations
+    }
+
+    fn check_rates(
+        target: String,
+        line: Option<f64>,
+        branch: Option<f64>,
+        rates: &ComputedRates,
+        violations: &mut Vec<PolicyViolation>,
+    ) {
+        let checks = [
+            (Rate::Line, line, rates.line_rate),
+            (Rate::Branch, branch, rates.branch_rate),
+        ];
+
+        for (rate, required, actual) in checks {
+            let (Some(required), Some(actual)) = (required, actual) else {
+                continue;
+            };
+
+            let actual = actual * 100.0;
+            if actual < required {
+                violations.push(PolicyViolation {
+                    target: target.clone(),
+                    rate,
+                    required,
+                    actual,
+                });
+            }
+        }
+    }
+}
+
+// Paths from git are relative to the repository root, while class file names
+// are often relative to a source directory (or absolute), so compare them by
+// their trailing components.
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    a.ends_with(b) || b.ends_with(a)
+}
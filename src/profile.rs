@@ -0,0 +1,144 @@
+// Rates at most this far outside of `[0, 1]` are rounding errors.
+#[cfg(feature = "parser")]
+const RATE_TOLERANCE: f64 = 1e-6;
+
+/// The tool that produced a coverage file.
+///
+/// Each tool deviates from the Cobertura DTD in its own way. A profile
+/// controls which attributes may be missing, and how odd values are
+/// interpreted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Profile {
+    /// Follow the DTD, and take all values as they are, apart from rounding
+    /// errors in rates.
+    #[default]
+    Generic,
+    /// coverage.py (Python). Writes every complexity as `0`, leaves out
+    /// `<conditions>` (but lists where missed branches jump to in a
+    /// `missing-branches` attribute), and writes file names relative to one
+    /// of several `<source>` roots.
+    CoveragePy,
+    /// gcovr (C/C++).
+    Gcovr,
+    /// Coverlet (.NET).
+    Coverlet,
+}
+
+impl Profile {
+    pub const ALL: [Profile; 4] = [
+        Profile::Generic,
+        Profile::CoveragePy,
+        Profile::Gcovr,
+        Profile::Coverlet,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Profile::Generic => "generic",
+            Profile::CoveragePy => "coverage.py",
+            Profile::Gcovr => "gcovr",
+            Profile::Coverlet => "coverlet",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.name().eq_ignore_ascii_case(name))
+    }
+
+    // Attributes that are required by the DTD, but that this tool may omit,
+    // as `(element, attribute)`.
+    fn optional_attributes(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Profile::Generic => &[],
+            Profile::CoveragePy => &[
+                ("coverage", "branches-covered"),
+                ("coverage", "branches-valid"),
+                ("coverage", "complexity"),
+                ("package", "complexity"),
+                ("class", "complexity"),
+            ],
+            Profile::Gcovr => &[
+                ("coverage", "complexity"),
+                ("package", "complexity"),
+                ("class", "complexity"),
+            ],
+            Profile::Coverlet => &[
+                ("coverage", "complexity"),
+                ("package", "complexity"),
+                ("class", "complexity"),
+            ],
+        }
+    }
+
+    // Attributes outside of the DTD that this tool is known to add, as
+    // `(element, attribute)`.
+    fn extension_attributes(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Profile::CoveragePy => &[("line", "missing-branches")],
+            Profile::Generic | Profile::Gcovr | Profile::Coverlet => &[],
+        }
+    }
+
+    /// Whether `attribute` of `element` is not in the DTD, but known to be
+    /// written by this tool.
+    pub fn is_extension(&self, element: &[u8], attribute: &[u8]) -> bool {
+        self.extension_attributes()
+            .iter()
+            .any(|(e, a)| e.as_bytes() == element && a.as_bytes() == attribute)
+    }
+
+    /// Whether `attribute` of `element` may be omitted.
+    pub fn is_optional(&self, element: &[u8], attribute: &[u8]) -> bool {
+        self.optional_attributes()
+            .iter()
+            .any(|(e, a)| e.as_bytes() == element && a.as_bytes() == attribute)
+    }
+
+    /// Whether rates may be written as percentages (e.g. `87.5` instead of
+    /// `0.875`).
+    pub fn rates_may_be_percentages(&self) -> bool {
+        !matches!(self, Profile::Generic)
+    }
+
+    /// Whether `NaN` rates, which tools write for elements without any lines
+    /// or branches, should be read as `1`.
+    pub fn nan_rate_as_one(&self) -> bool {
+        !matches!(self, Profile::Generic)
+    }
+
+    /// Whether `NaN` complexity values should be read as `0`.
+    pub fn nan_complexity_as_zero(&self) -> bool {
+        !matches!(self, Profile::Generic)
+    }
+
+    /// Clamp rounding errors into `[0, 1]`, and read percentages and `NaN`
+    /// as this tool means them. Returns the rate as it was read if it
+    /// changed.
+    #[cfg(feature = "parser")]
+    pub(crate) fn normalize_rate(&self, rate: &mut f64) -> Option<f64> {
+        let read = *rate;
+
+        if rate.is_nan() {
+            if self.nan_rate_as_one() {
+                *rate = 1.0;
+            }
+        } else if *rate > 1.0 && *rate <= 1.0 + RATE_TOLERANCE {
+            *rate = 1.0;
+        } else if *rate < 0.0 && *rate >= -RATE_TOLERANCE {
+            *rate = 0.0;
+        } else if self.rates_may_be_percentages() && *rate > 1.0 && *rate <= 100.0 {
+            *rate /= 100.0;
+        }
+
+        (rate.to_bits() != read.to_bits()).then_some(read)
+    }
+
+    #[cfg(feature = "parser")]
+    pub(crate) fn normalize_complexity(&self, complexity: &mut f64) {
+        if self.nan_complexity_as_zero() && complexity.is_nan() {
+            *complexity = 0.0;
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    fmt, io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// How far a long-running operation has come.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProgressUpdate {
+    /// The number of items processed, i.e. classes parsed or class pages
+    /// written.
+    pub items: u64,
+    /// The number of items that will be processed, if known.
+    pub total_items: Option<u64>,
+    /// The number of bytes of the input read, when parsing.
+    pub bytes: u64,
+    /// The size of the input, if known.
+    pub total_bytes: Option<u64>,
+}
+
+/// Cancels an operation from another thread. Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask operations using the token to stop. They stop cooperatively, i.e.
+    /// the next time they check the token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The error of an operation that was cancelled through its
+/// [`CancellationToken`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("The operation was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+impl From<Cancelled> for io::Error {
+    fn from(value: Cancelled) -> Self {
+        io::Error::new(io::ErrorKind::Interrupted, value)
+    }
+}
+
+type ProgressCallback = dyn Fn(&ProgressUpdate) + Send + Sync;
+
+/// Progress reporting and cancellation for the parser and generators.
+///
+/// The callback is called after every item, and can e.g. update an
+/// `indicatif` progress bar:
+///
+/// ```ignore
+/// let bar = ProgressBar::new(0);
+/// let progress = Progress::new().with_callback(move |update| {
+///     bar.set_length(update.total_bytes.unwrap_or(0));
+///     bar.set_position(update.bytes);
+/// });
+/// ```
+#[derive(Clone, Default)]
+pub struct Progress {
+    callback: Option<Arc<ProgressCallback>>,
+    token: Option<CancellationToken>,
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("callback", &self.callback.is_some())
+            .field("token", &self.token)
+            .finish()
+    }
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_callback(
+        mut self,
+        callback: impl Fn(&ProgressUpdate) + Send + Sync + 'static,
+    ) -> Self {
+        self.callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Stop with a [`Cancelled`] error once `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// `Err(Cancelled)` once cancelled, for use with `?`.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Pass `update` to the callback, if any.
+    pub fn report(&self, update: &ProgressUpdate) {
+        if let Some(callback) = &self.callback {
+            callback(update);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::Coverage;
+
+/// Which inputs contributed hits to which lines of a merged report, to find
+/// out why a line is covered.
+///
+/// Inputs are added before merging them, e.g.:
+///
+/// ```ignore
+/// let mut provenance = LineProvenance::new();
+/// provenance.add_input("unit.xml", &unit);
+/// provenance.add_input("integration.xml", &integration);
+/// unit.merge(integration);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineProvenance {
+    inputs: Vec<String>,
+    // Indices into `inputs` with the hits of that input, in ascending order.
+    lines: HashMap<(PathBuf, usize), Vec<(usize, u64)>>,
+}
+
+impl LineProvenance {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the hits of all covered lines of `coverage`, by its
+    /// [`Coverage::min_hits`], as coming from `input`. Adding the same input
+    /// again adds up its hits.
+    pub fn add_input(&mut self, input: impl Into<String>, coverage: &Coverage) {
+        let input = input.into();
+        let idx = match self.inputs.iter().position(|i| *i == input) {
+            Some(idx) => idx,
+            None => {
+                self.inputs.push(input);
+                self.inputs.len() - 1
+            }
+        };
+
+        for class in coverage.classes() {
+            for line in class
+                .lines
+                .iter()
+                .filter(|l| l.is_covered(coverage.min_hits))
+            {
+                let inputs = self
+                    .lines
+                    .entry((class.file_name.to_path_buf(), line.number))
+                    .or_default();
+
+                match inputs.binary_search_by_key(&idx, |(i, _)| *i) {
+                    Ok(pos) => inputs[pos].1 = inputs[pos].1.saturating_add(line.hits),
+                    Err(pos) => inputs.insert(pos, (idx, line.hits)),
+                }
+            }
+        }
+    }
+
+    /// All inputs, in the order they were added.
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    /// The inputs that contributed hits to line `line` of `file`, with their
+    /// hits, in the order they were added.
+    pub fn inputs_for_line<'a>(
+        &'a self,
+        file: &Path,
+        line: usize,
+    ) -> impl Iterator<Item = (&'a str, u64)> + 'a {
+        self.lines
+            .get(&(file.to_path_buf(), line))
+            .into_iter()
+            .flatten()
+            .map(|(idx, hits)| (self.inputs[*idx].as_str(), *hits))
+    }
+}
@@ -0,0 +1,174 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use crate::{unique::unique_rates, Class, Coverage, Line, Package};
+
+/// A summary of all classes defined in a single file.
+#[derive(Debug, Clone)]
+pub struct FileSummary<'a> {
+    pub path: &'a Path,
+    /// `None` if the file has no lines.
+    pub line_rate: Option<f64>,
+    /// `None` if the file has no branches.
+    pub branch_rate: Option<f64>,
+    pub classes: Vec<&'a Class>,
+}
+
+impl<'a> FileSummary<'a> {
+    pub fn lines(&self) -> impl Iterator<Item = &'a Line> + '_ {
+        self.classes.iter().flat_map(|c| &c.lines)
+    }
+}
+
+impl Coverage {
+    /// The first class whose `file_name` is `path`.
+    pub fn class_for_file(&self, path: impl AsRef<Path>) -> Option<&Class> {
+        let path = path.as_ref();
+        self.classes().find(|c| *c.file_name == *path)
+    }
+
+    /// The line with number `line` in the file `path`, searching all classes
+    /// defined in that file.
+    pub fn coverage_for_line(&self, path: impl AsRef<Path>, line: usize) -> Option<&Line> {
+        let path = path.as_ref();
+        self.classes()
+            .filter(|c| *c.file_name == *path)
+            .flat_map(|c| &c.lines)
+            .find(|l| l.number == line)
+    }
+
+    pub fn find_package(&self, name: &str) -> Option<&Package> {
+        self.packages.iter().find(|p| &*p.name == name)
+    }
+
+    /// A summary per file, in order of first appearance.
+    pub fn files(&self) -> impl Iterator<Item = FileSummary<'_>> {
+        let mut files: Vec<FileSummary> = Vec::new();
+        let mut positions: HashMap<&Path, usize> = HashMap::new();
+
+        for class in self.classes() {
+            let path = &*class.file_name;
+            let idx = *positions.entry(path).or_insert_with(|| {
+                files.push(FileSummary {
+                    path,
+                    line_rate: None,
+                    branch_rate: None,
+                    classes: Vec::new(),
+                });
+                files.len() - 1
+            });
+            files[idx].classes.push(class);
+        }
+
+        for file in &mut files {
+            let rates = unique_rates(file.classes.iter().copied(), self.min_hits);
+            file.line_rate = rates.line_rate;
+            file.branch_rate = rates.branch_rate;
+        }
+
+        files.into_iter()
+    }
+
+    /// Build a [`CoverageIndex`] for repeated lookups.
+    pub fn index(&self) -> CoverageIndex<'_> {
+        CoverageIndex::new(self)
+    }
+}
+
+impl Package {
+    pub fn find_class(&self, name: &str) -> Option<&Class> {
+        self.classes.iter().find(|c| &*c.name == name)
+    }
+}
+
+impl Class {
+    pub fn find_line(&self, number: usize) -> Option<&Line> {
+        self.lines.iter().find(|l| l.number == number)
+    }
+}
+
+/// An index over a [`Coverage`] for fast lookups by file, class name and line.
+///
+/// If a line number appears more than once for a file, the first occurrence
+/// is indexed.
+#[derive(Debug, Clone)]
+pub struct CoverageIndex<'a> {
+    packages: HashMap<&'a str, &'a Package>,
+    classes: HashMap<&'a str, Vec<&'a Class>>,
+    files: HashMap<&'a Path, Vec<&'a Class>>,
+    lines: HashMap<&'a Path, BTreeMap<usize, &'a Line>>,
+}
+
+impl<'a> CoverageIndex<'a> {
+    pub fn new(coverage: &'a Coverage) -> Self {
+        let mut packages = HashMap::new();
+        let mut classes: HashMap<_, Vec<_>> = HashMap::new();
+        let mut files: HashMap<_, Vec<_>> = HashMap::new();
+        let mut lines: HashMap<_, BTreeMap<_, _>> = HashMap::new();
+
+        for package in &coverage.packages {
+            packages.entry(&*package.name).or_insert(package);
+
+            for class in &package.classes {
+                let path = &*class.file_name;
+
+                classes.entry(&*class.name).or_default().push(class);
+                files.entry(path).or_default().push(class);
+
+                let file_lines = lines.entry(path).or_default();
+                for line in &class.lines {
+                    file_lines.entry(line.number).or_insert(line);
+                }
+            }
+        }
+
+        Self {
+            packages,
+            classes,
+            files,
+            lines,
+        }
+    }
+
+    pub fn package(&self, name: &str) -> Option<&'a Package> {
+        self.packages.get(name).copied()
+    }
+
+    /// All classes named `name`, across all packages.
+    pub fn classes_named(&self, name: &str) -> &[&'a Class] {
+        self.classes
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// All classes defined in the file `path`.
+    pub fn classes_for_file(&self, path: impl AsRef<Path>) -> &[&'a Class] {
+        self.files
+            .get(path.as_ref())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn class_for_file(&self, path: impl AsRef<Path>) -> Option<&'a Class> {
+        self.classes_for_file(path).first().copied()
+    }
+
+    pub fn line(&self, path: impl AsRef<Path>, line: usize) -> Option<&'a Line> {
+        self.lines.get(path.as_ref())?.get(&line).copied()
+    }
+
+    /// All lines of the file `path`, ordered by line number.
+    pub fn lines_for_file(&self, path: impl AsRef<Path>) -> impl Iterator<Item = &'a Line> + '_ {
+        self.lines
+            .get(path.as_ref())
+            .into_iter()
+            .flat_map(|l| l.values().copied())
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &'a Path> + '_ {
+        self.files.keys().copied()
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{Coverage, FileCoverage};
+
+/// Which files ranked views, such as the least covered files of a digest or
+/// the hotspots of [`Coverage::crap_ranking_with`], leave out, so that tiny
+/// files (e.g. a single uncovered constant) don't top them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RankingFilter {
+    /// Leave out files with fewer tracked lines than this.
+    pub min_lines: usize,
+}
+
+impl RankingFilter {
+    pub fn new(min_lines: usize) -> Self {
+        Self { min_lines }
+    }
+
+    /// Whether a file with `lines` tracked lines is ranked.
+    pub fn includes(&self, lines: usize) -> bool {
+        lines >= self.min_lines
+    }
+}
+
+impl Coverage {
+    /// The [files](Self::file_coverage) that `filter` doesn't leave out.
+    pub fn ranked_files(&self, filter: &RankingFilter) -> Vec<FileCoverage<'_>> {
+        let mut files = self.file_coverage();
+        files.retain(|file| filter.includes(file.lines.len()));
+        files
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{unique::unique_rates, Class, Coverage, Line, Method, Package};
+
+/// Rates computed from the line data of a single element.
+///
+/// A rate is `None` if there is nothing to compute it from (i.e. no lines, or
+/// no branch lines).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ComputedRates {
+    pub lines_valid: usize,
+    pub lines_covered: usize,
+    pub branches_valid: usize,
+    pub branches_covered: usize,
+    pub line_rate: Option<f64>,
+    pub branch_rate: Option<f64>,
+}
+
+impl ComputedRates {
+    /// The rates of `lines`, only counting lines hit at least `min_hits`
+    /// times as covered (see [`Line::is_covered`]).
+    pub fn from_lines<'a>(lines: impl IntoIterator<Item = &'a Line>, min_hits: u64) -> Self {
+        let mut valid = 0usize;
+        let mut covered = 0usize;
+        let mut branches_valid = 0usize;
+        let mut branches_covered = 0usize;
+
+        for line in lines {
+            valid += 1;
+            covered += line.is_covered(min_hits) as usize;
+
+            if let Some((hit, total)) = line.branch_counts() {
+                branches_covered += hit;
+                branches_valid += total;
+            }
+        }
+
+        let rate = |hit: usize, total: usize| (total > 0).then(|| hit as f64 / total as f64);
+
+        Self {
+            lines_valid: valid,
+            lines_covered: covered,
+            branches_valid,
+            branches_covered,
+            line_rate: rate(covered, valid),
+            branch_rate: rate(branches_covered, branches_valid),
+        }
+    }
+
+    fn apply(&self, line_rate: &mut f64, branch_rate: &mut f64) {
+        if let Some(rate) = self.line_rate {
+            *line_rate = rate;
+        }
+
+        if let Some(rate) = self.branch_rate {
+            *branch_rate = rate;
+        }
+    }
+}
+
+impl Method {
+    /// The rates of the method, with the `min_hits` of its report (see
+    /// [`Coverage::min_hits`]).
+    pub fn computed_rates(&self, min_hits: u64) -> ComputedRates {
+        ComputedRates::from_lines(&self.lines, min_hits)
+    }
+}
+
+impl Class {
+    /// The rates of the class, with the `min_hits` of its report (see
+    /// [`Coverage::min_hits`]).
+    pub fn computed_rates(&self, min_hits: u64) -> ComputedRates {
+        ComputedRates::from_lines(&self.lines, min_hits)
+    }
+
+    /// Recompute the rates of this class and its methods from their lines,
+    /// with the `min_hits` of its report (see [`Coverage::min_hits`]).
+    ///
+    /// The rates of the package and the report that contain this class are
+    /// not updated, see [`Coverage::recompute_rates`].
+    pub fn recompute_rates(&mut self, min_hits: u64) {
+        for method in &mut self.methods {
+            let rates = method.computed_rates(min_hits);
+            rates.apply(&mut method.line_rate, &mut method.branch_rate);
+        }
+
+        let rates = self.computed_rates(min_hits);
+        rates.apply(&mut self.line_rate, &mut self.branch_rate);
+    }
+}
+
+impl Package {
+    /// The rates of the package, with the `min_hits` of its report (see
+    /// [`Coverage::min_hits`]).
+    pub fn computed_rates(&self, min_hits: u64) -> ComputedRates {
+        unique_rates(&self.classes, min_hits)
+    }
+}
+
+impl Coverage {
+    /// The rates of the report, with [`Coverage::min_hits`].
+    pub fn computed_rates(&self) -> ComputedRates {
+        unique_rates(self.classes(), self.min_hits)
+    }
+
+    /// Recompute all declared rates and counts from the line data, with
+    /// [`Coverage::min_hits`].
+    ///
+    /// Rates of elements without any lines (or without any branches) are left
+    /// untouched, since there is nothing to compute them from.
+    pub fn recompute_rates(&mut self) {
+        for package in &mut self.packages {
+            for class in &mut package.classes {
+                class.recompute_rates(self.min_hits);
+            }
+
+            let rates = package.computed_rates(self.min_hits);
+            rates.apply(&mut package.line_rate, &mut package.branch_rate);
+        }
+
+        let rates = self.computed_rates();
+        rates.apply(&mut self.line_rate, &mut self.branch_rate);
+        self.lines_valid = rates.lines_valid;
+        self.lines_covered = rates.lines_covered;
+        self.branches_valid = rates.branches_valid;
+        self.branches_covered = rates.branches_covered;
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use crate::{Class, Coverage, Line};
+
+/// Which data to keep for a line that a class and one of its methods report
+/// differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LinePrecedence {
+    /// Keep the line of the class.
+    #[default]
+    Class,
+    /// Keep the line of the method.
+    Method,
+    /// Keep the line with the most hits.
+    Max,
+}
+
+/// A line whose hits differ between a class and one of its methods.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineConflict {
+    pub class: Arc<str>,
+    pub method: Arc<str>,
+    pub line: usize,
+    pub class_hits: u64,
+    pub method_hits: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReconciliationReport {
+    /// The lines that were resolved according to the precedence.
+    pub conflicts: Vec<LineConflict>,
+    /// The number of lines that were only reported for a method, and were
+    /// added to its class.
+    pub added_lines: usize,
+}
+
+impl Coverage {
+    /// Make the lines of every method agree with the lines of its class.
+    ///
+    /// Some tools report a line under both `<method><lines>` and
+    /// `<class><lines>` with different hits; these are resolved according to
+    /// `precedence` and reported as conflicts. Lines that are only reported
+    /// for a method are added to the class, so that the class lines (and thus
+    /// [`Coverage::lines`]) hold every line exactly once. All rates are
+    /// recomputed afterwards.
+    pub fn reconcile_lines(&mut self, precedence: LinePrecedence) -> ReconciliationReport {
+        let mut report = ReconciliationReport::default();
+
+        for class in self.packages.iter_mut().flat_map(|p| &mut p.classes) {
+            reconcile_class(class, precedence, &mut report);
+        }
+
+        self.recompute_rates();
+        report
+    }
+}
+
+fn reconcile_class(
+    class: &mut Class,
+    precedence: LinePrecedence,
+    report: &mut ReconciliationReport,
+) {
+    // Sorted, so that lines can be looked up and inserted in order.
+    class.lines.sort_by_key(|l| l.number);
+
+    for method in &mut class.methods {
+        for method_line in &mut method.lines {
+            let class_line = match class
+                .lines
+                .binary_search_by_key(&method_line.number, |l| l.number)
+            {
+                Ok(idx) => &mut class.lines[idx],
+                Err(idx) => {
+                    class.lines.insert(idx, method_line.clone());
+                    report.added_lines += 1;
+                    continue;
+                }
+            };
+
+            if class_line.hits == method_line.hits {
+                continue;
+            }
+
+            report.conflicts.push(LineConflict {
+                class: class.name.clone(),
+                method: method.name.clone(),
+                line: method_line.number,
+                class_hits: class_line.hits,
+                method_hits: method_line.hits,
+            });
+
+            let keep_method = match precedence {
+                LinePrecedence::Class => false,
+                LinePrecedence::Method => true,
+                LinePrecedence::Max => method_line.hits > class_line.hits,
+            };
+
+            if keep_method {
+                copy_hits(class_line, method_line);
+            } else {
+                copy_hits(method_line, class_line);
+            }
+        }
+    }
+}
+
+// Make the hits and branch coverage of `into` those of `from`.
+fn copy_hits(into: &mut Line, from: &Line) {
+    into.hits = from.hits;
+    into.branch = from.branch;
+    into.condition_coverage.clone_from(&from.condition_coverage);
+    into.conditions.clone_from(&from.conditions);
+}
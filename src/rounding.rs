@@ -0,0 +1,76 @@
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round to the nearest value, e.g. `99.96%` is shown as `100.0%`.
+    #[default]
+    Nearest,
+    /// Round down, so that `100%` is only shown if everything is covered.
+    Floor,
+}
+
+/// How generators display percentages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundingPolicy {
+    pub decimals: u8,
+    pub mode: RoundingMode,
+    pub decimal_separator: char,
+}
+
+impl Default for RoundingPolicy {
+    fn default() -> Self {
+        Self {
+            decimals: 1,
+            mode: RoundingMode::Nearest,
+            decimal_separator: '.',
+        }
+    }
+}
+
+impl RoundingPolicy {
+    /// Round `percentage` (in the range `0..=100`) to the configured number
+    /// of decimals.
+    pub fn round(&self, percentage: f64) -> f64 {
+        let factor = 10f64.powi(self.decimals as i32);
+        let scaled = percentage * factor;
+
+        let rounded = match self.mode {
+            RoundingMode::Nearest => scaled.round(),
+            // Allow for floating point error, so that e.g. `0.29 * 100.0`
+            // doesn't end up as `28`.
+            RoundingMode::Floor => (scaled + 1e-9).floor(),
+        };
+
+        rounded / factor
+    }
+
+    /// Format `percentage` (in the range `0..=100`), without a `%` sign.
+    pub fn format(&self, percentage: f64) -> String {
+        let formatted = format!("{:.*}", self.decimals as usize, self.round(percentage));
+
+        if self.decimal_separator == '.' {
+            formatted
+        } else {
+            formatted.replace('.', &self.decimal_separator.to_string())
+        }
+    }
+
+    /// Format `rate` (in the range `0..=1`) as a percentage, including the
+    /// `%` sign.
+    pub fn format_rate(&self, rate: f64) -> String {
+        format!("{}%", self.format(rate * 100.0))
+    }
+
+    /// Format the change `delta` of a rate (in the range `-1..=1`) in
+    /// percentage points, always with a sign, e.g. `+0.6` or `-1.0`.
+    pub fn format_change(&self, delta: f64) -> String {
+        let rounded = self.round(delta * 100.0);
+        let sign = if rounded > 0.0 {
+            "+"
+        } else if rounded < 0.0 {
+            "-"
+        } else {
+            "±"
+        };
+
+        format!("{sign}{}", self.format(rounded.abs()))
+    }
+}
@@ -0,0 +1,159 @@
+use crate::Method;
+
+// C# keywords for the built-in .NET types.
+static DOTNET_KEYWORDS: &[(&str, &str)] = &[
+    ("System.Boolean", "bool"),
+    ("System.Byte", "byte"),
+    ("System.SByte", "sbyte"),
+    ("System.Char", "char"),
+    ("System.Decimal", "decimal"),
+    ("System.Double", "double"),
+    ("System.Single", "float"),
+    ("System.Int16", "short"),
+    ("System.UInt16", "ushort"),
+    ("System.Int32", "int"),
+    ("System.UInt32", "uint"),
+    ("System.Int64", "long"),
+    ("System.UInt64", "ulong"),
+    ("System.Object", "object"),
+    ("System.String", "string"),
+    ("System.Void", "void"),
+];
+
+/// A readable form of a method signature, for display.
+///
+/// JVM descriptors such as `(Ljava/lang/String;[I)V` become
+/// `(String, int[]): void`, and .NET signatures such as
+/// `(System.String,System.Collections.Generic.List`1<System.Int32>)` become
+/// `(string, List<int>)`. Other signatures are returned as they are.
+pub fn prettify_signature(signature: &str) -> String {
+    jvm_signature(signature)
+        .or_else(|| dotnet_signature(signature))
+        .unwrap_or_else(|| signature.to_string())
+}
+
+impl Method {
+    /// The signature of this method in a readable form, see
+    /// [`prettify_signature`].
+    pub fn pretty_signature(&self) -> String {
+        prettify_signature(self.signature.as_deref().unwrap_or_default())
+    }
+}
+
+fn jvm_signature(signature: &str) -> Option<String> {
+    let mut rest = signature.strip_prefix('(')?;
+
+    let mut parameters = Vec::new();
+    let returns = loop {
+        if let Some(after) = rest.strip_prefix(')') {
+            let (returns, after) = jvm_type(after)?;
+            if !after.is_empty() {
+                return None;
+            }
+            break returns;
+        }
+
+        let (parameter, after) = jvm_type(rest)?;
+        parameters.push(parameter);
+        rest = after;
+    };
+
+    Some(format!("({}): {returns}", parameters.join(", ")))
+}
+
+// Parse one field descriptor from the start of `descriptor`, returning its
+// readable form and the rest of `descriptor`.
+fn jvm_type(descriptor: &str) -> Option<(String, &str)> {
+    let mut chars = descriptor.chars();
+    let name = match chars.next()? {
+        'B' => "byte",
+        'C' => "char",
+        'D' => "double",
+        'F' => "float",
+        'I' => "int",
+        'J' => "long",
+        'S' => "short",
+        'Z' => "boolean",
+        'V' => "void",
+        '[' => {
+            let (element, rest) = jvm_type(chars.as_str())?;
+            return Some((format!("{element}[]"), rest));
+        }
+        'L' => {
+            let (class, rest) = chars.as_str().split_once(';')?;
+            let name = class.rsplit('/').next()?.replace('$', ".");
+            return (!name.is_empty()).then_some((name, rest));
+        }
+        _ => return None,
+    };
+
+    Some((name.to_string(), chars.as_str()))
+}
+
+fn dotnet_signature(signature: &str) -> Option<String> {
+    let parameters = signature.strip_prefix('(')?.strip_suffix(')')?;
+    if parameters.trim().is_empty() {
+        return Some("()".to_string());
+    }
+
+    let parameters: Vec<_> = split_top_level(parameters)
+        .into_iter()
+        .map(dotnet_type)
+        .collect();
+
+    Some(format!("({})", parameters.join(", ")))
+}
+
+fn dotnet_type(name: &str) -> String {
+    let name = name.trim();
+
+    // Array, pointer and by-reference suffixes.
+    let base_end = name.trim_end_matches(['[', ']', ',', '*', '&']).len();
+    let (name, suffix) = name.split_at(base_end);
+
+    let (name, arguments) = match name.split_once('<') {
+        Some((name, arguments)) => {
+            let arguments = arguments.strip_suffix('>').unwrap_or(arguments);
+            let arguments: Vec<_> = split_top_level(arguments)
+                .into_iter()
+                .map(dotnet_type)
+                .collect();
+            (name, format!("<{}>", arguments.join(", ")))
+        }
+        None => (name, String::new()),
+    };
+
+    let name = match DOTNET_KEYWORDS.iter().find(|(full, _)| *full == name) {
+        Some((_, keyword)) => keyword.to_string(),
+        None => {
+            // Strip the namespace and the generic arity, e.g. `List`1`.
+            let name = name.rsplit('.').next().unwrap_or(name);
+            let name = name.split('`').next().unwrap_or(name);
+            name.replace(['/', '+'], ".")
+        }
+    };
+
+    format!("{name}{arguments}{suffix}")
+}
+
+// Split `list` on the commas that are not nested in brackets.
+fn split_top_level(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (idx, c) in list.char_indices() {
+        match c {
+            '<' | '[' | '(' => depth += 1,
+            '>' | ']' | ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&list[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(&list[start..]);
+    parts
+}
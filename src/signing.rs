@@ -0,0 +1,197 @@
+use std::{collections::BTreeMap, fmt, io};
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::ReportSink;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The SHA-256 hashes of all files of a generated report, by path, so that
+/// consumers can check that the report wasn't changed after it was
+/// generated.
+///
+/// A manifest can be signed with a secret key shared with the consumers.
+/// The signature is the hex HMAC-SHA256 of one `HASH  PATH` line per file
+/// (as written by `sha256sum`), sorted by path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportManifest {
+    files: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+}
+
+/// A file that doesn't match its hash in a [`ReportManifest`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestMismatch {
+    /// The file has changed since the manifest was written.
+    Changed { path: String },
+    /// The file is missing, or could not be read.
+    Missing { path: String },
+}
+
+impl fmt::Display for ManifestMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Changed { path } => write!(f, "{path} changed since the report was generated"),
+            Self::Missing { path } => write!(f, "{path} is missing"),
+        }
+    }
+}
+
+impl ReportManifest {
+    /// The file a [`ManifestSink`] writes the manifest to.
+    pub const FILE_NAME: &'static str = "report-manifest.json";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the file `path` with `contents`, replacing any previous hash.
+    /// This removes the signature.
+    pub fn add(&mut self, path: &str, contents: &[u8]) {
+        self.files
+            .insert(path.to_string(), hex(&Sha256::digest(contents)));
+        self.signature = None;
+    }
+
+    /// Forget the file `path`. This removes the signature.
+    pub fn remove(&mut self, path: &str) {
+        self.files.remove(path);
+        self.signature = None;
+    }
+
+    /// All files, as `(path, hex SHA-256)`, sorted by path.
+    pub fn files(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.files.iter().map(|(p, h)| (p.as_str(), h.as_str()))
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.signature.is_some()
+    }
+
+    fn mac(&self, key: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+        for (path, hash) in &self.files {
+            mac.update(format!("{hash}  {path}\n").as_bytes());
+        }
+        mac
+    }
+
+    /// Sign the manifest with `key`.
+    pub fn sign(&mut self, key: &[u8]) {
+        self.signature = Some(hex(&self.mac(key).finalize().into_bytes()));
+    }
+
+    /// Whether the manifest is signed with `key`, and its files weren't
+    /// changed since.
+    pub fn verify_signature(&self, key: &[u8]) -> bool {
+        let Some(signature) = &self.signature else {
+            return false;
+        };
+
+        let Some(signature) = (0..signature.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(signature.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<_>>>()
+        else {
+            return false;
+        };
+
+        self.mac(key).verify_slice(&signature).is_ok()
+    }
+
+    /// Check every file against its hash. `read` is called once per file to
+    /// read it, returning `None` if it doesn't exist. Files that are not in
+    /// the manifest are not noticed.
+    pub fn verify(
+        &self,
+        mut read: impl FnMut(&str) -> io::Result<Option<Vec<u8>>>,
+    ) -> Vec<ManifestMismatch> {
+        let mut mismatches = Vec::new();
+
+        for (path, hash) in &self.files {
+            match read(path) {
+                Ok(Some(contents)) if hex(&Sha256::digest(&contents)) == *hash => {}
+                Ok(Some(_)) => mismatches.push(ManifestMismatch::Changed { path: path.clone() }),
+                Ok(None) => mismatches.push(ManifestMismatch::Missing { path: path.clone() }),
+                Err(e) => {
+                    log::debug!("Could not read {path}: {e}");
+                    mismatches.push(ManifestMismatch::Missing { path: path.clone() });
+                }
+            }
+        }
+
+        mismatches
+    }
+
+    pub fn to_json(&self) -> io::Result<Vec<u8>> {
+        serde_json::to_vec_pretty(self).map_err(io::Error::other)
+    }
+
+    pub fn from_json(data: &[u8]) -> io::Result<Self> {
+        serde_json::from_slice(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// A sink that records every file written to another sink in a
+/// [`ReportManifest`], e.g. to sign all files of a generated report.
+///
+/// It reports all files as missing, so that incremental generators write
+/// every file, and all of them end up in the manifest.
+pub struct ManifestSink<'a> {
+    sink: &'a mut dyn ReportSink,
+    manifest: ReportManifest,
+}
+
+impl<'a> ManifestSink<'a> {
+    pub fn new(sink: &'a mut dyn ReportSink) -> Self {
+        Self {
+            sink,
+            manifest: ReportManifest::new(),
+        }
+    }
+
+    /// The files written so far.
+    pub fn manifest(&self) -> &ReportManifest {
+        &self.manifest
+    }
+
+    /// Sign the manifest with `key` if given, and write it to
+    /// [`ReportManifest::FILE_NAME`] of the sink.
+    pub fn finish(mut self, key: Option<&[u8]>) -> io::Result<ReportManifest> {
+        if let Some(key) = key {
+            self.manifest.sign(key);
+        }
+
+        self.sink
+            .write(ReportManifest::FILE_NAME, &self.manifest.to_json()?)?;
+        Ok(self.manifest)
+    }
+}
+
+impl ReportSink for ManifestSink<'_> {
+    fn write(&mut self, path: &str, contents: &[u8]) -> io::Result<()> {
+        self.manifest.add(path, contents);
+        self.sink.write(path, contents)
+    }
+
+    fn write_batch(&mut self, files: &[(String, Vec<u8>)]) -> io::Result<()> {
+        for (path, contents) in files {
+            self.manifest.add(path, contents);
+        }
+        self.sink.write_batch(files)
+    }
+
+    fn read(&self, path: &str) -> io::Result<Option<Vec<u8>>> {
+        self.sink.read(path)
+    }
+
+    fn remove(&mut self, path: &str) -> io::Result<()> {
+        self.manifest.remove(path);
+        self.sink.remove(path)
+    }
+}
@@ -0,0 +1,143 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+mod object_store;
+mod zip;
+
+pub use object_store::{content_type, ObjectStore, ObjectStoreSink};
+pub use zip::ZipSink;
+
+/// Where generators write their output files to.
+///
+/// Paths are relative to the root of the report and use `/` as separator.
+pub trait ReportSink {
+    /// Write `contents` to `path`, replacing it if it exists.
+    fn write(&mut self, path: &str, contents: &[u8]) -> io::Result<()>;
+
+    /// Write several files, as `(path, contents)`. Sinks that can write
+    /// files concurrently override this, by default they are written one
+    /// after the other.
+    fn write_batch(&mut self, files: &[(String, Vec<u8>)]) -> io::Result<()> {
+        for (path, contents) in files {
+            self.write(path, contents)?;
+        }
+        Ok(())
+    }
+
+    /// Whether `path` exists. Sinks that can't tell always return `false`,
+    /// which causes incremental generators to rewrite everything.
+    fn contains(&self, path: &str) -> bool {
+        let _ = path;
+        false
+    }
+
+    /// The contents of `path`, or `None` if it doesn't exist or the sink
+    /// can't read back what it wrote.
+    fn read(&self, path: &str) -> io::Result<Option<Vec<u8>>> {
+        let _ = path;
+        Ok(None)
+    }
+
+    /// Remove `path`. Removing a file that doesn't exist is not an error.
+    fn remove(&mut self, path: &str) -> io::Result<()> {
+        let _ = path;
+        Ok(())
+    }
+}
+
+/// Writes files to a directory, creating it (and any subdirectories) as
+/// needed.
+#[derive(Debug, Clone)]
+pub struct DirectorySink {
+    root: PathBuf,
+}
+
+impl DirectorySink {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // Only needs `&self`, so that files can be written from several threads.
+    fn write_file(&self, path: &str, contents: &[u8]) -> io::Result<()> {
+        let path = self.root.join(path);
+        log::trace!("Writing {}", path.display());
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, contents)
+    }
+}
+
+impl ReportSink for DirectorySink {
+    fn write(&mut self, path: &str, contents: &[u8]) -> io::Result<()> {
+        self.write_file(path, contents)
+    }
+
+    #[cfg(feature = "parallel")]
+    fn write_batch(&mut self, files: &[(String, Vec<u8>)]) -> io::Result<()> {
+        use rayon::prelude::*;
+
+        files
+            .par_iter()
+            .try_for_each(|(path, contents)| self.write_file(path, contents))
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.root.join(path).is_file()
+    }
+
+    fn read(&self, path: &str) -> io::Result<Option<Vec<u8>>> {
+        match std::fs::read(self.root.join(path)) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn remove(&mut self, path: &str) -> io::Result<()> {
+        match std::fs::remove_file(self.root.join(path)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Keeps all files in memory, e.g. to serve them directly or when there is
+/// no file system (as in the browser).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemorySink {
+    pub files: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ReportSink for MemorySink {
+    fn write(&mut self, path: &str, contents: &[u8]) -> io::Result<()> {
+        self.files.insert(path.to_string(), contents.to_vec());
+        Ok(())
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn read(&self, path: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.files.get(path).cloned())
+    }
+
+    fn remove(&mut self, path: &str) -> io::Result<()> {
+        self.files.remove(path);
+        Ok(())
+    }
+}
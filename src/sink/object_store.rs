@@ -0,0 +1,75 @@
+use std::io;
+
+use super::ReportSink;
+
+/// A minimal key-value object store, such as an S3 bucket. Implement this
+/// for a client to upload reports with [`ObjectStoreSink`].
+pub trait ObjectStore {
+    fn put(&mut self, key: &str, body: &[u8], content_type: &str) -> io::Result<()>;
+
+    /// Whether `key` exists.
+    fn head(&self, key: &str) -> io::Result<bool>;
+
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// Delete `key`. Deleting a key that doesn't exist is not an error.
+    fn delete(&mut self, key: &str) -> io::Result<()>;
+}
+
+/// The content type of a report file, by extension.
+pub fn content_type(path: &str) -> &'static str {
+    match path.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("json") => "application/json",
+        Some("svg") => "image/svg+xml",
+        Some("xml") => "application/xml",
+        Some("txt" | "csv") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Writes each file as an object, with `prefix` prepended to its key.
+#[derive(Debug)]
+pub struct ObjectStoreSink<S> {
+    store: S,
+    prefix: String,
+}
+
+impl<S: ObjectStore> ObjectStoreSink<S> {
+    pub fn new(store: S, prefix: impl Into<String>) -> Self {
+        Self {
+            store,
+            prefix: prefix.into(),
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+
+    fn key(&self, path: &str) -> String {
+        format!("{}{path}", self.prefix)
+    }
+}
+
+impl<S: ObjectStore> ReportSink for ObjectStoreSink<S> {
+    fn write(&mut self, path: &str, contents: &[u8]) -> io::Result<()> {
+        let key = self.key(path);
+        self.store.put(&key, contents, content_type(path))
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.store.head(&self.key(path)).unwrap_or(false)
+    }
+
+    fn read(&self, path: &str) -> io::Result<Option<Vec<u8>>> {
+        self.store.get(&self.key(path))
+    }
+
+    fn remove(&mut self, path: &str) -> io::Result<()> {
+        let key = self.key(path);
+        self.store.delete(&key)
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use super::ReportSink;
+
+const LOCAL_HEADER: u32 = 0x04034b50;
+const CENTRAL_HEADER: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const VERSION: u16 = 20;
+// File names are UTF-8.
+const FLAGS: u16 = 1 << 11;
+// 1980-01-01, the earliest date a zip file can hold.
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb88320 & mask);
+        }
+    }
+    !crc
+}
+
+fn too_large() -> io::Error {
+    io::Error::other("Report is too large for a zip file without zip64 support.")
+}
+
+/// Collects all files in memory, and writes them as an (uncompressed) zip
+/// archive on [`finish`](Self::finish).
+#[derive(Debug)]
+pub struct ZipSink<W: Write> {
+    out: W,
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl<W: Write> ZipSink<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Write the archive, and return the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.files.len() > u16::MAX as usize {
+            return Err(too_large());
+        }
+
+        let mut offset = 0u32;
+        let mut central = Vec::new();
+
+        for (name, data) in &self.files {
+            let crc = crc32(data);
+            let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+            let name_len = u16::try_from(name.len()).map_err(|_| too_large())?;
+
+            let mut local = Vec::with_capacity(30 + name.len());
+            local.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+            local.extend_from_slice(&VERSION.to_le_bytes());
+            local.extend_from_slice(&FLAGS.to_le_bytes());
+            // Stored, without compression.
+            local.extend_from_slice(&0u16.to_le_bytes());
+            local.extend_from_slice(&0u16.to_le_bytes());
+            local.extend_from_slice(&DOS_DATE.to_le_bytes());
+            local.extend_from_slice(&crc.to_le_bytes());
+            local.extend_from_slice(&size.to_le_bytes());
+            local.extend_from_slice(&size.to_le_bytes());
+            local.extend_from_slice(&name_len.to_le_bytes());
+            local.extend_from_slice(&0u16.to_le_bytes());
+            local.extend_from_slice(name.as_bytes());
+
+            central.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+            central.extend_from_slice(&VERSION.to_le_bytes());
+            central.extend_from_slice(&local[4..30]);
+            // Comment length, disk number, internal and external attributes.
+            central.extend_from_slice(&[0; 10]);
+            central.extend_from_slice(&offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+
+            self.out.write_all(&local)?;
+            self.out.write_all(data)?;
+
+            offset = u32::try_from(local.len() + data.len())
+                .ok()
+                .and_then(|len| offset.checked_add(len))
+                .ok_or_else(too_large)?;
+        }
+
+        let entries = self.files.len() as u16;
+        let central_len = u32::try_from(central.len()).map_err(|_| too_large())?;
+
+        self.out.write_all(&central)?;
+        self.out
+            .write_all(&END_OF_CENTRAL_DIRECTORY.to_le_bytes())?;
+        self.out.write_all(&[0; 4])?;
+        self.out.write_all(&entries.to_le_bytes())?;
+        self.out.write_all(&entries.to_le_bytes())?;
+        self.out.write_all(&central_len.to_le_bytes())?;
+        self.out.write_all(&offset.to_le_bytes())?;
+        self.out.write_all(&0u16.to_le_bytes())?;
+        self.out.flush()?;
+
+        Ok(self.out)
+    }
+}
+
+impl<W: Write> ReportSink for ZipSink<W> {
+    fn write(&mut self, path: &str, contents: &[u8]) -> io::Result<()> {
+        self.files.insert(path.to_string(), contents.to_vec());
+        Ok(())
+    }
+
+    fn contains(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn read(&self, path: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.files.get(path).cloned())
+    }
+
+    fn remove(&mut self, path: &str) -> io::Result<()> {
+        self.files.remove(path);
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use std::io::{self, Read, Write};
+
+use crate::{input::SNAPSHOT_MAGIC, Coverage, Interner};
+
+// Bumped whenever the model changes in a way that changes its encoding.
+const SNAPSHOT_VERSION: u16 = 3;
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+impl Coverage {
+    /// Write this report as a compact binary snapshot, which is much faster
+    /// to load than the XML it came from.
+    ///
+    /// Snapshots are only meant to be read by the same version of this
+    /// crate, e.g. to cache a report between CI stages.
+    pub fn save_snapshot(&self, mut out: impl Write) -> io::Result<()> {
+        let data = postcard::to_stdvec(self).map_err(io::Error::other)?;
+
+        out.write_all(SNAPSHOT_MAGIC)?;
+        out.write_all(&SNAPSHOT_VERSION.to_le_bytes())?;
+        out.write_all(&data)
+    }
+
+    /// Load a snapshot written by [`save_snapshot`](Self::save_snapshot).
+    pub fn load_snapshot(mut input: impl Read) -> io::Result<Coverage> {
+        let mut data = Vec::new();
+        input.read_to_end(&mut data)?;
+
+        let data = data
+            .strip_prefix(SNAPSHOT_MAGIC)
+            .ok_or_else(|| invalid("Not a coverage snapshot."))?;
+
+        let (version, data) = data
+            .split_first_chunk::<2>()
+            .ok_or_else(|| invalid("Truncated coverage snapshot."))?;
+        let version = u16::from_le_bytes(*version);
+        if version != SNAPSHOT_VERSION {
+            return Err(invalid(format!(
+                "Unsupported coverage snapshot version {version}, expected {SNAPSHOT_VERSION}."
+            )));
+        }
+
+        let mut coverage: Coverage = postcard::from_bytes(data).map_err(invalid_data)?;
+        coverage.intern_names(&mut Interner::new());
+
+        Ok(coverage)
+    }
+
+    // Share the allocations of equal names again, as the parser does.
+    fn intern_names(&mut self, interner: &mut Interner) {
+        for package in &mut self.packages {
+            package.name = interner.intern(&package.name);
+
+            for class in &mut package.classes {
+                class.name = interner.intern(&class.name);
+                class.file_name = interner.intern_path(&class.file_name);
+
+                for method in &mut class.methods {
+                    method.name = interner.intern(&method.name);
+                    if let Some(signature) = &mut method.signature {
+                        *signature = interner.intern(signature);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn invalid_data(error: postcard::Error) -> io::Error {
+    invalid(format!("Invalid coverage snapshot: {error}"))
+}
@@ -0,0 +1,45 @@
+use crate::{Class, Coverage, Line, Package};
+
+impl Coverage {
+    /// Sort packages by project and name, classes by name and file name,
+    /// methods by name and signature, and lines and conditions by number, so
+    /// that reports don't depend on the order of their inputs.
+    ///
+    /// Packages without a project come first. Parsing and merging already
+    /// leave reports sorted.
+    pub fn sort(&mut self) {
+        self.packages
+            .sort_by(|a, b| (&a.project, &a.name).cmp(&(&b.project, &b.name)));
+
+        self.packages.iter_mut().for_each(Package::sort);
+    }
+}
+
+impl Package {
+    /// Sort the classes of the package like [`Coverage::sort`].
+    pub fn sort(&mut self) {
+        self.classes
+            .sort_by(|a, b| (&a.name, &a.file_name).cmp(&(&b.name, &b.file_name)));
+        self.classes.iter_mut().for_each(Class::sort);
+    }
+}
+
+impl Class {
+    /// Sort the methods and lines of the class like [`Coverage::sort`].
+    pub fn sort(&mut self) {
+        self.methods
+            .sort_by(|a, b| (&a.name, &a.signature).cmp(&(&b.name, &b.signature)));
+
+        sort_lines(&mut self.lines);
+        for method in &mut self.methods {
+            sort_lines(&mut method.lines);
+        }
+    }
+}
+
+pub(crate) fn sort_lines(lines: &mut [Line]) {
+    lines.sort_by_key(|l| l.number);
+    for line in lines {
+        line.conditions.sort_by_key(|c| c.number);
+    }
+}
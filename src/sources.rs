@@ -0,0 +1,64 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::Coverage;
+
+/// Locates class files on disk.
+///
+/// A file name is tried as-is if it is absolute, then relative to each source
+/// root in order, and finally relative to the working directory.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceResolver {
+    roots: Vec<PathBuf>,
+}
+
+impl SourceResolver {
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        Self {
+            roots: roots.into_iter().collect(),
+        }
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// The first existing candidate path for `file_name`, if any.
+    pub fn resolve(&self, file_name: &Path) -> Option<PathBuf> {
+        if file_name.is_absolute() {
+            return file_name.is_file().then(|| file_name.to_path_buf());
+        }
+
+        self.roots
+            .iter()
+            .map(|root| root.join(file_name))
+            .chain(std::iter::once(file_name.to_path_buf()))
+            .find(|candidate| candidate.is_file())
+    }
+
+    pub fn read_to_string(&self, file_name: &Path) -> io::Result<String> {
+        std::fs::read_to_string(self.locate(file_name)?)
+    }
+
+    pub fn read(&self, file_name: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(self.locate(file_name)?)
+    }
+
+    fn locate(&self, file_name: &Path) -> io::Result<PathBuf> {
+        self.resolve(file_name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Could not locate source file {}", file_name.display()),
+            )
+        })
+    }
+}
+
+impl Coverage {
+    /// A resolver for the `<source>` roots of this report.
+    pub fn source_resolver(&self) -> SourceResolver {
+        SourceResolver::new(self.sources.iter().map(|s| s.path.clone()))
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    glob::{glob_match, path_matches},
+    Class, Coverage, Package,
+};
+
+impl Coverage {
+    /// Split the report into one report per pattern, followed by one with
+    /// all remaining classes, e.g. for services that limit the size of
+    /// uploads.
+    ///
+    /// A class goes to the first pattern that matches its file name (or any
+    /// trailing part of it, e.g. `parser/**`) or the name of its package
+    /// (with `.` as the separator, e.g. `com.example.*`). Reports without any
+    /// classes are kept, so that the reports line up with `patterns`.
+    pub fn split_by_patterns(&self, patterns: &[impl AsRef<str>]) -> Vec<Coverage> {
+        let matching = |class: &Class, package: &Package| {
+            patterns.iter().position(|pattern| {
+                let pattern = pattern.as_ref();
+                path_matches(pattern, &class.file_name) || glob_match(pattern, &package.name, '.')
+            })
+        };
+
+        let mut parts = vec![Vec::new(); patterns.len() + 1];
+        for package in &self.packages {
+            let mut classes = vec![Vec::new(); parts.len()];
+            for class in &package.classes {
+                let idx = matching(class, package).unwrap_or(patterns.len());
+                classes[idx].push(class.clone());
+            }
+
+            for (part, classes) in parts.iter_mut().zip(classes) {
+                if !classes.is_empty() {
+                    part.push(Package {
+                        classes,
+                        name: package.name.clone(),
+                        line_rate: package.line_rate,
+                        branch_rate: package.branch_rate,
+                        complexity: package.complexity,
+                        project: package.project.clone(),
+                        extra_attributes: package.extra_attributes.clone(),
+                    });
+                }
+            }
+        }
+
+        parts.into_iter().map(|p| self.with_packages(p)).collect()
+    }
+
+    /// Split the report into at most `parts` reports with about the same
+    /// number of lines, keeping packages whole. Fewer reports are returned
+    /// if there are fewer packages.
+    pub fn split_evenly(&self, parts: usize) -> Vec<Coverage> {
+        let parts = parts.clamp(1, self.packages.len().max(1));
+        let size = |p: &Package| -> usize { p.classes.iter().map(|c| c.lines.len()).sum() };
+
+        // Hand out the largest packages first, each to the smallest part.
+        let mut order: Vec<_> = (0..self.packages.len()).collect();
+        order.sort_by_key(|&idx| std::cmp::Reverse(size(&self.packages[idx])));
+
+        let mut assigned: Vec<(usize, Vec<usize>)> = vec![Default::default(); parts];
+        for idx in order {
+            if let Some((lines, packages)) = assigned.iter_mut().min_by_key(|(lines, _)| *lines) {
+                *lines += size(&self.packages[idx]);
+                packages.push(idx);
+            }
+        }
+
+        assigned
+            .into_iter()
+            .filter(|(_, packages)| !packages.is_empty())
+            .map(|(_, mut packages)| {
+                // Keep the packages in the order of the report.
+                packages.sort_unstable();
+                self.with_packages(
+                    packages
+                        .into_iter()
+                        .map(|idx| self.packages[idx].clone())
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    // A report with the attributes and sources of this one, and only
+    // `packages`, with recomputed rates.
+    pub(crate) fn with_packages(&self, packages: Vec<Package>) -> Coverage {
+        let mut coverage = Coverage {
+            sources: self.sources.clone(),
+            packages,
+            complexity: self.complexity,
+            version: self.version.clone(),
+            timestamp: self.timestamp,
+            extra_attributes: self.extra_attributes.clone(),
+            min_hits: self.min_hits,
+            ..Default::default()
+        };
+        coverage.recompute_rates();
+        coverage
+    }
+}
@@ -0,0 +1,226 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    mem::size_of,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{Condition, ConditionType, Coverage, Line, RankingFilter};
+
+/// Size statistics of a [`Coverage`] model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Statistics {
+    pub packages: usize,
+    pub classes: usize,
+    pub methods: usize,
+    pub files: usize,
+    pub class_lines: usize,
+    pub method_lines: usize,
+    pub conditions: usize,
+    /// The number of lines marked as branch lines.
+    pub branch_lines: usize,
+    /// The total number of branch points, according to `condition-coverage`.
+    pub branch_points: usize,
+    /// The number of `<class>` entries that have the same name and file name
+    /// as an earlier entry.
+    pub duplicate_classes: usize,
+    /// The files with the most uncovered lines, in descending order.
+    pub largest_uncovered_files: Vec<(PathBuf, usize)>,
+    /// See [`Coverage::memory_usage_estimate`].
+    pub memory_usage: usize,
+}
+
+// Adds up the memory used by the parts of a report, counting allocations
+// that are shared between them (i.e. interned names) once.
+#[derive(Default)]
+struct MemoryUsage {
+    shared: HashSet<*const u8>,
+    bytes: usize,
+}
+
+impl MemoryUsage {
+    // The unused capacity of vectors and strings is counted as well, the
+    // bookkeeping of allocators and maps isn't.
+    fn vec<T>(&mut self, vec: &Vec<T>) {
+        self.bytes += vec.capacity() * size_of::<T>();
+    }
+
+    fn string(&mut self, string: &String) {
+        self.bytes += string.capacity();
+    }
+
+    fn shared<T: ?Sized>(&mut self, value: &Arc<T>, len: usize) {
+        // The reference counts come before the value.
+        if self.shared.insert(Arc::as_ptr(value).cast()) {
+            self.bytes += 2 * size_of::<usize>() + len;
+        }
+    }
+
+    fn extra(&mut self, attributes: &BTreeMap<String, String>) {
+        for (name, value) in attributes {
+            self.bytes += size_of::<(String, String)>();
+            self.string(name);
+            self.string(value);
+        }
+    }
+
+    fn lines(&mut self, lines: &Vec<Line>) {
+        self.vec(lines);
+        for line in lines {
+            self.vec(&line.conditions);
+            if let Some(coverage) = &line.condition_coverage {
+                self.string(coverage);
+            }
+            self.extra(&line.extra_attributes);
+
+            for condition in &line.conditions {
+                self.condition(condition);
+            }
+        }
+    }
+
+    fn condition(&mut self, condition: &Condition) {
+        if let ConditionType::Other(r#type) = &condition.r#type {
+            self.string(r#type);
+        }
+        self.string(&condition.coverage);
+        self.extra(&condition.extra_attributes);
+    }
+}
+
+impl Coverage {
+    /// Collect size statistics, including the `top` files with the most
+    /// uncovered lines.
+    pub fn statistics(&self, top: usize) -> Statistics {
+        self.statistics_with(top, &RankingFilter::default())
+    }
+
+    /// Like [`Coverage::statistics`], leaving the files that `ranking`
+    /// leaves out off the list of files with the most uncovered lines.
+    pub fn statistics_with(&self, top: usize, ranking: &RankingFilter) -> Statistics {
+        let mut stats = Statistics {
+            packages: self.packages.len(),
+            ..Default::default()
+        };
+
+        let mut seen_classes = HashMap::new();
+        let mut uncovered_by_file: HashMap<&Path, usize> = HashMap::new();
+        let mut lines_by_file: HashMap<&Path, usize> = HashMap::new();
+
+        for class in self.classes() {
+            stats.classes += 1;
+            stats.methods += class.methods.len();
+            stats.class_lines += class.lines.len();
+            stats.method_lines += class.methods.iter().map(|m| m.lines.len()).sum::<usize>();
+
+            let count = seen_classes
+                .entry((&class.name, &class.file_name))
+                .or_insert(0usize);
+            if *count > 0 {
+                stats.duplicate_classes += 1;
+            }
+            *count += 1;
+
+            *lines_by_file.entry(&class.file_name).or_default() += class.lines.len();
+            let uncovered = uncovered_by_file.entry(&class.file_name).or_default();
+
+            for line in &class.lines {
+                stats.conditions += line.conditions.len();
+                stats.branch_lines += line.branch as usize;
+                stats.branch_points += line.branch_counts().map(|(_, t)| t).unwrap_or(0);
+                *uncovered += !line.is_covered(self.min_hits) as usize;
+            }
+        }
+
+        stats.files = uncovered_by_file.len();
+
+        let mut files: Vec<_> = uncovered_by_file
+            .into_iter()
+            .filter(|(path, uncovered)| *uncovered > 0 && ranking.includes(lines_by_file[path]))
+            .map(|(path, uncovered)| (path.to_path_buf(), uncovered))
+            .collect();
+        files.sort_by(|(a_path, a), (b_path, b)| b.cmp(a).then_with(|| a_path.cmp(b_path)));
+        files.truncate(top);
+        stats.largest_uncovered_files = files;
+        stats.memory_usage = self.memory_usage_estimate();
+
+        stats
+    }
+
+    /// An estimate of the memory used by this report, in bytes. Names and
+    /// file names that are shared between classes, as the parser does, are
+    /// only counted once.
+    pub fn memory_usage_estimate(&self) -> usize {
+        let mut usage = MemoryUsage {
+            bytes: size_of::<Coverage>(),
+            ..Default::default()
+        };
+
+        usage.vec(&self.sources);
+        for source in &self.sources {
+            usage.bytes += source.path.capacity();
+        }
+        usage.string(&self.version);
+        usage.extra(&self.extra_attributes);
+
+        usage.vec(&self.packages);
+        for package in &self.packages {
+            usage.shared(&package.name, package.name.len());
+            if let Some(project) = &package.project {
+                usage.string(project);
+            }
+            usage.extra(&package.extra_attributes);
+
+            usage.vec(&package.classes);
+            for class in &package.classes {
+                usage.shared(&class.name, class.name.len());
+                usage.shared(&class.file_name, class.file_name.as_os_str().len());
+                usage.extra(&class.extra_attributes);
+                usage.lines(&class.lines);
+
+                usage.vec(&class.methods);
+                for method in &class.methods {
+                    usage.shared(&method.name, method.name.len());
+                    if let Some(signature) = &method.signature {
+                        usage.shared(signature, signature.len());
+                    }
+                    usage.extra(&method.extra_attributes);
+                    usage.lines(&method.lines);
+                }
+            }
+        }
+
+        usage.bytes
+    }
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Packages:           {}", self.packages)?;
+        writeln!(f, "Classes:            {}", self.classes)?;
+        writeln!(f, "Duplicate classes:  {}", self.duplicate_classes)?;
+        writeln!(f, "Methods:            {}", self.methods)?;
+        writeln!(f, "Files:              {}", self.files)?;
+        writeln!(f, "Class lines:        {}", self.class_lines)?;
+        writeln!(f, "Method lines:       {}", self.method_lines)?;
+        writeln!(f, "Branch lines:       {}", self.branch_lines)?;
+        writeln!(f, "Branch points:      {}", self.branch_points)?;
+        writeln!(f, "Conditions:         {}", self.conditions)?;
+        let kib = self.memory_usage as f64 / 1024.0;
+        if kib < 1024.0 {
+            writeln!(f, "Memory (estimate):  {kib:.1} KiB")?;
+        } else {
+            writeln!(f, "Memory (estimate):  {:.1} MiB", kib / 1024.0)?;
+        }
+
+        if !self.largest_uncovered_files.is_empty() {
+            writeln!(f, "Largest files by uncovered lines:")?;
+            for (path, uncovered) in &self.largest_uncovered_files {
+                writeln!(f, "  {uncovered:>8}  {}", path.display())?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use std::io::{self, Write};
+
+use crate::{
+    ComponentSummary, Coverage, HistoryEntry, ReportGenerator, ReportMetadata, ReportSink,
+    RoundingPolicy,
+};
+
+/// Writes the overall coverage of a report, with the change since the
+/// previous run if there is one.
+pub struct SummaryGenerator;
+
+impl SummaryGenerator {
+    /// `rate`, followed by its change since `previous`, e.g. `84.2% (+0.6)`.
+    pub fn format_rate(rate: f64, previous: Option<f64>, rounding: &RoundingPolicy) -> String {
+        match previous {
+            Some(previous) => format!(
+                "{} ({})",
+                rounding.format_rate(rate),
+                rounding.format_change(rate - previous)
+            ),
+            None => rounding.format_rate(rate),
+        }
+    }
+
+    /// Write e.g. `Line coverage: 84.2% (+0.6), branch coverage: 50.0% (±0.0)`.
+    pub fn write_text(
+        coverage: &Coverage,
+        previous: Option<&HistoryEntry>,
+        rounding: &RoundingPolicy,
+        mut out: impl Write,
+    ) -> io::Result<()> {
+        writeln!(
+            out,
+            "Line coverage: {}, branch coverage: {}",
+            Self::format_rate(coverage.line_rate, previous.map(|p| p.line_rate), rounding),
+            Self::format_rate(
+                coverage.branch_rate,
+                previous.map(|p| p.branch_rate),
+                rounding
+            ),
+        )
+    }
+
+    /// Write a Markdown table with the line and branch coverage, e.g. for a
+    /// pull request comment or a CI job summary, preceded by the title and
+    /// other `metadata`, and followed by a table of the `components` if there
+    /// are any.
+    pub fn write_markdown(
+        coverage: &Coverage,
+        previous: Option<&HistoryEntry>,
+        metadata: &ReportMetadata,
+        components: &[ComponentSummary],
+        rounding: &RoundingPolicy,
+        mut out: impl Write,
+    ) -> io::Result<()> {
+        if let Some(title) = &metadata.title {
+            writeln!(out, "### {title}\n")?;
+        }
+
+        let details: Vec<_> = metadata
+            .details()
+            .map(|(key, value)| match key {
+                "tag" => format!("Tag: `{value}`"),
+                "commit" => format!("Commit: `{value}`"),
+                "branch" => format!("Branch: `{value}`"),
+                _ => format!("[Build]({value})"),
+            })
+            .collect();
+        if !details.is_empty() {
+            writeln!(out, "{}\n", details.join(" · "))?;
+        }
+
+        let change = |rate: f64, previous: Option<f64>| {
+            previous.map_or_else(|| "-".to_string(), |p| rounding.format_change(rate - p))
+        };
+
+        writeln!(out, "| | Covered | Total | Coverage | Change |")?;
+        writeln!(out, "|---|---:|---:|---:|---:|")?;
+        writeln!(
+            out,
+            "| Lines | {} | {} | {} | {} |",
+            coverage.lines_covered,
+            coverage.lines_valid,
+            rounding.format_rate(coverage.line_rate),
+            change(coverage.line_rate, previous.map(|p| p.line_rate))
+        )?;
+        writeln!(
+            out,
+            "| Branches | {} | {} | {} | {} |",
+            coverage.branches_covered,
+            coverage.branches_valid,
+            rounding.format_rate(coverage.branch_rate),
+            change(coverage.branch_rate, previous.map(|p| p.branch_rate))
+        )?;
+
+        if components.is_empty() {
+            return Ok(());
+        }
+
+        let format =
+            |rate: Option<f64>| rate.map_or_else(|| "-".to_string(), |r| rounding.format_rate(r));
+
+        writeln!(
+            out,
+            "\n| Component | Files | Line coverage | Branch coverage |"
+        )?;
+        writeln!(out, "|---|---:|---:|---:|")?;
+        for summary in components {
+            writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                summary.component.as_deref().unwrap_or("(no component)"),
+                summary.files,
+                format(summary.rates.line_rate),
+                format(summary.rates.branch_rate)
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a JSON document with the totals of `coverage`, `metadata`, the
+    /// `previous` run (`null` if there is none), and the `components` (left
+    /// out if there are none).
+    #[cfg(feature = "json")]
+    pub fn write_json(
+        coverage: &Coverage,
+        previous: Option<&HistoryEntry>,
+        metadata: &ReportMetadata,
+        components: &[ComponentSummary],
+        mut out: impl Write,
+    ) -> io::Result<()> {
+        #[derive(serde::Serialize)]
+        struct Component<'a> {
+            component: Option<&'a str>,
+            files: usize,
+            lines_valid: usize,
+            lines_covered: usize,
+            branches_valid: usize,
+            branches_covered: usize,
+            line_rate: Option<f64>,
+            branch_rate: Option<f64>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Summary<'a> {
+            metadata: &'a ReportMetadata,
+            lines_covered: usize,
+            lines_valid: usize,
+            line_rate: f64,
+            branches_covered: usize,
+            branches_valid: usize,
+            branch_rate: f64,
+            previous: Option<&'a HistoryEntry>,
+            #[serde(skip_serializing_if = "Vec::is_empty")]
+            components: Vec<Component<'a>>,
+        }
+
+        let summary = Summary {
+            metadata,
+            lines_covered: coverage.lines_covered,
+            lines_valid: coverage.lines_valid,
+            line_rate: coverage.line_rate,
+            branches_covered: coverage.branches_covered,
+            branches_valid: coverage.branches_valid,
+            branch_rate: coverage.branch_rate,
+            previous,
+            components: components
+                .iter()
+                .map(|s| Component {
+                    component: s.component.as_deref(),
+                    files: s.files,
+                    lines_valid: s.rates.lines_valid,
+                    lines_covered: s.rates.lines_covered,
+                    branches_valid: s.rates.branches_valid,
+                    branches_covered: s.rates.branches_covered,
+                    line_rate: s.rates.line_rate,
+                    branch_rate: s.rates.branch_rate,
+                })
+                .collect(),
+        };
+
+        serde_json::to_writer_pretty(&mut out, &summary).map_err(io::Error::other)?;
+        writeln!(out)
+    }
+}
+
+/// The summary of [`SummaryGenerator::write_markdown`] as a
+/// [`ReportGenerator`] named `MarkdownSummary`, written to `summary.md`.
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownSummaryReport {
+    pub previous: Option<HistoryEntry>,
+    pub metadata: ReportMetadata,
+    pub components: Vec<ComponentSummary>,
+    pub rounding: RoundingPolicy,
+}
+
+impl ReportGenerator for MarkdownSummaryReport {
+    fn name(&self) -> &str {
+        "MarkdownSummary"
+    }
+
+    fn generate(&self, coverage: &Coverage, sink: &mut dyn ReportSink) -> io::Result<()> {
+        let mut markdown = Vec::new();
+        SummaryGenerator::write_markdown(
+            coverage,
+            self.previous.as_ref(),
+            &self.metadata,
+            &self.components,
+            &self.rounding,
+            &mut markdown,
+        )?;
+        sink.write("summary.md", &markdown)
+    }
+}
@@ -0,0 +1,93 @@
+use std::io::{self, Write};
+
+use crate::Coverage;
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '|' => escaped.push_str("||"),
+            '\'' => escaped.push_str("|'"),
+            '\n' => escaped.push_str("|n"),
+            '\r' => escaped.push_str("|r"),
+            '[' => escaped.push_str("|["),
+            ']' => escaped.push_str("|]"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn percentage(covered: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        covered as f64 * 100.0 / total as f64
+    }
+}
+
+/// Writes TeamCity `buildStatisticValue` service messages, so that TeamCity
+/// can chart the coverage of a build.
+pub struct TeamCityGenerator;
+
+impl TeamCityGenerator {
+    pub fn write(coverage: &Coverage, mut out: impl Write) -> io::Result<()> {
+        let rates = coverage.computed_rates();
+
+        let classes_total = coverage.classes().count();
+        let classes_covered = coverage
+            .classes()
+            .filter(|c| c.lines.iter().any(|l| l.is_covered(coverage.min_hits)))
+            .count();
+
+        let methods_total = coverage.methods().count();
+        let methods_covered = coverage
+            .methods()
+            .filter(|m| m.lines.iter().any(|l| l.is_covered(coverage.min_hits)))
+            .count();
+
+        let statistics = [
+            ("CodeCoverageAbsCCovered", classes_covered.to_string()),
+            ("CodeCoverageAbsCTotal", classes_total.to_string()),
+            (
+                "CodeCoverageC",
+                format!("{:.2}", percentage(classes_covered, classes_total)),
+            ),
+            ("CodeCoverageAbsMCovered", methods_covered.to_string()),
+            ("CodeCoverageAbsMTotal", methods_total.to_string()),
+            (
+                "CodeCoverageM",
+                format!("{:.2}", percentage(methods_covered, methods_total)),
+            ),
+            ("CodeCoverageAbsLCovered", rates.lines_covered.to_string()),
+            ("CodeCoverageAbsLTotal", rates.lines_valid.to_string()),
+            (
+                "CodeCoverageL",
+                format!("{:.2}", percentage(rates.lines_covered, rates.lines_valid)),
+            ),
+            (
+                "CodeCoverageAbsBCovered",
+                rates.branches_covered.to_string(),
+            ),
+            ("CodeCoverageAbsBTotal", rates.branches_valid.to_string()),
+            (
+                "CodeCoverageB",
+                format!(
+                    "{:.2}",
+                    percentage(rates.branches_covered, rates.branches_valid)
+                ),
+            ),
+        ];
+
+        for (key, value) in statistics {
+            writeln!(
+                out,
+                "##teamcity[buildStatisticValue key='{}' value='{}']",
+                escape(key),
+                escape(&value)
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use std::time::{Duration, SystemTime};
+
+use crate::Coverage;
+
+// Timestamps above this are interpreted as milliseconds since the epoch. As
+// seconds, it would be a date in the year 5138; as milliseconds it is early
+// 1973, before any coverage tool we know of existed.
+const MILLISECONDS_THRESHOLD: u64 = 100_000_000_000;
+
+impl Coverage {
+    /// The timestamp as a duration since the Unix epoch, accounting for
+    /// tools that write milliseconds instead of seconds.
+    ///
+    /// Returns `None` if no timestamp was present.
+    pub fn timestamp_duration(&self) -> Option<Duration> {
+        match self.timestamp {
+            0 => None,
+            ts if ts >= MILLISECONDS_THRESHOLD => Some(Duration::from_millis(ts)),
+            ts => Some(Duration::from_secs(ts)),
+        }
+    }
+
+    /// When this report was generated, if it has a timestamp.
+    pub fn generated_at(&self) -> Option<SystemTime> {
+        SystemTime::UNIX_EPOCH.checked_add(self.timestamp_duration()?)
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn generated_at_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let duration = self.timestamp_duration()?;
+        let millis = i64::try_from(duration.as_millis()).ok()?;
+        chrono::DateTime::from_timestamp_millis(millis)
+    }
+}
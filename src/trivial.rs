@@ -0,0 +1,134 @@
+use std::{
+    collections::BTreeSet,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{ComputedRates, Coverage};
+
+/// Heuristics for lines that some tools report as executable, but that hold
+/// no code worth covering, e.g. closing braces or a lone `else`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrivialLines {
+    /// Lines that are empty or only whitespace.
+    pub blank: bool,
+    /// Lines that consist of exactly one of these (ignoring surrounding
+    /// whitespace).
+    pub tokens: Vec<String>,
+    /// Lines that start with one of these comment markers.
+    pub comments: Vec<String>,
+}
+
+impl Default for TrivialLines {
+    fn default() -> Self {
+        let tokens = [
+            "{",
+            "}",
+            "};",
+            "})",
+            "});",
+            "}}",
+            ")",
+            ");",
+            "]",
+            "];",
+            "else",
+            "else {",
+            "} else",
+            "} else {",
+            "try",
+            "try {",
+            "finally",
+            "finally {",
+            "} finally {",
+            "do",
+            "do {",
+            "end",
+            "begin",
+        ];
+
+        Self {
+            blank: true,
+            tokens: tokens.into_iter().map(String::from).collect(),
+            comments: vec!["//".into(), "/*".into(), "#".into()],
+        }
+    }
+}
+
+impl TrivialLines {
+    pub fn is_trivial(&self, line: &str) -> bool {
+        let line = line.trim();
+
+        (self.blank && line.is_empty())
+            || self.tokens.iter().any(|t| t == line)
+            || (!line.is_empty() && self.comments.iter().any(|c| line.starts_with(c.as_str())))
+    }
+
+    /// The (1-based) numbers of the trivial lines in `source`.
+    pub fn trivial_lines(&self, source: &str) -> BTreeSet<usize> {
+        source
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| self.is_trivial(line))
+            .map(|(idx, _)| idx + 1)
+            .collect()
+    }
+}
+
+/// The coverage of a report with and without its trivial lines.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EffectiveCoverage {
+    /// The rates of all lines.
+    pub raw: ComputedRates,
+    /// The rates without the trivial lines.
+    pub effective: ComputedRates,
+    /// The number of trivial lines left out.
+    pub trivial_lines: usize,
+    /// Files that could not be loaded, whose lines all count as non-trivial.
+    pub unreadable: Vec<PathBuf>,
+}
+
+impl Coverage {
+    /// Compute the coverage without the lines that `trivial` considers
+    /// trivial, next to the raw coverage. The report itself is left as it
+    /// is.
+    ///
+    /// `load_source` is called once per file to load its contents. Lines are
+    /// counted once per file, see [`Coverage::unique_lines`].
+    pub fn effective_coverage(
+        &self,
+        trivial: &TrivialLines,
+        mut load_source: impl FnMut(&Path) -> io::Result<String>,
+    ) -> EffectiveCoverage {
+        let mut unreadable = Vec::new();
+        let mut effective = Vec::new();
+        let mut trivial_lines = 0;
+
+        let files = self.file_coverage();
+        for file in &files {
+            let lines = match load_source(file.path) {
+                Ok(source) => Some(trivial.trivial_lines(&source)),
+                Err(e) => {
+                    log::debug!("Could not read {}: {e}", file.path.display());
+                    unreadable.push(file.path.to_path_buf());
+                    None
+                }
+            };
+
+            for line in &file.lines {
+                if lines.as_ref().is_some_and(|l| l.contains(&line.number)) {
+                    trivial_lines += 1;
+                } else {
+                    effective.push(line.as_ref());
+                }
+            }
+        }
+
+        EffectiveCoverage {
+            raw: self.computed_rates(),
+            effective: ComputedRates::from_lines(effective, self.min_hits),
+            trivial_lines,
+            unreadable,
+        }
+    }
+}
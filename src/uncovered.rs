@@ -0,0 +1,131 @@
+use std::{
+    fmt,
+    io::{self, Write},
+    ops::RangeInclusive,
+    path::PathBuf,
+};
+
+use crate::Coverage;
+
+/// The uncovered lines of a single file, as ranges of line numbers.
+///
+/// A range spans all uncovered lines that are not separated by a covered
+/// line, so lines that are not tracked at all (blank lines, comments) do not
+/// split a range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UncoveredFile {
+    pub path: PathBuf,
+    pub ranges: Vec<RangeInclusive<usize>>,
+}
+
+impl fmt::Display for UncoveredFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.path.display())?;
+
+        for (idx, range) in self.ranges.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+
+            if range.start() == range.end() {
+                write!(f, "{}", range.start())?;
+            } else {
+                write!(f, "{}-{}", range.start(), range.end())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Coverage {
+    /// The uncovered line ranges of every file with at least one uncovered
+    /// line, in file order. A line is uncovered if none of the classes in its
+    /// file have hits for it.
+    pub fn uncovered_files(&self) -> Vec<UncoveredFile> {
+        self.files()
+            .filter_map(|file| {
+                let lines = file.unique_lines();
+                let ranges = uncovered_ranges(
+                    lines
+                        .iter()
+                        .map(|l| (l.number, !l.is_covered(self.min_hits))),
+                );
+
+                (!ranges.is_empty()).then(|| UncoveredFile {
+                    path: file.path.to_path_buf(),
+                    ranges,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Group the line numbers that are `uncovered` into ranges, given all tracked
+/// lines of a file in ascending order.
+pub(crate) fn uncovered_ranges(
+    lines: impl IntoIterator<Item = (usize, bool)>,
+) -> Vec<RangeInclusive<usize>> {
+    let mut ranges: Vec<RangeInclusive<usize>> = Vec::new();
+    let mut previous_uncovered = false;
+
+    for (number, uncovered) in lines {
+        if !uncovered {
+            previous_uncovered = false;
+            continue;
+        }
+
+        match ranges.last_mut() {
+            Some(range) if previous_uncovered => *range = *range.start()..=number,
+            _ => ranges.push(number..=number),
+        }
+        previous_uncovered = true;
+    }
+
+    ranges
+}
+
+/// Lists the uncovered line ranges per file, e.g.
+/// `src/foo.rs: 10-14, 27, 90-102`.
+pub struct UncoveredLinesGenerator;
+
+impl UncoveredLinesGenerator {
+    /// Write one line per file with uncovered lines.
+    pub fn write_text(coverage: &Coverage, mut out: impl Write) -> io::Result<()> {
+        for file in coverage.uncovered_files() {
+            writeln!(out, "{file}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a JSON document of the form
+    /// `{"files": [{"path": "src/foo.rs", "ranges": [[10, 14], [27, 27]]}]}`.
+    #[cfg(feature = "json")]
+    pub fn write_json(coverage: &Coverage, mut out: impl Write) -> io::Result<()> {
+        #[derive(serde::Serialize)]
+        struct File {
+            path: String,
+            ranges: Vec<[usize; 2]>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Report {
+            files: Vec<File>,
+        }
+
+        let report = Report {
+            files: coverage
+                .uncovered_files()
+                .into_iter()
+                .map(|file| File {
+                    path: file.path.to_string_lossy().to_string(),
+                    ranges: file.ranges.iter().map(|r| [*r.start(), *r.end()]).collect(),
+                })
+                .collect(),
+        };
+
+        serde_json::to_writer(&mut out, &report).map_err(io::Error::other)?;
+        writeln!(out)
+    }
+}
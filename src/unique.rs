@@ -0,0 +1,98 @@
+use std::{borrow::Cow, collections::BTreeMap, path::Path};
+
+use crate::{merge, Class, ComputedRates, Coverage, FileSummary, Line};
+
+/// The lines of `classes` and their methods by file name and line number, so
+/// that every line is only counted once. Lines that are reported more than
+/// once are combined like [`Coverage::consolidate`] does.
+pub(crate) fn unique_lines<'a>(
+    classes: impl IntoIterator<Item = &'a Class>,
+) -> BTreeMap<(&'a Path, usize), Cow<'a, Line>> {
+    let mut lines: BTreeMap<(&Path, usize), Cow<Line>> = BTreeMap::new();
+
+    for class in classes {
+        let file = &*class.file_name;
+        let method_lines = class.methods.iter().flat_map(|m| &m.lines);
+
+        for line in class.lines.iter().chain(method_lines) {
+            match lines.get_mut(&(file, line.number)) {
+                Some(existing) => merge::consolidate_line(existing.to_mut(), line.clone()),
+                None => {
+                    lines.insert((file, line.number), Cow::Borrowed(line));
+                }
+            }
+        }
+    }
+
+    lines
+}
+
+/// The rates of the [unique lines](unique_lines) of `classes`, only counting
+/// lines hit at least `min_hits` times as covered.
+pub(crate) fn unique_rates<'a>(
+    classes: impl IntoIterator<Item = &'a Class>,
+    min_hits: u64,
+) -> ComputedRates {
+    ComputedRates::from_lines(unique_lines(classes).values().map(AsRef::as_ref), min_hits)
+}
+
+/// The lines of a single file, combined from all classes and methods that
+/// report them.
+#[derive(Debug, Clone)]
+pub struct FileCoverage<'a> {
+    pub path: &'a Path,
+    /// Every line once, in ascending order.
+    pub lines: Vec<Cow<'a, Line>>,
+    /// See [`Coverage::min_hits`].
+    pub min_hits: u64,
+}
+
+impl FileCoverage<'_> {
+    pub fn rates(&self) -> ComputedRates {
+        ComputedRates::from_lines(self.lines.iter().map(AsRef::as_ref), self.min_hits)
+    }
+}
+
+impl<'a> FileSummary<'a> {
+    /// The lines of this file, with every line number once. See
+    /// [`Coverage::unique_lines`].
+    pub fn unique_lines(&self) -> Vec<Cow<'a, Line>> {
+        unique_lines(self.classes.iter().copied())
+            .into_values()
+            .collect()
+    }
+}
+
+impl Coverage {
+    /// Every line of the report once, by file name, in order of file name
+    /// and line number.
+    ///
+    /// Unlike [`Coverage::lines`], this includes lines that are only reported
+    /// for a method, and combines lines that several classes or methods
+    /// report for the same file (keeping the maximum hits and the best branch
+    /// coverage). Rates are computed from these lines.
+    pub fn unique_lines(&self) -> impl Iterator<Item = (&Path, Cow<'_, Line>)> {
+        unique_lines(self.classes())
+            .into_iter()
+            .map(|((file, _), line)| (file, line))
+    }
+
+    /// The [unique lines](Self::unique_lines) of every file, in order of file
+    /// name.
+    pub fn file_coverage(&self) -> Vec<FileCoverage<'_>> {
+        let mut files: Vec<FileCoverage> = Vec::new();
+
+        for (path, line) in self.unique_lines() {
+            match files.last_mut() {
+                Some(file) if file.path == path => file.lines.push(line),
+                _ => files.push(FileCoverage {
+                    path,
+                    lines: vec![line],
+                    min_hits: self.min_hits,
+                }),
+            }
+        }
+
+        files
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    path::Path,
+};
+
+use serde::Serialize;
+
+use crate::{Coverage, GitInfo};
+
+/// What identifies an upload to Coveralls.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverallsOptions {
+    /// The secret token of the repository. Not needed for some CI services.
+    pub repo_token: Option<String>,
+    /// The CI service, e.g. `github`.
+    pub service_name: Option<String>,
+    /// The id of the CI job, e.g. `$GITHUB_RUN_ID`.
+    pub service_job_id: Option<String>,
+    pub git: Option<GitInfo>,
+}
+
+/// Writes the JSON payload of the Coveralls jobs API.
+pub struct CoverallsGenerator;
+
+impl CoverallsGenerator {
+    /// Write the payload for `coverage`, with one source file per file name.
+    ///
+    /// Coveralls checks the MD5 digest of every source file, so
+    /// `load_source` is called once per file to read it. Files that can't be
+    /// read are sent without a digest. As for LCOV, the first branches of a
+    /// line are written as taken, and lines hit fewer than
+    /// [`Coverage::min_hits`] times as not hit.
+    pub fn write(
+        coverage: &Coverage,
+        options: &CoverallsOptions,
+        mut load_source: impl FnMut(&Path) -> io::Result<Vec<u8>>,
+        mut out: impl Write,
+    ) -> io::Result<()> {
+        #[derive(Serialize)]
+        struct Head<'a> {
+            id: &'a str,
+            author_name: &'a str,
+            author_email: &'a str,
+            committer_name: &'a str,
+            committer_email: &'a str,
+            message: &'a str,
+        }
+
+        #[derive(Serialize)]
+        struct Remote<'a> {
+            name: &'a str,
+            url: &'a str,
+        }
+
+        #[derive(Serialize)]
+        struct Git<'a> {
+            head: Head<'a>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            branch: Option<&'a str>,
+            remotes: Vec<Remote<'a>>,
+        }
+
+        #[derive(Serialize)]
+        struct SourceFile {
+            name: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            source_digest: Option<String>,
+            // The hits of every line, `null` for lines without code.
+            coverage: Vec<Option<u64>>,
+            // Flattened `[line, block, branch, hits]` quadruples.
+            branches: Vec<u64>,
+        }
+
+        #[derive(Serialize)]
+        struct Job<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            repo_token: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            service_name: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            service_job_id: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            git: Option<Git<'a>>,
+            source_files: Vec<SourceFile>,
+        }
+
+        let source_files = coverage
+            .file_coverage()
+            .into_iter()
+            .map(|file| {
+                let last = file.lines.iter().map(|l| l.number).max().unwrap_or(0);
+                let mut lines = vec![None; last];
+                let mut branches = Vec::new();
+
+                for line in &file.lines {
+                    if let Some(hits) = line.number.checked_sub(1).map(|idx| &mut lines[idx]) {
+                        *hits = Some(line.covered_hits(coverage.min_hits));
+                    }
+
+                    let (covered, total) = line.branch_counts().unwrap_or_default();
+                    for branch in 0..total {
+                        let taken = line.is_covered(coverage.min_hits) && branch < covered;
+                        branches.extend([line.number as u64, 0, branch as u64, taken as u64]);
+                    }
+                }
+
+                let source_digest = match load_source(file.path) {
+                    Ok(source) => Some(format!("{:x}", md5::compute(source))),
+                    Err(e) => {
+                        log::debug!("Could not read {}: {e}", file.path.display());
+                        None
+                    }
+                };
+
+                SourceFile {
+                    name: file.path.to_string_lossy().replace('\\', "/"),
+                    source_digest,
+                    coverage: lines,
+                    branches,
+                }
+            })
+            .collect();
+
+        let job = Job {
+            repo_token: options.repo_token.as_deref(),
+            service_name: options.service_name.as_deref(),
+            service_job_id: options.service_job_id.as_deref(),
+            git: options.git.as_ref().map(|git| Git {
+                head: Head {
+                    id: &git.commit,
+                    author_name: &git.author_name,
+                    author_email: &git.author_email,
+                    committer_name: &git.committer_name,
+                    committer_email: &git.committer_email,
+                    message: &git.message,
+                },
+                branch: git.branch.as_deref(),
+                remotes: git
+                    .remotes
+                    .iter()
+                    .map(|(name, url)| Remote { name, url })
+                    .collect(),
+            }),
+            source_files,
+        };
+
+        serde_json::to_writer(&mut out, &job).map_err(io::Error::other)?;
+        writeln!(out)
+    }
+}
+
+/// Writes Codecov's JSON coverage format.
+pub struct CodecovGenerator;
+
+impl CodecovGenerator {
+    /// Write `{"coverage": {"file": {"1": 3, "2": 0, "5": "1/2"}}}`, with the
+    /// hits of every line, or the covered out of all branches for partially
+    /// covered branch lines. Lines hit fewer than [`Coverage::min_hits`]
+    /// times are written with 0 hits.
+    pub fn write(coverage: &Coverage, mut out: impl Write) -> io::Result<()> {
+        #[derive(Serialize)]
+        #[serde(untagged)]
+        enum LineCoverage {
+            Hits(u64),
+            Partial(String),
+        }
+
+        #[derive(Serialize)]
+        struct Report {
+            coverage: BTreeMap<String, BTreeMap<String, LineCoverage>>,
+        }
+
+        let coverage = coverage
+            .file_coverage()
+            .into_iter()
+            .map(|file| {
+                let lines = file
+                    .lines
+                    .iter()
+                    .map(|line| {
+                        let coverage = match line.branch_counts() {
+                            Some((covered, total))
+                                if line.is_covered(coverage.min_hits) && covered < total =>
+                            {
+                                LineCoverage::Partial(format!("{covered}/{total}"))
+                            }
+                            _ => LineCoverage::Hits(line.covered_hits(coverage.min_hits)),
+                        };
+                        (line.number.to_string(), coverage)
+                    })
+                    .collect();
+
+                (file.path.to_string_lossy().replace('\\', "/"), lines)
+            })
+            .collect();
+
+        serde_json::to_writer(&mut out, &Report { coverage }).map_err(io::Error::other)?;
+        writeln!(out)
+    }
+}
@@ -0,0 +1,391 @@
+use std::{fmt, io::BufRead};
+
+use quick_xml::{events::BytesStart, Reader};
+
+use crate::FilteredEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Occurs {
+    Once,
+    Optional,
+    Many,
+}
+
+struct ElementRule {
+    name: &'static str,
+    children: &'static [(&'static str, Occurs)],
+    required_attributes: &'static [&'static str],
+    text: bool,
+}
+
+// The element and attribute rules of `coverage-04.dtd`.
+static RULES: &[ElementRule] = &[
+    ElementRule {
+        name: "coverage",
+        children: &[("sources", Occurs::Optional), ("packages", Occurs::Once)],
+        required_attributes: &[
+            "line-rate",
+            "branch-rate",
+            "lines-covered",
+            "lines-valid",
+            "branches-covered",
+            "branches-valid",
+            "complexity",
+            "version",
+            "timestamp",
+        ],
+        text: false,
+    },
+    ElementRule {
+        name: "sources",
+        children: &[("source", Occurs::Many)],
+        required_attributes: &[],
+        text: false,
+    },
+    ElementRule {
+        name: "source",
+        children: &[],
+        required_attributes: &[],
+        text: true,
+    },
+    ElementRule {
+        name: "packages",
+        children: &[("package", Occurs::Many)],
+        required_attributes: &[],
+        text: false,
+    },
+    ElementRule {
+        name: "package",
+        children: &[("classes", Occurs::Once)],
+        required_attributes: &["name", "line-rate", "branch-rate", "complexity"],
+        text: false,
+    },
+    ElementRule {
+        name: "classes",
+        children: &[("class", Occurs::Many)],
+        required_attributes: &[],
+        text: false,
+    },
+    ElementRule {
+        name: "class",
+        children: &[("methods", Occurs::Once), ("lines", Occurs::Once)],
+        required_attributes: &["name", "filename", "line-rate", "branch-rate", "complexity"],
+        text: false,
+    },
+    ElementRule {
+        name: "methods",
+        children: &[("method", Occurs::Many)],
+        required_attributes: &[],
+        text: false,
+    },
+    ElementRule {
+        name: "method",
+        children: &[("lines", Occurs::Once)],
+        required_attributes: &[
+            "name",
+            "signature",
+            "line-rate",
+            "branch-rate",
+            "complexity",
+        ],
+        text: false,
+    },
+    ElementRule {
+        name: "lines",
+        children: &[("line", Occurs::Many)],
+        required_attributes: &[],
+        text: false,
+    },
+    ElementRule {
+        name: "line",
+        children: &[("conditions", Occurs::Many)],
+        required_attributes: &["number", "hits"],
+        text: false,
+    },
+    ElementRule {
+        name: "conditions",
+        children: &[("condition", Occurs::Many)],
+        required_attributes: &[],
+        text: false,
+    },
+    ElementRule {
+        name: "condition",
+        children: &[],
+        required_attributes: &["number", "type", "coverage"],
+        text: false,
+    },
+];
+
+fn rule(name: &str) -> Option<&'static ElementRule> {
+    RULES.iter().find(|r| r.name == name)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DtdViolationKind {
+    /// The root element is not `coverage`.
+    InvalidRoot { element: String },
+    /// An element appeared that is not allowed at this position.
+    UnexpectedElement { element: String },
+    /// A required child element is missing.
+    MissingElement { element: String },
+    /// A required attribute is missing.
+    MissingAttribute { attribute: String },
+    /// Text content in an element that may not contain text.
+    UnexpectedText,
+    /// The document is not well-formed XML. Validation stops here.
+    Malformed(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DtdViolation {
+    /// The path of the element the violation occurred in, e.g.
+    /// `/coverage/packages/package`.
+    pub path: String,
+    /// The byte position in the input at which the violation was detected.
+    pub position: u64,
+    pub kind: DtdViolationKind,
+}
+
+impl fmt::Display for DtdViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (byte {}): ", self.path, self.position)?;
+
+        match &self.kind {
+            DtdViolationKind::InvalidRoot { element } => {
+                write!(f, "root element must be `coverage`, found `{element}`")
+            }
+            DtdViolationKind::UnexpectedElement { element } => {
+                write!(f, "element `{element}` is not allowed here")
+            }
+            DtdViolationKind::MissingElement { element } => {
+                write!(f, "missing required element `{element}`")
+            }
+            DtdViolationKind::MissingAttribute { attribute } => {
+                write!(f, "missing required attribute `{attribute}`")
+            }
+            DtdViolationKind::UnexpectedText => write!(f, "text content is not allowed here"),
+            DtdViolationKind::Malformed(msg) => write!(f, "malformed XML: {msg}"),
+        }
+    }
+}
+
+struct OpenElement {
+    name: String,
+    rule: Option<&'static ElementRule>,
+    // Index into `rule.children` of the child we are currently matching.
+    position: usize,
+    // Whether the child at `position` has been seen at least once.
+    seen_current: bool,
+}
+
+/// A validator that checks a document against the structure rules of the
+/// Cobertura DTD (element order, allowed children, required attributes).
+///
+/// Unlike [`Parser`](crate::Parser), it does not stop at the first problem
+/// but collects every violation it finds.
+#[derive(Default)]
+pub struct DtdValidator {
+    stack: Vec<OpenElement>,
+    violations: Vec<DtdViolation>,
+    seen_root: bool,
+}
+
+impl DtdValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn validate<R>(reader: &mut Reader<R>) -> Vec<DtdViolation>
+    where
+        R: BufRead,
+    {
+        let mut validator = Self::new();
+        let mut buf = Vec::new();
+
+        loop {
+            buf.clear();
+            let position = reader.buffer_position();
+            let event = match reader.read_event_into(&mut buf) {
+                Ok(event) => event,
+                Err(e) => {
+                    validator.push(position, DtdViolationKind::Malformed(e.to_string()));
+                    break;
+                }
+            };
+
+            if event == quick_xml::events::Event::Eof {
+                break;
+            }
+
+            if let Some(event) = FilteredEvent::try_from(event) {
+                validator.consume_event(&event, position);
+            }
+        }
+
+        validator.finish()
+    }
+
+    fn path(&self) -> String {
+        if self.stack.is_empty() {
+            return "/".to_string();
+        }
+
+        self.stack.iter().fold(String::new(), |mut path, e| {
+            path.push('/');
+            path.push_str(&e.name);
+            path
+        })
+    }
+
+    fn push(&mut self, position: u64, kind: DtdViolationKind) {
+        let path = self.path();
+        self.violations.push(DtdViolation {
+            path,
+            position,
+            kind,
+        });
+    }
+
+    pub fn consume_event(&mut self, event: &FilteredEvent, position: u64) {
+        match event {
+            FilteredEvent::Start(start) => self.start(start, position),
+            FilteredEvent::AttributesOnly(start) => {
+                self.start(start, position);
+                self.end(position);
+            }
+            FilteredEvent::End(_) => self.end(position),
+            FilteredEvent::Text(_) => {
+                let allowed = self
+                    .stack
+                    .last()
+                    .is_some_and(|e| e.rule.is_none_or(|r| r.text));
+
+                if !allowed {
+                    self.push(position, DtdViolationKind::UnexpectedText);
+                }
+            }
+        }
+    }
+
+    fn start(&mut self, start: &BytesStart, position: u64) {
+        let name = String::from_utf8_lossy(start.local_name().as_ref()).to_string();
+
+        match self.stack.last_mut() {
+            None => {
+                if self.seen_root || name != "coverage" {
+                    self.push(
+                        position,
+                        DtdViolationKind::InvalidRoot {
+                            element: name.clone(),
+                        },
+                    );
+                }
+                self.seen_root = true;
+            }
+            Some(parent) => {
+                if let Some(parent_rule) = parent.rule {
+                    match Self::advance(parent, parent_rule, &name) {
+                        Some(skipped) => {
+                            for element in skipped {
+                                let element = element.to_string();
+                                self.push(position, DtdViolationKind::MissingElement { element });
+                            }
+                        }
+                        None => {
+                            let element = name.clone();
+                            self.push(position, DtdViolationKind::UnexpectedElement { element });
+                        }
+                    }
+                }
+            }
+        }
+
+        let rule = rule(&name);
+
+        self.stack.push(OpenElement {
+            name,
+            rule,
+            position: 0,
+            seen_current: false,
+        });
+
+        if let Some(rule) = rule {
+            for attribute in rule.required_attributes {
+                let present = start
+                    .attributes()
+                    .flatten()
+                    .any(|a| a.key.local_name().as_ref() == attribute.as_bytes());
+
+                if !present {
+                    self.push(
+                        position,
+                        DtdViolationKind::MissingAttribute {
+                            attribute: attribute.to_string(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    // Try to match the child `name` against the content model of `parent`,
+    // advancing the position in the model. Returns the required children that
+    // were skipped over, or `None` if the child is not allowed at this point.
+    fn advance(
+        parent: &mut OpenElement,
+        rule: &ElementRule,
+        name: &str,
+    ) -> Option<Vec<&'static str>> {
+        let mut position = parent.position;
+        let mut seen_current = parent.seen_current;
+        let mut skipped = Vec::new();
+
+        while let Some((child, occurs)) = rule.children.get(position) {
+            if *child == name && !(seen_current && *occurs != Occurs::Many) {
+                parent.position = position;
+                parent.seen_current = true;
+                return Some(skipped);
+            }
+
+            if *occurs == Occurs::Once && !seen_current {
+                skipped.push(*child);
+            }
+
+            position += 1;
+            seen_current = false;
+        }
+
+        None
+    }
+
+    fn end(&mut self, position: u64) {
+        if let Some(OpenElement {
+            rule: Some(rule),
+            position: child_position,
+            seen_current,
+            ..
+        }) = self.stack.last()
+        {
+            let missing: Vec<_> = rule
+                .children
+                .iter()
+                .enumerate()
+                .filter(|(idx, (_, occurs))| {
+                    *occurs == Occurs::Once
+                        && (*idx > *child_position || (*idx == *child_position && !seen_current))
+                })
+                .map(|(_, (child, _))| child.to_string())
+                .collect();
+
+            for element in missing {
+                self.push(position, DtdViolationKind::MissingElement { element });
+            }
+        }
+
+        self.stack.pop();
+    }
+
+    pub fn finish(self) -> Vec<DtdViolation> {
+        self.violations
+    }
+}
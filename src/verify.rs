@@ -0,0 +1,181 @@
+use std::{fmt, sync::Arc};
+
+use crate::{ComputedRates, Coverage};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rate {
+    Line,
+    Branch,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scope {
+    Coverage,
+    Package {
+        name: Arc<str>,
+    },
+    Class {
+        package: Arc<str>,
+        name: Arc<str>,
+    },
+    Method {
+        package: Arc<str>,
+        class: Arc<str>,
+        name: Arc<str>,
+        signature: Option<Arc<str>>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Discrepancy {
+    pub scope: Scope,
+    pub rate: Rate,
+    pub declared: f64,
+    pub computed: f64,
+}
+
+impl Discrepancy {
+    pub fn difference(&self) -> f64 {
+        (self.declared - self.computed).abs()
+    }
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rate = match self.rate {
+            Rate::Line => "line rate",
+            Rate::Branch => "branch rate",
+        };
+
+        match &self.scope {
+            Scope::Coverage => write!(f, "coverage")?,
+            Scope::Package { name } => write!(f, "package `{name}`")?,
+            Scope::Class { package, name } => write!(f, "class `{name}` (package `{package}`)")?,
+            Scope::Method {
+                package,
+                class,
+                name,
+                signature,
+            } => write!(
+                f,
+                "method `{name}{}` (class `{class}`, package `{package}`)",
+                signature.as_deref().unwrap_or_default()
+            )?,
+        }
+
+        write!(
+            f,
+            ": declared {rate} {} does not match computed {rate} {}",
+            self.declared, self.computed
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VerificationReport {
+    pub tolerance: f64,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl VerificationReport {
+    pub fn is_ok(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+}
+
+struct Verifier {
+    tolerance: f64,
+    discrepancies: Vec<Discrepancy>,
+}
+
+impl Verifier {
+    fn check(
+        &mut self,
+        scope: impl Fn() -> Scope,
+        line_rate: f64,
+        branch_rate: f64,
+        computed: ComputedRates,
+    ) {
+        let checks = [
+            (Rate::Line, line_rate, computed.line_rate),
+            (Rate::Branch, branch_rate, computed.branch_rate),
+        ];
+
+        for (rate, declared, computed) in checks {
+            let Some(computed) = computed else {
+                continue;
+            };
+
+            if (declared - computed).abs() > self.tolerance {
+                self.discrepancies.push(Discrepancy {
+                    scope: scope(),
+                    rate,
+                    declared,
+                    computed,
+                });
+            }
+        }
+    }
+}
+
+impl Coverage {
+    /// Verify that the declared line and branch rates match the rates computed
+    /// from the line data, at the coverage, package, class and method levels.
+    ///
+    /// Rates are considered equal if they differ by at most `tolerance`.
+    pub fn verify(&self, tolerance: f64) -> VerificationReport {
+        let mut verifier = Verifier {
+            tolerance,
+            discrepancies: Vec::new(),
+        };
+
+        verifier.check(
+            || Scope::Coverage,
+            self.line_rate,
+            self.branch_rate,
+            self.computed_rates(),
+        );
+
+        for package in &self.packages {
+            verifier.check(
+                || Scope::Package {
+                    name: package.name.clone(),
+                },
+                package.line_rate,
+                package.branch_rate,
+                package.computed_rates(self.min_hits),
+            );
+
+            for class in &package.classes {
+                verifier.check(
+                    || Scope::Class {
+                        package: package.name.clone(),
+                        name: class.name.clone(),
+                    },
+                    class.line_rate,
+                    class.branch_rate,
+                    ComputedRates::from_lines(&class.lines, self.min_hits),
+                );
+
+                for method in &class.methods {
+                    verifier.check(
+                        || Scope::Method {
+                            package: package.name.clone(),
+                            class: class.name.clone(),
+                            name: method.name.clone(),
+                            signature: method.signature.clone(),
+                        },
+                        method.line_rate,
+                        method.branch_rate,
+                        ComputedRates::from_lines(&method.lines, self.min_hits),
+                    );
+                }
+            }
+        }
+
+        VerificationReport {
+            tolerance,
+            discrepancies: verifier.discrepancies,
+        }
+    }
+}
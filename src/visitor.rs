@@ -0,0 +1,33 @@
+use crate::{Class, Line, Method, Package};
+
+/// Callbacks for [`Parser::parse_with_visitor`](crate::Parser::parse_with_visitor).
+///
+/// Each class is handed to the visitor once it has been parsed completely,
+/// and is dropped afterwards, so only a single class is kept in memory at any
+/// time. All methods do nothing by default.
+pub trait CoverageVisitor {
+    /// Called once the `<package>` element has ended. Its classes have
+    /// already been passed to [`on_class`](Self::on_class), so
+    /// `package.classes` is empty.
+    fn on_package(&mut self, package: &Package) {
+        let _ = package;
+    }
+
+    /// Called for every class. `package` is the package that contains it,
+    /// without any classes.
+    fn on_class(&mut self, package: &Package, class: &Class) {
+        let _ = (package, class);
+    }
+
+    /// Called for every method of a class, after
+    /// [`on_class`](Self::on_class).
+    fn on_method(&mut self, class: &Class, method: &Method) {
+        let _ = (class, method);
+    }
+
+    /// Called for every line of a class (not of its methods), after
+    /// [`on_class`](Self::on_class).
+    fn on_line(&mut self, class: &Class, line: &Line) {
+        let _ = (class, line);
+    }
+}
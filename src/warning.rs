@@ -0,0 +1,83 @@
+use std::fmt;
+
+use crate::Coverage;
+
+/// A problem in the input that the parser recovered from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// An element that is not part of the Cobertura format was skipped,
+    /// together with everything inside it.
+    SkippedElement { name: String },
+    /// Text where none was expected was skipped.
+    SkippedText,
+    /// An attribute that is not part of the Cobertura format, kept in the
+    /// `extra_attributes` of the element.
+    UnknownAttribute { element: String, attribute: String },
+    /// A rate that was normalized according to the
+    /// [`Profile`](crate::Profile), e.g. a percentage or a rounding error
+    /// just above `1`.
+    RateNormalized {
+        element: String,
+        attribute: String,
+        read: f64,
+        value: f64,
+    },
+    /// A rate outside of `[0, 1]`, kept as it is.
+    RateOutOfRange {
+        element: String,
+        attribute: String,
+        value: f64,
+    },
+    /// A class lists the same line more than once.
+    DuplicateLine { class: String, line: usize },
+    /// The timestamp of the report is too large to be a date, and was
+    /// ignored.
+    TimestampOverflow,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SkippedElement { name } => write!(f, "skipped unknown element <{name}>"),
+            Self::SkippedText => write!(f, "skipped unexpected text"),
+            Self::UnknownAttribute { element, attribute } => {
+                write!(f, "unknown attribute `{attribute}` on <{element}>")
+            }
+            Self::RateNormalized {
+                element,
+                attribute,
+                read,
+                value,
+            } => write!(f, "read {attribute} {read} of <{element}> as {value}"),
+            Self::RateOutOfRange {
+                element,
+                attribute,
+                value,
+            } => write!(f, "{attribute} {value} of <{element}> is not in [0, 1]"),
+            Self::DuplicateLine { class, line } => {
+                write!(f, "class {class} lists line {line} more than once")
+            }
+            Self::TimestampOverflow => write!(f, "ignored timestamp that is too large"),
+        }
+    }
+}
+
+/// The warnings collected while parsing a single document.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseReport {
+    pub warnings: Vec<Warning>,
+}
+
+/// A parsed report, with the problems in its input that the parser recovered
+/// from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseOutcome {
+    pub coverage: Coverage,
+    pub warnings: Vec<Warning>,
+}
+
+impl ParseReport {
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
@@ -0,0 +1,35 @@
+//! Bindings for parsing and rendering reports from JavaScript.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{HtmlGenerator, HtmlOptions, MemorySink, Parser};
+
+fn parse(xml: &str) -> Result<crate::Coverage, JsError> {
+    Parser::new()
+        .parse_str(xml)
+        .map_err(|e| JsError::new(&format!("Failed to parse coverage: {e:?}")))
+}
+
+/// Parse a Cobertura report, and summarize it as text.
+#[wasm_bindgen]
+pub fn summarize(xml: &str) -> Result<String, JsError> {
+    Ok(parse(xml)?.statistics(10).to_string())
+}
+
+/// Parse a Cobertura report, and render it as a single self-contained HTML
+/// document.
+#[wasm_bindgen(js_name = renderHtml)]
+pub fn render_html(xml: &str) -> Result<String, JsError> {
+    let coverage = parse(xml)?;
+    let options = HtmlOptions {
+        single_file: true,
+        ..Default::default()
+    };
+
+    let mut sink = MemorySink::new();
+    HtmlGenerator::generate_to(&coverage, &options, &mut sink)
+        .map_err(|e| JsError::new(&e.to_string()))?;
+
+    let index = sink.files.remove("index.html").unwrap_or_default();
+    String::from_utf8(index).map_err(|e| JsError::new(&e.to_string()))
+}
@@ -0,0 +1,190 @@
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use crate::{ComputedRates, Coverage, RankingFilter, ReportGenerator, ReportSink, RoundingPolicy};
+
+/// How [`Coverage::worst_covered`] orders files and classes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WorstOrder {
+    /// The most uncovered lines first, and the lowest line coverage among
+    /// those with as many.
+    #[default]
+    Uncovered,
+    /// The lowest line coverage first, and the most uncovered lines among
+    /// those with the same coverage.
+    Rate,
+}
+
+/// Which files or classes [`Coverage::worst_covered`] lists.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorstCoveredOptions {
+    /// The number of files or classes to list.
+    pub count: usize,
+    /// List classes instead of files.
+    pub classes: bool,
+    pub order: WorstOrder,
+    /// Which files or classes to leave out, by their number of lines.
+    pub ranking: RankingFilter,
+}
+
+impl Default for WorstCoveredOptions {
+    fn default() -> Self {
+        Self {
+            count: 10,
+            classes: false,
+            order: WorstOrder::default(),
+            ranking: RankingFilter::default(),
+        }
+    }
+}
+
+/// A file or class of [`Coverage::worst_covered`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorstCovered {
+    /// The path of the file, or the name of the class.
+    pub name: String,
+    /// The file of a class, `None` for files.
+    pub file: Option<PathBuf>,
+    pub rates: ComputedRates,
+}
+
+impl WorstCovered {
+    /// The number of uncovered lines.
+    pub fn uncovered(&self) -> usize {
+        self.rates.lines_valid - self.rates.lines_covered
+    }
+}
+
+impl Coverage {
+    /// The worst covered files or classes, as chosen by `options`. Only
+    /// files and classes with uncovered lines are listed.
+    pub fn worst_covered(&self, options: &WorstCoveredOptions) -> Vec<WorstCovered> {
+        let mut worst: Vec<_> = if options.classes {
+            self.classes()
+                .map(|class| WorstCovered {
+                    name: class.name.to_string(),
+                    file: Some(class.file_name.to_path_buf()),
+                    rates: class.computed_rates(self.min_hits),
+                })
+                .filter(|class| options.ranking.includes(class.rates.lines_valid))
+                .collect()
+        } else {
+            self.ranked_files(&options.ranking)
+                .into_iter()
+                .map(|file| WorstCovered {
+                    name: file.path.to_string_lossy().into_owned(),
+                    file: None,
+                    rates: file.rates(),
+                })
+                .collect()
+        };
+        worst.retain(|w| w.uncovered() > 0);
+
+        let rate = |w: &WorstCovered| w.rates.line_rate.unwrap_or(1.0);
+        worst.sort_by(|a, b| {
+            let by_uncovered = b.uncovered().cmp(&a.uncovered());
+            let by_rate = rate(a).total_cmp(&rate(b));
+            match options.order {
+                WorstOrder::Uncovered => by_uncovered.then(by_rate),
+                WorstOrder::Rate => by_rate.then(by_uncovered),
+            }
+            .then_with(|| a.name.cmp(&b.name))
+        });
+        worst.truncate(options.count);
+        worst
+    }
+}
+
+/// Lists the worst covered files or classes of a report.
+pub struct WorstCoveredGenerator;
+
+impl WorstCoveredGenerator {
+    /// Write one line per file or class, with its number of uncovered lines
+    /// and its line coverage.
+    pub fn write_text(
+        coverage: &Coverage,
+        options: &WorstCoveredOptions,
+        rounding: &RoundingPolicy,
+        mut out: impl Write,
+    ) -> io::Result<()> {
+        for worst in coverage.worst_covered(options) {
+            let rate = worst
+                .rates
+                .line_rate
+                .map_or("-".to_string(), |r| rounding.format_rate(r));
+            write!(out, "{:>8}  {rate:>7}  {}", worst.uncovered(), worst.name)?;
+            match &worst.file {
+                Some(file) => writeln!(out, " ({})", file.display())?,
+                None => writeln!(out)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write a Markdown table of the files or classes.
+    pub fn write_markdown(
+        coverage: &Coverage,
+        options: &WorstCoveredOptions,
+        rounding: &RoundingPolicy,
+        mut out: impl Write,
+    ) -> io::Result<()> {
+        let worst = coverage.worst_covered(options);
+
+        if options.classes {
+            writeln!(out, "### Least covered classes\n")?;
+            writeln!(out, "| Class | File | Uncovered lines | Line coverage |")?;
+            writeln!(out, "|---|---|---:|---:|")?;
+        } else {
+            writeln!(out, "### Least covered files\n")?;
+            writeln!(out, "| File | Uncovered lines | Line coverage |")?;
+            writeln!(out, "|---|---:|---:|")?;
+        }
+
+        for worst in worst {
+            let rate = worst
+                .rates
+                .line_rate
+                .map_or("-".to_string(), |r| rounding.format_rate(r));
+            match &worst.file {
+                Some(file) => writeln!(
+                    out,
+                    "| `{}` | `{}` | {} | {rate} |",
+                    worst.name,
+                    file.display(),
+                    worst.uncovered()
+                )?,
+                None => writeln!(out, "| `{}` | {} | {rate} |", worst.name, worst.uncovered())?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The table of [`WorstCoveredGenerator::write_markdown`] as a
+/// [`ReportGenerator`] named `WorstCovered`, written to `worst-covered.md`.
+#[derive(Debug, Clone, Default)]
+pub struct WorstCoveredReport {
+    pub options: WorstCoveredOptions,
+    pub rounding: RoundingPolicy,
+}
+
+impl ReportGenerator for WorstCoveredReport {
+    fn name(&self) -> &str {
+        "WorstCovered"
+    }
+
+    fn generate(&self, coverage: &Coverage, sink: &mut dyn ReportSink) -> io::Result<()> {
+        let mut markdown = Vec::new();
+        WorstCoveredGenerator::write_markdown(
+            coverage,
+            &self.options,
+            &self.rounding,
+            &mut markdown,
+        )?;
+        sink.write("worst-covered.md", &markdown)
+    }
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "parser")]
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use cobertura_rs::{CoberturaGenerator, Coverage, Parser, ParserError, Profile, SourceResolver};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/coverage_py")
+        .join(name)
+}
+
+fn parse(name: &str, profile: Profile) -> Result<Coverage, ParserError> {
+    Parser::with_profile(profile).parse_path(fixture(name))
+}
+
+#[test]
+fn reads_branch_lines_without_conditions() {
+    let coverage = parse("branches.xml", Profile::CoveragePy).unwrap();
+
+    let class = coverage.class_for_file("mypkg/core.py").unwrap();
+    assert_eq!(&*class.name, "core.py");
+
+    let line = class.lines.iter().find(|l| l.number == 4).unwrap();
+    assert!(line.branch);
+    assert!(line.conditions.is_empty());
+    assert_eq!(line.branch_counts(), Some((1, 2)));
+    assert_eq!(line.missing_branches(), Some("6"));
+
+    let rates = coverage.computed_rates();
+    assert_eq!((rates.branches_covered, rates.branches_valid), (3, 4));
+    assert!(coverage.verify(1e-4).is_ok());
+}
+
+#[test]
+fn keeps_all_source_roots() {
+    let coverage = parse("branches.xml", Profile::CoveragePy).unwrap();
+
+    let roots: Vec<_> = coverage.sources.iter().map(|s| s.path.as_path()).collect();
+    assert_eq!(
+        roots,
+        [
+            Path::new("/home/runner/work/project/src"),
+            Path::new("/home/runner/work/project/lib"),
+        ]
+    );
+}
+
+#[test]
+fn resolves_file_names_relative_to_any_source() {
+    let base = std::env::temp_dir().join(format!("coverage-py-sources-{}", std::process::id()));
+    let src = base.join("src");
+    let lib = base.join("lib");
+    std::fs::create_dir_all(&src).unwrap();
+    std::fs::create_dir_all(lib.join("mypkg")).unwrap();
+    std::fs::write(src.join("main.py"), "import mypkg\n").unwrap();
+    std::fs::write(lib.join("mypkg/core.py"), "def f():\n    pass\n").unwrap();
+
+    let resolver = SourceResolver::new([src.clone(), lib.clone()]);
+    let main = resolver.resolve(Path::new("main.py"));
+    let core = resolver.resolve(Path::new("mypkg/core.py"));
+    std::fs::remove_dir_all(&base).unwrap();
+
+    assert_eq!(main, Some(src.join("main.py")));
+    assert_eq!(core, Some(lib.join("mypkg/core.py")));
+}
+
+#[test]
+fn complexity_is_always_zero() {
+    let coverage = parse("branches.xml", Profile::CoveragePy).unwrap();
+
+    assert_eq!(coverage.complexity, 0.0);
+    assert!(coverage.packages.iter().all(|p| p.complexity == 0.0));
+    assert!(coverage.classes().all(|c| c.complexity == 0.0));
+}
+
+#[test]
+fn timestamp_is_in_milliseconds() {
+    let coverage = parse("branches.xml", Profile::CoveragePy).unwrap();
+
+    let expected = SystemTime::UNIX_EPOCH + Duration::from_millis(1_706_781_234_567);
+    assert_eq!(coverage.generated_at(), Some(expected));
+}
+
+#[test]
+fn reads_reports_without_branch_totals() {
+    assert!(parse("lines_only.xml", Profile::Generic).is_err());
+
+    let coverage = parse("lines_only.xml", Profile::CoveragePy).unwrap();
+    assert_eq!((coverage.lines_covered, coverage.lines_valid), (3, 4));
+    assert_eq!(coverage.branches_valid, 0);
+    assert!(coverage.verify(1e-4).is_ok());
+}
+
+#[test]
+fn round_trips() {
+    for name in ["branches.xml", "lines_only.xml"] {
+        let input = std::fs::read(fixture(name)).unwrap();
+        // `check_round_trip` parses with the generic profile, so check the
+        // written report, which has all required attributes.
+        let coverage = Parser::with_profile(Profile::CoveragePy)
+            .parse_bytes(&input)
+            .unwrap();
+        let mut written = Vec::new();
+        CoberturaGenerator::write_round_trip(&coverage, &mut written).unwrap();
+
+        assert_eq!(
+            CoberturaGenerator::check_round_trip(&written).unwrap(),
+            None,
+            "{name}"
+        );
+    }
+}
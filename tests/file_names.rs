@@ -0,0 +1,46 @@
+#![cfg(feature = "html")]
+
+use cobertura_rs::FileNameIndex;
+
+#[test]
+fn replaces_unsafe_characters() {
+    let mut index = FileNameIndex::new("html");
+    assert_eq!(index.insert("Foo<T>"), "Foo_T_.html");
+    assert_eq!(index.insert("a/b"), "a_b.html");
+    assert_eq!(index.insert("..hidden."), "_.hidden.html");
+}
+
+#[test]
+fn escapes_reserved_device_names() {
+    let mut index = FileNameIndex::new("html");
+    assert_eq!(index.insert("con"), "_con.html");
+    assert_eq!(index.insert("Con.Foo"), "_Con.Foo.html");
+    assert_eq!(index.insert("LPT1.a.b"), "_LPT1.a.b.html");
+    assert_eq!(index.insert("console.Foo"), "console.Foo.html");
+}
+
+#[test]
+fn disambiguates_case_only_collisions() {
+    let mut index = FileNameIndex::new("html");
+    let first = index.insert("Foo").to_string();
+    let second = index.insert("foo").to_string();
+
+    assert_eq!(first, "Foo.html");
+    assert_ne!(first.to_ascii_lowercase(), second.to_ascii_lowercase());
+    assert!(second.starts_with("foo-"), "{second}");
+    assert_eq!(index.get("Foo"), Some("Foo.html"));
+    assert_eq!(index.get("foo"), Some(second.as_str()));
+}
+
+#[test]
+fn is_deterministic() {
+    let names = ["a/b", "a_b", "A_B", "a:b"];
+    let build = || {
+        let mut index = FileNameIndex::new("html");
+        for name in names {
+            index.insert(name);
+        }
+        index.entries().to_vec()
+    };
+    assert_eq!(build(), build());
+}
@@ -0,0 +1,44 @@
+use std::path::Path;
+
+use cobertura_rs::CoverageFilter;
+
+fn ignored(filter: &str, path: &str) -> bool {
+    CoverageFilter::parse(filter).is_ignored(Path::new(path))
+}
+
+#[test]
+fn names_match_at_any_depth() {
+    assert!(ignored("*.generated.cs", "src/Models/User.generated.cs"));
+    assert!(ignored("build", "src/build/out.rs"));
+    assert!(!ignored("build/", "src/build"));
+    assert!(ignored("build/", "src/build/out.rs"));
+}
+
+#[test]
+fn paths_match_from_the_root() {
+    assert!(ignored("/tests/", "tests/parser.rs"));
+    assert!(!ignored("/tests/", "src/app/tests/parser.rs"));
+    assert!(ignored("src/gen", "src/gen/a.rs"));
+    assert!(!ignored("src/gen", "lib/src/gen/a.rs"));
+    assert!(ignored("**/src/gen", "lib/src/gen/a.rs"));
+}
+
+#[test]
+fn files_in_ignored_directories_stay_ignored() {
+    assert!(ignored(
+        "vendor/\n!vendor/patched/",
+        "vendor/patched/lib.rs"
+    ));
+
+    let filter = "vendor/*\n!vendor/patched/";
+    assert!(ignored(filter, "vendor/other/lib.rs"));
+    assert!(ignored(filter, "vendor/lib.rs"));
+    assert!(!ignored(filter, "vendor/patched/lib.rs"));
+}
+
+#[test]
+fn last_matching_pattern_decides() {
+    let filter = "*.rs\n!main.rs";
+    assert!(ignored(filter, "src/lib.rs"));
+    assert!(!ignored(filter, "src/main.rs"));
+}
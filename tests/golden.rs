@@ -0,0 +1,192 @@
+#![cfg(feature = "parser")]
+
+//! Parses real-world reports from `tests/fixtures/golden` and compares the
+//! model to the snapshot next to each of them. Run with `UPDATE_GOLDEN=1` to
+//! write the snapshots instead, and review the diff.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use cobertura_rs::{CoberturaGenerator, Coverage, Line, Parser, Profile};
+
+// Each fixture, with the profile of the tool that wrote it.
+const FIXTURES: &[(&str, Profile)] = &[
+    ("coverage_py", Profile::CoveragePy),
+    ("coverlet", Profile::Coverlet),
+    ("gcovr", Profile::Gcovr),
+    // Converted from JaCoCo's format by `cover2cover.py`.
+    ("jacoco", Profile::Generic),
+    ("kover", Profile::Generic),
+];
+
+fn fixture(name: &str, extension: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/golden")
+        .join(name)
+        .with_extension(extension)
+}
+
+fn parse(name: &str, profile: Profile) -> Coverage {
+    Parser::with_profile(profile)
+        .parse_path(fixture(name, "xml"))
+        .unwrap_or_else(|e| panic!("{name}: {e:?}"))
+}
+
+fn extra(attributes: &BTreeMap<String, String>) -> String {
+    attributes
+        .iter()
+        .map(|(name, value)| format!(" {name}={value:?}"))
+        .collect()
+}
+
+fn dump_lines(out: &mut String, lines: &[Line], indent: &str) {
+    for line in lines {
+        write!(out, "{indent}line {} hits={}", line.number, line.hits).unwrap();
+        if line.branch {
+            write!(out, " branch").unwrap();
+        }
+        if let Some(coverage) = &line.condition_coverage {
+            write!(out, " {coverage:?}").unwrap();
+        }
+        writeln!(out, "{}", extra(&line.extra_attributes)).unwrap();
+
+        for condition in &line.conditions {
+            writeln!(
+                out,
+                "{indent}  condition {} {} {}{}",
+                condition.number,
+                condition.r#type,
+                condition.coverage,
+                extra(&condition.extra_attributes)
+            )
+            .unwrap();
+        }
+    }
+}
+
+// A readable dump of the whole model, so that snapshot diffs show exactly
+// what changed.
+fn dump(coverage: &Coverage) -> String {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "coverage version={:?} timestamp={} lines={}/{} branches={}/{} line-rate={} branch-rate={} complexity={}{}",
+        coverage.version,
+        coverage.timestamp,
+        coverage.lines_covered,
+        coverage.lines_valid,
+        coverage.branches_covered,
+        coverage.branches_valid,
+        coverage.line_rate,
+        coverage.branch_rate,
+        coverage.complexity,
+        extra(&coverage.extra_attributes)
+    )
+    .unwrap();
+
+    for source in &coverage.sources {
+        writeln!(out, "source {}", source.path.display()).unwrap();
+    }
+
+    for package in &coverage.packages {
+        writeln!(
+            out,
+            "package {:?} line-rate={} branch-rate={} complexity={}{}",
+            package.name,
+            package.line_rate,
+            package.branch_rate,
+            package.complexity,
+            extra(&package.extra_attributes)
+        )
+        .unwrap();
+
+        for class in &package.classes {
+            writeln!(
+                out,
+                "  class {:?} file={:?} line-rate={} branch-rate={} complexity={}{}",
+                class.name,
+                class.file_name,
+                class.line_rate,
+                class.branch_rate,
+                class.complexity,
+                extra(&class.extra_attributes)
+            )
+            .unwrap();
+
+            for method in &class.methods {
+                writeln!(
+                    out,
+                    "    method {:?} signature={:?} line-rate={} branch-rate={} complexity={}{}",
+                    method.name,
+                    method.signature,
+                    method.line_rate,
+                    method.branch_rate,
+                    method.complexity,
+                    extra(&method.extra_attributes)
+                )
+                .unwrap();
+                dump_lines(&mut out, &method.lines, "      ");
+            }
+
+            dump_lines(&mut out, &class.lines, "    ");
+        }
+    }
+
+    out
+}
+
+#[test]
+fn models_match_snapshots() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut mismatches = Vec::new();
+
+    for (name, profile) in FIXTURES {
+        let actual = dump(&parse(name, *profile));
+        let path = fixture(name, "snap");
+
+        if update {
+            std::fs::write(&path, &actual).unwrap();
+            continue;
+        }
+
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+            .replace("\r\n", "\n");
+        if actual != expected {
+            eprintln!("--- {}\n+++ actual\n{actual}", path.display());
+            mismatches.push(*name);
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "models differ from their snapshots: {mismatches:?} (run with UPDATE_GOLDEN=1 to update them)"
+    );
+}
+
+#[test]
+fn declared_rates_match() {
+    for (name, profile) in FIXTURES {
+        let coverage = parse(name, *profile);
+        assert!(coverage.verify(1e-4).is_ok(), "{name}");
+    }
+}
+
+#[test]
+fn round_trip() {
+    for (name, profile) in FIXTURES {
+        let coverage = parse(name, *profile);
+        let mut written = Vec::new();
+        CoberturaGenerator::write_round_trip(&coverage, &mut written).unwrap();
+
+        assert_eq!(
+            CoberturaGenerator::check_round_trip(&written).unwrap(),
+            None,
+            "{name}"
+        );
+    }
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "parser")]
+
+use std::path::Path;
+
+use cobertura_rs::{CancellationToken, Parser, ParserError, Progress};
+
+fn basic() -> String {
+    std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("basic.xml")).unwrap()
+}
+
+#[test]
+fn reuse_after_truncated_document() {
+    let input = basic();
+    let truncated = &input[..input.find("</class>").unwrap()];
+
+    let mut parser = Parser::new();
+    assert!(matches!(
+        parser.parse_str(truncated),
+        Err(ParserError::UnexpectedEof)
+    ));
+
+    let coverage = parser.parse_str(&input).unwrap();
+    assert_eq!(coverage, Parser::new().parse_str(&input).unwrap());
+}
+
+// The progress is reported when `</class>` is read, which here is inside an
+// unknown element, so that the parser is still skipping it when a callback
+// cancels it.
+const UNKNOWN_ELEMENT: &str = r#"<?xml version="1.0" ?>
+<coverage line-rate="1" branch-rate="0" lines-covered="0" lines-valid="0" branches-covered="0" branches-valid="0" complexity="0" version="1" timestamp="1">
+	<sources/>
+	<packages>
+		<package name="app" line-rate="1" branch-rate="0" complexity="0">
+			<unknown>
+				<class name="App" filename="app.rs" line-rate="1" branch-rate="0" complexity="0"/>
+				<class name="Other" filename="other.rs" line-rate="1" branch-rate="0" complexity="0">
+				</class>
+				<more/>
+			</unknown>
+		</package>
+	</packages>
+</coverage>"#;
+
+#[test]
+fn reuse_after_cancelling_inside_unknown_element() {
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+
+    let mut parser = Parser::new();
+    parser.set_skip_unknown(true);
+    parser.set_progress(
+        Progress::new()
+            .with_callback(move |_| cancel.cancel())
+            .with_cancellation(token),
+    );
+    assert!(matches!(
+        parser.parse_str(UNKNOWN_ELEMENT),
+        Err(ParserError::Cancelled)
+    ));
+
+    parser.set_progress(Progress::new());
+    let input = basic();
+    assert_eq!(
+        parser.parse_str(&input).unwrap(),
+        Parser::new().parse_str(&input).unwrap()
+    );
+}
+
+#[test]
+fn reuse_after_invalid_xml_inside_unknown_element() {
+    let invalid = UNKNOWN_ELEMENT.replace("<more/>", "<more></less>");
+
+    let mut parser = Parser::new();
+    parser.set_skip_unknown(true);
+    assert!(matches!(
+        parser.parse_str(&invalid),
+        Err(ParserError::Xml(_))
+    ));
+
+    let input = basic();
+    assert_eq!(
+        parser.parse_str(&input).unwrap(),
+        Parser::new().parse_str(&input).unwrap()
+    );
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "parser")]
+
+//! Combines the reports in `tests/fixtures/set_operations` and compares the
+//! results to the expected reports next to them.
+
+use std::path::Path;
+
+use cobertura_rs::{BranchStrategy, Coverage, HitsStrategy, MergeOptions, Parser};
+
+const TOLERANCE: f64 = 1e-9;
+
+fn parse(name: &str) -> Coverage {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/set_operations")
+        .join(name);
+    Parser::new()
+        .parse_path(&path)
+        .unwrap_or_else(|e| panic!("{name}: {e}"))
+}
+
+fn assert_matches(actual: &Coverage, expected: &str) {
+    let expected = parse(expected);
+    assert!(
+        actual.structurally_equal(&expected, TOLERANCE),
+        "{actual:#?}\ndoes not match\n{expected:#?}"
+    );
+}
+
+#[test]
+fn intersect() {
+    let mut coverage = parse("base.xml");
+    coverage.intersect(&parse("other.xml"));
+    assert_matches(&coverage, "intersect.xml");
+}
+
+#[test]
+fn subtract() {
+    let mut coverage = parse("base.xml");
+    coverage.subtract(&parse("other.xml"));
+    assert_matches(&coverage, "subtract.xml");
+}
+
+#[test]
+fn merge_sum() {
+    let mut coverage = parse("base.xml");
+    coverage.merge(parse("other.xml"));
+    assert_matches(&coverage, "merge_sum.xml");
+}
+
+#[test]
+fn merge_max() {
+    let mut coverage = parse("base.xml");
+    let options = MergeOptions {
+        hits: HitsStrategy::Max,
+        branches: BranchStrategy::Best,
+    };
+    coverage.merge_with(parse("other.xml"), &options);
+    assert_matches(&coverage, "merge_max.xml");
+}
+
+#[test]
+fn merge_or() {
+    let mut coverage = parse("base.xml");
+    let options = MergeOptions {
+        hits: HitsStrategy::Or,
+        branches: BranchStrategy::Sum,
+    };
+    coverage.merge_with(parse("other.xml"), &options);
+    assert_matches(&coverage, "merge_or.xml");
+}
+
+#[test]
+fn apply_min_hits() {
+    let mut coverage = parse("base.xml");
+    // Line 4 was hit, but only twice.
+    assert_eq!(coverage.apply_min_hits(3), 1);
+
+    let mut expected = parse("min_hits.xml");
+    expected.min_hits = 3;
+    // The fixture has the same hits as `base.xml`, as they are kept so that
+    // the report can still be merged.
+    assert!(coverage.structurally_equal(&expected, TOLERANCE));
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "parser")]
+
+use cobertura_rs::{DtdValidator, DtdViolation, DtdViolationKind};
+use quick_xml::Reader;
+
+const INVALID: &str = r#"<coverage line-rate="1" branch-rate="1" lines-covered="1" lines-valid="1" branches-covered="0" branches-valid="0" complexity="0" version="1">
+<packages>
+<package name="app" line-rate="1" branch-rate="1">
+<classes>
+<class name="App" filename="app.rs" line-rate="1" branch-rate="1" complexity="0">
+<methods>
+<method name="run" signature="()" line-rate="1" branch-rate="1">
+<lines><line number="1" hits="1"/></lines>
+</method>
+</methods>
+<unknown/>
+<line number="2"/>
+</class>
+</classes>
+</package>
+</packages>
+</coverage>"#;
+
+fn violation(path: &str, position: u64, kind: DtdViolationKind) -> DtdViolation {
+    DtdViolation {
+        path: path.to_string(),
+        position,
+        kind,
+    }
+}
+
+fn missing_attribute(attribute: &str) -> DtdViolationKind {
+    DtdViolationKind::MissingAttribute {
+        attribute: attribute.to_string(),
+    }
+}
+
+fn unexpected_element(element: &str) -> DtdViolationKind {
+    DtdViolationKind::UnexpectedElement {
+        element: element.to_string(),
+    }
+}
+
+#[test]
+fn reports_every_violation() {
+    let violations = DtdValidator::validate(&mut Reader::from_str(INVALID));
+
+    let class = "/coverage/packages/package/classes/class";
+    assert_eq!(
+        violations,
+        [
+            violation("/coverage", 0, missing_attribute("timestamp")),
+            violation(
+                "/coverage/packages/package",
+                153,
+                missing_attribute("complexity")
+            ),
+            violation(
+                &format!("{class}/methods/method"),
+                306,
+                missing_attribute("complexity")
+            ),
+            violation(class, 435, unexpected_element("unknown")),
+            violation(class, 446, unexpected_element("line")),
+            violation(&format!("{class}/line"), 446, missing_attribute("hits")),
+            violation(
+                class,
+                465,
+                DtdViolationKind::MissingElement {
+                    element: "lines".to_string()
+                }
+            ),
+        ]
+    );
+}